@@ -0,0 +1,135 @@
+//! Two players pressing a jump button, each on their own copy of the game, with their input exchanged over the network. Both copies should see the same presses on the same frames. Run with `cargo run --example net-loopback`, or `cargo run --example net-loopback -- --udp` to send the input over UDP on localhost instead of in memory.
+
+use asterism::control::{InputWrapper, KeyboardControl, Values};
+use asterism::network::{LoopbackTransport, NetworkControl, Transport, UdpTransport};
+
+const FRAMES: u64 = 12;
+const DELAY: u64 = 2;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct Jump;
+
+/// keys held on each frame, standing in for a keyboard
+struct Script {
+    this_frame: Vec<char>,
+    last_frame: Vec<char>,
+}
+
+impl InputWrapper for Script {
+    type KeyCode = char;
+    type InputHelper = Vec<char>;
+
+    fn new() -> Self {
+        Self {
+            this_frame: Vec::new(),
+            last_frame: Vec::new(),
+        }
+    }
+
+    fn clear(&mut self) {
+        self.last_frame = std::mem::take(&mut self.this_frame);
+    }
+
+    fn update_held(&mut self, key: &char, held: &Vec<char>) -> bool {
+        if held.contains(key) {
+            self.this_frame.push(*key);
+            return true;
+        }
+        false
+    }
+
+    fn is_pressed(&self, key: &char, _held: &Vec<char>) -> bool {
+        self.this_frame.contains(key) && !self.last_frame.contains(key)
+    }
+
+    fn is_released(&self, key: &char, held: &Vec<char>) -> bool {
+        self.last_frame.contains(key) && !held.contains(key)
+    }
+}
+
+struct Peer<T: Transport> {
+    name: &'static str,
+    control: KeyboardControl<Jump, Script>,
+    network: NetworkControl<T>,
+    /// the frames this player holds space on
+    presses: std::ops::Range<u64>,
+    /// both players' values on each frame that ran
+    seen: Vec<(Values, Values)>,
+}
+
+impl<T: Transport> Peer<T> {
+    /// Player 1 is set 0 and player 2 is set 1 on both peers.
+    fn new(
+        name: &'static str,
+        transport: T,
+        local_set: usize,
+        presses: std::ops::Range<u64>,
+    ) -> Self {
+        let mut control = KeyboardControl::new();
+        control.add_key_map(0, ' ', Jump, true);
+        control.add_key_map(1, ' ', Jump, true);
+        Self {
+            name,
+            control,
+            network: NetworkControl::new(transport, local_set, 1 - local_set, DELAY),
+            presses,
+            seen: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, tick: u64) {
+        let held = if self.presses.contains(&tick) {
+            vec![' ']
+        } else {
+            Vec::new()
+        };
+        let frame = self.network.frame();
+        if self.network.update(&mut self.control, &held).unwrap() {
+            let values = (self.control.values[0][0], self.control.values[1][0]);
+            println!(
+                "{} frame {}: player 1 {:?}, player 2 {:?}",
+                self.name, frame, values.0, values.1
+            );
+            self.seen.push(values);
+        } else {
+            println!("{} frame {}: waiting", self.name, frame);
+        }
+    }
+}
+
+fn run<T: Transport>(a: T, b: T) {
+    let mut alice = Peer::new("alice", a, 0, 2..5);
+    let mut bob = Peer::new("bob", b, 1, 6..8);
+    let mut tick = 0;
+    while alice.seen.len() < FRAMES as usize || bob.seen.len() < FRAMES as usize {
+        alice.update(tick);
+        bob.update(tick);
+        tick += 1;
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    let same = alice.seen[..FRAMES as usize]
+        .iter()
+        .zip(bob.seen[..FRAMES as usize].iter())
+        .all(|(a, b)| {
+            a.0.value == b.0.value
+                && a.0.changed_by == b.0.changed_by
+                && a.1.value == b.1.value
+                && a.1.changed_by == b.1.changed_by
+        });
+    assert!(same, "alice and bob saw different input");
+    println!("alice and bob saw the same input for {} frames", FRAMES);
+}
+
+fn main() {
+    if std::env::args().any(|arg| arg == "--udp") {
+        let a = UdpTransport::bind("127.0.0.1:0").unwrap();
+        let b = UdpTransport::bind("127.0.0.1:0").unwrap();
+        a.connect(b.local_addr().unwrap()).unwrap();
+        b.connect(a.local_addr().unwrap()).unwrap();
+        run(a, b);
+    } else {
+        let (a, b) = LoopbackTransport::pair();
+        run(a, b);
+    }
+}
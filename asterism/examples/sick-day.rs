@@ -0,0 +1,31 @@
+//! sick day blues: a text game about being sick, played in the terminal. Run with `cargo run --example sick-day`.
+
+use asterism::dialogue::Dialogue;
+use asterism::resources::QueuedResources;
+
+#[derive(Ord, PartialOrd, Eq, PartialEq, Copy, Clone, Debug)]
+enum PoolID {
+    Energy,
+    Sleeps,
+}
+
+fn main() {
+    let mut dialogue = Dialogue::parse(include_str!("sick-day.yarn"), |name| match name {
+        "energy" => Some(PoolID::Energy),
+        "sleeps" => Some(PoolID::Sleeps),
+        _ => None,
+    })
+    .unwrap_or_else(|err| panic!("error reading dialogue: {}", err));
+
+    let mut resources = QueuedResources::<PoolID, i16>::new();
+    resources.items.insert(PoolID::Energy, (49, -100, 200));
+    resources.items.insert(PoolID::Sleeps, (0, 0, 3));
+
+    // take the jump out of the starting node
+    dialogue.update(&resources);
+
+    let stdin = std::io::stdin();
+    dialogue
+        .run_terminal(&mut resources, stdin.lock(), std::io::stdout())
+        .unwrap();
+}
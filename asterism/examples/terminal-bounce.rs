@@ -0,0 +1,129 @@
+//! A ball bouncing around a box, drawn in the terminal, with a count of how many times it's bounced. Run with `cargo run --example terminal-bounce`, or `cargo run --example terminal-bounce -- --snapshot` to print only the last frame without colors, like a golden-file test would see it.
+
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+use asterism::bindings::{Anchor, Bindings};
+use asterism::collision::AabbCollision;
+use asterism::physics::PointPhysics;
+use asterism::resources::{QueuedResources, Transaction};
+use asterism::terminal::{Color, Glyph, TerminalRenderer};
+use glam::Vec2;
+
+const WIDTH: f32 = 40.0;
+const HEIGHT: f32 = 16.0;
+const FRAMES: usize = 120;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Body {
+    Wall,
+    Ball,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Pool {
+    Bounces,
+}
+
+struct World {
+    physics: PointPhysics,
+    collision: AabbCollision<Body>,
+    bindings: Bindings,
+    resources: QueuedResources<Pool, u32>,
+}
+
+impl World {
+    fn new() -> Self {
+        let mut collision = AabbCollision::new();
+        let walls = [
+            (Vec2::ZERO, Vec2::new(WIDTH, 1.0)),
+            (Vec2::new(0.0, HEIGHT - 1.0), Vec2::new(WIDTH, 1.0)),
+            (Vec2::ZERO, Vec2::new(1.0, HEIGHT)),
+            (Vec2::new(WIDTH - 1.0, 0.0), Vec2::new(1.0, HEIGHT)),
+        ];
+        for &(pos, size) in walls.iter() {
+            collision.add_entity_as_xywh(pos, size, Vec2::ZERO, true, true, Body::Wall);
+        }
+        let ball_pos = Vec2::new(5.0, 3.0);
+        let ball_vel = Vec2::new(0.7, 0.4);
+        collision.add_entity_as_xywh(ball_pos, Vec2::ONE, ball_vel, true, false, Body::Ball);
+
+        let mut physics = PointPhysics::new();
+        physics.add_physics_entity(ball_pos, ball_vel, Vec2::ZERO);
+
+        let mut bindings = Bindings::new(Anchor::TopLeft);
+        bindings.bind(0, 4);
+
+        let mut resources = QueuedResources::new();
+        resources.items.insert(Pool::Bounces, (0, 0, u32::MAX));
+
+        Self {
+            physics,
+            collision,
+            bindings,
+            resources,
+        }
+    }
+
+    fn update(&mut self) {
+        self.physics.update();
+        self.bindings
+            .physics_to_collision(&self.physics, &mut self.collision);
+        self.collision.update();
+        self.bindings
+            .collision_to_physics(&self.collision, &mut self.physics);
+
+        // walls are fixed, so the ball is always first in its contacts. Walls touch each other at the corners too
+        for contact in self.collision.contacts.iter() {
+            if self.collision.get_ids(contact) != (Body::Ball, Body::Wall) {
+                continue;
+            }
+            let vel = &mut self.physics.velocities[0];
+            if contact.displacement.x != 0.0 {
+                vel.x = -vel.x;
+            }
+            if contact.displacement.y != 0.0 {
+                vel.y = -vel.y;
+            }
+            self.resources
+                .transactions
+                .push((Pool::Bounces, Transaction::Change(1)));
+        }
+        self.resources.update();
+    }
+
+    fn draw(&self, terminal: &mut TerminalRenderer) {
+        terminal.clear();
+        terminal.draw_aabbs(&self.collision, |_, meta| match meta.id {
+            Body::Wall => Some(Glyph::new('#', Some(Color::Blue))),
+            Body::Ball => Some(Glyph::new('o', Some(Color::Yellow))),
+        });
+        terminal.draw_resources(2, HEIGHT as i32, &self.resources, None);
+    }
+}
+
+fn main() -> io::Result<()> {
+    let snapshot = std::env::args().any(|arg| arg == "--snapshot");
+    let mut world = World::new();
+    let mut terminal = TerminalRenderer::new(WIDTH as usize, HEIGHT as usize + 1);
+
+    if snapshot {
+        for _ in 0..FRAMES {
+            world.update();
+        }
+        world.draw(&mut terminal);
+        println!("{}", terminal);
+        return Ok(());
+    }
+
+    let mut out = io::stdout();
+    TerminalRenderer::begin(&mut out)?;
+    for _ in 0..FRAMES {
+        world.update();
+        world.draw(&mut terminal);
+        terminal.present(&mut out)?;
+        thread::sleep(Duration::from_millis(50));
+    }
+    TerminalRenderer::end(&mut out)
+}
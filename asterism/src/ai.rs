@@ -0,0 +1,251 @@
+//! # AI control
+//!
+//! Computer players that press buttons instead of moving things around directly. An [AiControl] fills in sets of mappings in a [KeyboardControl] from [Policy]s instead of the keyboard, so the rest of the game sees the same [ControlEvent][crate::control::ControlEvent]s either way, and a set can be switched between a person and the computer without changing any rules.
+//!
+//! Policies decide which actions to hold each frame from a context the game passes in, usually its condition tables, so they can read what other logics output last frame. [FollowTarget] moves toward a point, [BehaviorTree] picks actions with sequences, selectors, and conditions, and [UtilityPolicy] follows whichever of several policies scores highest. Closures are policies too.
+
+use glam::Vec2;
+
+use crate::control::{InputWrapper, KeyboardControl, Values};
+
+/// Decides what a computer player does.
+pub trait Policy<ID, Context> {
+    /// the actions to hold this frame
+    fn decide(&mut self, context: &Context) -> Vec<ID>;
+}
+
+impl<ID, Context, F> Policy<ID, Context> for F
+where
+    F: FnMut(&Context) -> Vec<ID>,
+{
+    fn decide(&mut self, context: &Context) -> Vec<ID> {
+        self(context)
+    }
+}
+
+/// finds a point in the game, or `None` if it isn't there
+pub type Locator<Context> = Box<dyn Fn(&Context) -> Option<Vec2>>;
+
+/// Holds the actions that move something toward a target, ex. a paddle toward the ball.
+pub struct FollowTarget<ID, Context> {
+    /// where the thing being moved is
+    pub position: Locator<Context>,
+    /// where it's moving to
+    pub target: Locator<Context>,
+    /// the action moving toward -x, if it can move horizontally
+    pub left: Option<ID>,
+    /// the action moving toward +x
+    pub right: Option<ID>,
+    /// the action moving toward -y, if it can move vertically
+    pub up: Option<ID>,
+    /// the action moving toward +y
+    pub down: Option<ID>,
+    /// how close on each axis counts as there, so it doesn't jitter back and forth over the target
+    pub dead_zone: f32,
+}
+
+impl<ID, Context> FollowTarget<ID, Context> {
+    /// Doesn't move on either axis until the actions for it are set.
+    pub fn new(position: Locator<Context>, target: Locator<Context>) -> Self {
+        Self {
+            position,
+            target,
+            left: None,
+            right: None,
+            up: None,
+            down: None,
+            dead_zone: 1.0,
+        }
+    }
+}
+
+impl<ID: Copy, Context> Policy<ID, Context> for FollowTarget<ID, Context> {
+    fn decide(&mut self, context: &Context) -> Vec<ID> {
+        let (position, target) = match ((self.position)(context), (self.target)(context)) {
+            (Some(position), Some(target)) => (position, target),
+            _ => return Vec::new(),
+        };
+        let offset = target - position;
+        let mut held = Vec::new();
+        if offset.x < -self.dead_zone {
+            held.extend(self.left);
+        } else if offset.x > self.dead_zone {
+            held.extend(self.right);
+        }
+        if offset.y < -self.dead_zone {
+            held.extend(self.up);
+        } else if offset.y > self.dead_zone {
+            held.extend(self.down);
+        }
+        held
+    }
+}
+
+/// A behavior tree, run from the root every frame. Each node succeeds or fails, and the actions held are the ones from the [BehaviorTree::Hold] and [BehaviorTree::Policy] nodes that succeeded. Actions from a branch that fails aren't held.
+pub enum BehaviorTree<ID, Context> {
+    /// runs its children in order until one fails. Succeeds if they all succeed.
+    Sequence(Vec<BehaviorTree<ID, Context>>),
+    /// runs its children in order until one succeeds. Fails if they all fail.
+    Selector(Vec<BehaviorTree<ID, Context>>),
+    /// succeeds if the closure returns true
+    Condition(Box<dyn Fn(&Context) -> bool>),
+    /// succeeds if its child fails and fails if it succeeds
+    Not(Box<BehaviorTree<ID, Context>>),
+    /// holds the actions and succeeds
+    Hold(Vec<ID>),
+    /// holds whatever the policy decides. Succeeds if it decides to hold anything.
+    Policy(Box<dyn Policy<ID, Context>>),
+}
+
+impl<ID: Copy, Context> BehaviorTree<ID, Context> {
+    /// runs the tree, adding the actions to hold to `held`. Returns if it succeeded.
+    pub fn run(&mut self, context: &Context, held: &mut Vec<ID>) -> bool {
+        let len = held.len();
+        let succeeded = match self {
+            BehaviorTree::Sequence(children) => {
+                children.iter_mut().all(|child| child.run(context, held))
+            }
+            BehaviorTree::Selector(children) => {
+                children.iter_mut().any(|child| child.run(context, held))
+            }
+            BehaviorTree::Condition(condition) => condition(context),
+            BehaviorTree::Not(child) => !child.run(context, &mut Vec::new()),
+            BehaviorTree::Hold(actions) => {
+                held.extend(actions.iter().copied());
+                true
+            }
+            BehaviorTree::Policy(policy) => {
+                let actions = policy.decide(context);
+                let decided = !actions.is_empty();
+                held.extend(actions);
+                decided
+            }
+        };
+        if !succeeded {
+            held.truncate(len);
+        }
+        succeeded
+    }
+}
+
+impl<ID: Copy, Context> Policy<ID, Context> for BehaviorTree<ID, Context> {
+    fn decide(&mut self, context: &Context) -> Vec<ID> {
+        let mut held = Vec::new();
+        self.run(context, &mut held);
+        held
+    }
+}
+
+/// how much a computer player wants to do something right now
+pub type Score<Context> = Box<dyn Fn(&Context) -> f32>;
+
+/// something a computer player could do, and how much it wants to do it
+pub type UtilityOption<ID, Context> = (Score<Context>, Box<dyn Policy<ID, Context>>);
+
+/// Scores every option each frame and follows the policy of the one that scores highest. Ties go to the option added first.
+pub struct UtilityPolicy<ID, Context> {
+    pub options: Vec<UtilityOption<ID, Context>>,
+}
+
+impl<ID, Context> UtilityPolicy<ID, Context> {
+    pub fn new() -> Self {
+        Self {
+            options: Vec::new(),
+        }
+    }
+
+    pub fn add_option(
+        &mut self,
+        score: Score<Context>,
+        policy: impl Policy<ID, Context> + 'static,
+    ) -> &mut Self {
+        self.options.push((score, Box::new(policy)));
+        self
+    }
+}
+
+impl<ID, Context> Policy<ID, Context> for UtilityPolicy<ID, Context> {
+    fn decide(&mut self, context: &Context) -> Vec<ID> {
+        let mut best: Option<(f32, usize)> = None;
+        for (i, (score, _)) in self.options.iter().enumerate() {
+            let score = score(context);
+            // NaN never wins
+            if best.map_or(!score.is_nan(), |(best, _)| score > best) {
+                best = Some((score, i));
+            }
+        }
+        match best {
+            Some((_, i)) => self.options[i].1.decide(context),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// a set of mappings played by the computer
+struct AiSet<ID, Context> {
+    set: usize,
+    policy: Box<dyn Policy<ID, Context>>,
+    /// the values the policy left the set with, since the keyboard changes them in between
+    last: Vec<Values>,
+}
+
+/// Plays sets of mappings in a [KeyboardControl] with [Policy]s.
+pub struct AiControl<ID, Context> {
+    sets: Vec<AiSet<ID, Context>>,
+}
+
+impl<ID: Copy + Eq + Ord, Context> AiControl<ID, Context> {
+    pub fn new() -> Self {
+        Self { sets: Vec::new() }
+    }
+
+    /// Has the computer play a set of mappings, replacing whatever policy was already playing it.
+    pub fn control_set(&mut self, set: usize, policy: impl Policy<ID, Context> + 'static) {
+        self.release_set(set);
+        self.sets.push(AiSet {
+            set,
+            policy: Box::new(policy),
+            last: Vec::new(),
+        });
+    }
+
+    /// Gives a set of mappings back to the keyboard. Returns false if the computer wasn't playing it.
+    pub fn release_set(&mut self, set: usize) -> bool {
+        let len = self.sets.len();
+        self.sets.retain(|ai| ai.set != set);
+        self.sets.len() != len
+    }
+
+    pub fn controls_set(&self, set: usize) -> bool {
+        self.sets.iter().any(|ai| ai.set == set)
+    }
+
+    /// Fills in the sets the computer plays with the actions their policies hold. Call after updating the control logic, so what the keyboard did to those sets is replaced.
+    pub fn update<Wrapper: InputWrapper>(
+        &mut self,
+        control: &mut KeyboardControl<ID, Wrapper>,
+        context: &Context,
+    ) {
+        for ai in self.sets.iter_mut() {
+            let held = ai.policy.decide(context);
+            let mapping = match control.mapping.get(ai.set) {
+                Some(mapping) => mapping,
+                None => continue,
+            };
+            let values: Vec<Values> = mapping
+                .iter()
+                .enumerate()
+                .map(|(i, action)| {
+                    let value = if held.contains(&action.id) { 1.0 } else { 0.0 };
+                    let last = ai.last.get(i).map_or(0.0, |last| last.value);
+                    Values {
+                        changed_by: value - last,
+                        value,
+                    }
+                })
+                .collect();
+            control.replay_set(ai.set, &values);
+            ai.last = control.values[ai.set].clone();
+        }
+    }
+}
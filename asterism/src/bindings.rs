@@ -0,0 +1,145 @@
+//! # Bindings
+//!
+//! Synthesis between [PointPhysics] and [AabbCollision]: a physics body and a collision body that are the same thing in the game, ex. a ball, are bound together once, and then their positions and velocities are copied from one logic to the other after each update. Physics moves bodies, collision pushes them back out of whatever they hit, and the bindings keep the two agreeing.
+//!
+//! Both logics keep their bodies in `Vec`s, so adding or removing a body shifts the indices of everything after it. Tell the bindings about it with the `*_inserted` and `*_removed` methods instead of recomputing indices by hand.
+
+use serde::{Deserialize, Serialize};
+
+use crate::collision::AabbCollision;
+use crate::physics::PointPhysics;
+
+/// which point of a collision box a physics position refers to
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Anchor {
+    /// the physics position is the top left corner of the box, like [CollisionReaction::SetPos][crate::collision::CollisionReaction::SetPos]
+    TopLeft,
+    /// the physics position is the center of the box
+    Center,
+}
+
+/// a physics body and the collision body it's bound to, by index
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct Binding {
+    pub physics: usize,
+    pub collision: usize,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bindings {
+    pub bindings: Vec<Binding>,
+    pub anchor: Anchor,
+    /// if velocities are copied along with positions
+    pub sync_vel: bool,
+}
+
+impl Bindings {
+    pub fn new(anchor: Anchor) -> Self {
+        Self {
+            bindings: Vec::new(),
+            anchor,
+            sync_vel: true,
+        }
+    }
+
+    /// binds a physics body to a collision body, replacing any bindings either of them already had
+    pub fn bind(&mut self, physics: usize, collision: usize) {
+        self.bindings
+            .retain(|binding| binding.physics != physics && binding.collision != collision);
+        self.bindings.push(Binding { physics, collision });
+    }
+
+    pub fn unbind(&mut self, physics: usize) {
+        self.bindings.retain(|binding| binding.physics != physics);
+    }
+
+    /// the collision body bound to a physics body
+    pub fn get_collision(&self, physics: usize) -> Option<usize> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.physics == physics)
+            .map(|binding| binding.collision)
+    }
+
+    /// the physics body bound to a collision body
+    pub fn get_physics(&self, collision: usize) -> Option<usize> {
+        self.bindings
+            .iter()
+            .find(|binding| binding.collision == collision)
+            .map(|binding| binding.physics)
+    }
+
+    /// call after inserting a physics body at `idx`, so bodies after it stay bound
+    pub fn physics_inserted(&mut self, idx: usize) {
+        for binding in self.bindings.iter_mut() {
+            if binding.physics >= idx {
+                binding.physics += 1;
+            }
+        }
+    }
+
+    /// call after removing the physics body at `idx`. Removes its binding.
+    pub fn physics_removed(&mut self, idx: usize) {
+        self.bindings.retain(|binding| binding.physics != idx);
+        for binding in self.bindings.iter_mut() {
+            if binding.physics > idx {
+                binding.physics -= 1;
+            }
+        }
+    }
+
+    /// call after inserting a collision body at `idx`, so bodies after it stay bound
+    pub fn collision_inserted(&mut self, idx: usize) {
+        for binding in self.bindings.iter_mut() {
+            if binding.collision >= idx {
+                binding.collision += 1;
+            }
+        }
+    }
+
+    /// call after removing the collision body at `idx`. Removes its binding.
+    pub fn collision_removed(&mut self, idx: usize) {
+        self.bindings.retain(|binding| binding.collision != idx);
+        for binding in self.bindings.iter_mut() {
+            if binding.collision > idx {
+                binding.collision -= 1;
+            }
+        }
+    }
+
+    /// Moves bound collision bodies to where physics put them. Call after updating physics and before updating collision.
+    pub fn physics_to_collision<ID: Copy + Eq>(
+        &self,
+        physics: &PointPhysics,
+        collision: &mut AabbCollision<ID>,
+    ) {
+        for binding in self.bindings.iter() {
+            let pos = physics.positions[binding.physics];
+            collision.centers[binding.collision] = match self.anchor {
+                Anchor::TopLeft => pos + collision.half_sizes[binding.collision],
+                Anchor::Center => pos,
+            };
+            if self.sync_vel {
+                collision.velocities[binding.collision] = physics.velocities[binding.physics];
+            }
+        }
+    }
+
+    /// Moves bound physics bodies to where collision left them. Call after updating collision.
+    pub fn collision_to_physics<ID: Copy + Eq>(
+        &self,
+        collision: &AabbCollision<ID>,
+        physics: &mut PointPhysics,
+    ) {
+        for binding in self.bindings.iter() {
+            let center = collision.centers[binding.collision];
+            physics.positions[binding.physics] = match self.anchor {
+                Anchor::TopLeft => center - collision.half_sizes[binding.collision],
+                Anchor::Center => center,
+            };
+            if self.sync_vel {
+                physics.velocities[binding.physics] = collision.velocities[binding.collision];
+            }
+        }
+    }
+}
@@ -5,10 +5,11 @@
 //! Note: Collision is hard and may be broken.
 
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
-use macroquad::math::Vec2;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
 
 /// Information for each contact. If the entities at the indices `i` and `j` are both unfixed or both fixed, then `i < j`. If one is unfixed and the other is fixed, `i` will be the index of the unfixed entity.
-#[derive(PartialEq, Copy, Clone)]
+#[derive(PartialEq, Copy, Clone, Serialize, Deserialize)]
 pub struct Contact {
     /// The index of the first contact in `centers`, `half_sizes`, `velocities`, `metadata`, and `displacements`.
     pub i: usize,
@@ -32,7 +33,7 @@ impl Contact {
 }
 
 /// Metadata of each collision entity.
-#[derive(Default, Clone, Copy)]
+#[derive(Default, Clone, Copy, Serialize, Deserialize)]
 pub struct CollisionData<ID: Copy + Eq> {
     /// True if the entity is solid, i.e. can stop other entities.
     ///
@@ -46,6 +47,7 @@ pub struct CollisionData<ID: Copy + Eq> {
 }
 
 /// A collision logic for axis-aligned bounding boxes.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct AabbCollision<ID: Copy + Eq> {
     /// A vector of the centers of the bounding box.
     pub centers: Vec<Vec2>,
@@ -217,7 +219,7 @@ impl<ID: Copy + Eq> AabbCollision<ID> {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AabbColData<ID: Copy + Eq> {
     pub center: Vec2,
     pub half_size: Vec2,
@@ -233,6 +235,11 @@ impl<ID: Copy + Eq> Logic for AabbCollision<ID> {
 
     type Ident = usize;
     type IdentData = AabbColData<ID>;
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        AabbCollision::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
@@ -294,7 +301,7 @@ impl<ID: Copy + Eq> Logic for AabbCollision<ID> {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum CollisionReaction<ID> {
     /// sets the position, _not_ the center
     SetPos(usize, Vec2),
@@ -2,8 +2,11 @@
 //!
 //! Control logics communicate that different entities are controlled by different inputs at different times. They map button inputs, AI intentions, network socket messages, etc onto high-level game actions.
 //!
+//! Besides the keyboard, a set of mappings can be filled in by a remote player over the network with [NetworkControl][crate::network::NetworkControl], or by a computer player with [AiControl][crate::ai::AiControl].
+//!
 //! We're currently trying to consider analog as well as digital inputs, but we haven't implemented controller support, so some of these fields don't really make sense yet.
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
+use serde::{Deserialize, Serialize};
 
 /// Information for a key/button press.
 trait Input {
@@ -14,6 +17,13 @@ trait Input {
 /// A keyboard control logic.
 ///
 /// A Wrapper is a helper struct that helps keep track of keypress information that libraries may not but we do want. This is currently only necessary if you're using `winit_input_helper`.
+///
+/// Saving a control logic saves its mappings and values, but not its input wrapper, which starts over when loaded.
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "ID: Serialize, Wrapper::KeyCode: KeyName",
+    deserialize = "ID: Deserialize<'de>, Wrapper::KeyCode: KeyName"
+))]
 pub struct KeyboardControl<ID, Wrapper>
 where
     ID: Copy + Eq + Ord,
@@ -24,6 +34,7 @@ where
     /// The values for each keypress in the sets described above.
     pub values: Vec<Vec<Values>>,
     /// An input wrapper
+    #[serde(skip, default = "Wrapper::new")]
     input_wrapper: Wrapper,
 }
 
@@ -39,6 +50,11 @@ where
     type Ident = usize;
     /// values are only included for reference; modifying values will not change the data in the logic. Not super sure about this type...
     type IdentData = Vec<Action<ID, Wrapper::KeyCode>>;
+    type Context = Wrapper::InputHelper;
+
+    fn update(&mut self, events: &Self::Context) {
+        KeyboardControl::update(self, events);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
@@ -127,6 +143,34 @@ where
         }
     }
 
+    /// the values of every action this frame, for replaying later with [KeyboardControl::replay]
+    pub fn record(&self) -> InputFrame {
+        self.values.clone()
+    }
+
+    /// Updates with recorded values instead of reading input. Actions that aren't valid are reset, like in [KeyboardControl::update], and actions with no recorded values are left alone.
+    pub fn replay(&mut self, input: &InputFrame) {
+        self.input_wrapper.clear();
+        for (set, input) in input.iter().enumerate() {
+            self.replay_set(set, input);
+        }
+    }
+
+    /// Like [KeyboardControl::replay], but for one set of mappings, ex. one filled in from somewhere other than the keyboard.
+    pub fn replay_set(&mut self, set: <Self as Logic>::Ident, input: &[Values]) {
+        if let (Some(map), Some(map_values)) = (self.mapping.get(set), self.values.get_mut(set)) {
+            for ((action, values), input_values) in
+                map.iter().zip(map_values.iter_mut()).zip(input.iter())
+            {
+                *values = if action.is_valid {
+                    *input_values
+                } else {
+                    Values::new()
+                };
+            }
+        }
+    }
+
     /// Returns the [Values] for the first action in the mapping with the given ID.
     pub fn get_action(&self, id: ID) -> Option<Values> {
         for (i, ..) in self.mapping.iter().enumerate() {
@@ -163,12 +207,33 @@ where
 }
 
 /// A keyboard input.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct KeyInput<KeyCode: Copy> {
     /// The keycode that the input is tracking.
     keycode: KeyCode,
 }
 
+/// Keycodes that can be saved. Input libraries' keycodes don't implement `Serialize`, so they're saved by name instead.
+pub trait KeyName: Copy {
+    fn key_name(&self) -> String;
+    fn from_key_name(name: &str) -> Option<Self>;
+}
+
+impl<KeyCode: KeyName> Serialize for KeyInput<KeyCode> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.keycode.key_name())
+    }
+}
+
+impl<'de, KeyCode: KeyName> Deserialize<'de> for KeyInput<KeyCode> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        let keycode = KeyCode::from_key_name(&name)
+            .ok_or_else(|| serde::de::Error::custom(format!("unknown key {:?}", name)))?;
+        Ok(Self { keycode })
+    }
+}
+
 impl<KeyCode: Copy> Input for KeyInput<KeyCode> {
     /// Minimum value for a keypress is 0.0.
     fn min(&self) -> f32 {
@@ -180,7 +245,7 @@ impl<KeyCode: Copy> Input for KeyInput<KeyCode> {
     }
 }
 
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Serialize, Deserialize)]
 pub enum InputType {
     /// an input that can be a range of values (joystick, etc)
     Analog,
@@ -188,8 +253,11 @@ pub enum InputType {
     Digital,
 }
 
+/// The [Values] of every action in a [KeyboardControl] for one frame, in the same sets and order as its mappings. Replaying a recorded frame reproduces its control events exactly, wherever the input originally came from.
+pub type InputFrame = Vec<Vec<Values>>;
+
 /// Information about the player's input related to one action.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 pub struct Values {
     /// How much the value of the input was changed last frame.
     pub changed_by: f32,
@@ -207,7 +275,11 @@ impl Values {
 }
 
 /// Information for an action and the input it's attached to.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "ID: Serialize, KeyCode: KeyName",
+    deserialize = "ID: Deserialize<'de>, KeyCode: KeyName"
+))]
 pub struct Action<ID, KeyCode: Copy> {
     pub id: ID,
     /// The input's keycode and min/max.
@@ -233,7 +305,7 @@ impl<ID, KeyCode: Copy> Action<ID, KeyCode> {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum ControlReaction<ID: Copy + Eq, KeyCode: Copy + Eq> {
     /// add a key to the set with the given id, and if it's valid or not.
     AddKeyToSet(usize, ID, KeyCode, bool),
@@ -243,7 +315,7 @@ pub enum ControlReaction<ID: Copy + Eq, KeyCode: Copy + Eq> {
 
 impl<ID: Copy + Eq, KeyCode: Copy + Eq> Reaction for ControlReaction<ID, KeyCode> {}
 
-#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ControlEvent<ID> {
     pub event_type: ControlEventType,
     pub set: usize,
@@ -257,7 +329,7 @@ impl<ID> Event for ControlEvent<ID> {
     }
 }
 
-#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Ord, PartialOrd, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ControlEventType {
     KeyPressed,
     KeyReleased,
@@ -350,10 +422,162 @@ pub trait InputWrapper {
     fn is_released(&self, key: &Self::KeyCode, events: &Self::InputHelper) -> bool;
 }
 
+#[cfg(feature = "macroquad-render")]
 use macroquad::prelude::{is_key_down, is_key_pressed, is_key_released, KeyCode as MqKeyCode};
+/// keycodes by their names in macroquad, ex. `"Space"` or `"LeftShift"`
+#[cfg(feature = "macroquad-render")]
+macro_rules! mq_key_names {
+    ($($key:ident),* $(,)?) => {
+        impl MacroquadInputWrapper {
+            /// every keycode, in the order macroquad declares them
+            pub const KEYCODES: &'static [MqKeyCode] = &[$(MqKeyCode::$key),*];
+        }
+
+        impl KeyName for MqKeyCode {
+            fn key_name(&self) -> String {
+                format!("{:?}", self)
+            }
+
+            fn from_key_name(name: &str) -> Option<Self> {
+                match name {
+                    $(stringify!($key) => Some(MqKeyCode::$key),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+#[cfg(feature = "macroquad-render")]
+mq_key_names!(
+    Space,
+    Apostrophe,
+    Comma,
+    Minus,
+    Period,
+    Slash,
+    Key0,
+    Key1,
+    Key2,
+    Key3,
+    Key4,
+    Key5,
+    Key6,
+    Key7,
+    Key8,
+    Key9,
+    Semicolon,
+    Equal,
+    A,
+    B,
+    C,
+    D,
+    E,
+    F,
+    G,
+    H,
+    I,
+    J,
+    K,
+    L,
+    M,
+    N,
+    O,
+    P,
+    Q,
+    R,
+    S,
+    T,
+    U,
+    V,
+    W,
+    X,
+    Y,
+    Z,
+    LeftBracket,
+    Backslash,
+    RightBracket,
+    GraveAccent,
+    World1,
+    World2,
+    Escape,
+    Enter,
+    Tab,
+    Backspace,
+    Insert,
+    Delete,
+    Right,
+    Left,
+    Down,
+    Up,
+    PageUp,
+    PageDown,
+    Home,
+    End,
+    CapsLock,
+    ScrollLock,
+    NumLock,
+    PrintScreen,
+    Pause,
+    F1,
+    F2,
+    F3,
+    F4,
+    F5,
+    F6,
+    F7,
+    F8,
+    F9,
+    F10,
+    F11,
+    F12,
+    F13,
+    F14,
+    F15,
+    F16,
+    F17,
+    F18,
+    F19,
+    F20,
+    F21,
+    F22,
+    F23,
+    F24,
+    F25,
+    Kp0,
+    Kp1,
+    Kp2,
+    Kp3,
+    Kp4,
+    Kp5,
+    Kp6,
+    Kp7,
+    Kp8,
+    Kp9,
+    KpDecimal,
+    KpDivide,
+    KpMultiply,
+    KpSubtract,
+    KpAdd,
+    KpEnter,
+    KpEqual,
+    LeftShift,
+    LeftControl,
+    LeftAlt,
+    LeftSuper,
+    RightShift,
+    RightControl,
+    RightAlt,
+    RightSuper,
+    Menu,
+    Unknown,
+);
+
 /// Macroquad's input handler already correctly handles the information we need, so this is just a wrapper for their functions
+#[cfg(feature = "macroquad-render")]
 pub struct MacroquadInputWrapper {}
 
+#[cfg(feature = "macroquad-render")]
 impl InputWrapper for MacroquadInputWrapper {
     type KeyCode = MqKeyCode;
     type InputHelper = ();
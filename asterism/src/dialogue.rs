@@ -0,0 +1,657 @@
+//! # Dialogue
+//!
+//! A linking logic for branching conversations and text games. Each node of the dialogue has some text and a list of choices, and each choice is a labelled edge to another node. Choices can depend on the values of resource pools and change them when they're picked, which is how a dialogue remembers what's happened so far.
+//!
+//! The graph itself lives in a [GraphedLinking], so it can be queried, mutated, and exported like any other linking logic.
+//!
+//! ## Text format
+//!
+//! Dialogues can be written in a Yarn-like text format and read with [Dialogue::parse]:
+//!
+//! ```text
+//! title: start
+//! ---
+//! you wake up. energy: {energy}
+//! [[go back to sleep|sleep]] <<if energy < 50>> <<set energy += 10>>
+//! [[get up|kitchen]]
+//! <<jump faint>> <<if energy <= 0>>
+//! ===
+//! ```
+//!
+//! - A node starts with `title: <name>` and `---`, and ends with `===`. The first node is where the dialogue starts.
+//! - `[[text|node]]` is a choice leading to the node with the title `node`. `[[node]]` uses the title as the text.
+//! - `<<jump node>>` is a jump: a choice without text that's taken as soon as the node is entered, if its conditions pass.
+//! - `<<if pool op value>>` after a choice or jump is a condition, where `op` is one of `<`, `<=`, `==`, `!=`, `>=`, `>`. Every condition has to pass for the choice to be available.
+//! - `<<set pool += value>>`, `<<set pool -= value>>`, and `<<set pool = value>>` (or `<<set pool to value>>`) after a choice or jump are effects, applied when it's taken. `-=` only works for values that can be negated (see [ResourceValue::negate]).
+//! - `{pool}` in text is replaced with the value of the pool.
+//! - Pool names can start with a `$`, like in Yarn. Lines starting with `//` are comments, and every other line is text.
+
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display};
+use std::io::{BufRead, Write};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::linking::{GraphedLinking, LinkingReaction};
+use crate::resources::{QueuedResources, ResourceValue, Transaction};
+use crate::{graph::EdgeData, tables::OutputTable, Event, EventType, Logic, Reaction};
+
+/// A dialogue tree (or graph) of text and choices. See the [module documentation][self] for an overview.
+pub struct Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue,
+{
+    /// the dialogue graph. Its one graph's node IDs are indices into `nodes`.
+    pub linking: GraphedLinking<usize>,
+    pub nodes: Vec<DialogueNode<PoolID, Value>>,
+    /// pools that can be shown in text with `{name}`
+    pub pool_names: BTreeMap<String, PoolID>,
+    /// effects of the choices and jumps taken since they were last applied. See [Dialogue::apply_effects].
+    pub queued_effects: Vec<(PoolID, Transaction<Value>)>,
+    /// choice picked since the last update, by its index in the current node's choices
+    chosen: Option<usize>,
+    /// events from the last update
+    events: Vec<DialogueEvent>,
+    /// events from reactions since the last update, reported after the next one
+    queued_events: Vec<DialogueEvent>,
+}
+
+/// A node of a [Dialogue].
+#[derive(Clone)]
+pub struct DialogueNode<PoolID, Value: ResourceValue> {
+    pub title: String,
+    /// lines of text, shown when the node is entered
+    pub text: Vec<String>,
+    pub choices: Vec<Choice<PoolID, Value>>,
+    /// choices without text, taken as soon as the node is entered if their conditions pass. The first one that passes is taken.
+    pub jumps: Vec<Choice<PoolID, Value>>,
+}
+
+/// An edge of a [Dialogue].
+#[derive(Clone)]
+pub struct Choice<PoolID, Value: ResourceValue> {
+    pub text: String,
+    /// index of the node it leads to
+    pub to: usize,
+    pub conditions: Vec<Condition<PoolID, Value>>,
+    pub effects: Vec<(PoolID, Transaction<Value>)>,
+}
+
+/// A comparison between the value of a pool and a constant.
+#[derive(Clone, Copy)]
+pub struct Condition<PoolID, Value> {
+    pub pool: PoolID,
+    pub comparison: Comparison,
+    pub value: Value,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Comparison {
+    Less,
+    LessOrEqual,
+    Equal,
+    NotEqual,
+    GreaterOrEqual,
+    Greater,
+}
+
+impl<PoolID: Copy + Ord + Debug, Value: ResourceValue> Condition<PoolID, Value> {
+    /// If the pool's value compares to `value` the right way. Values that can't be compared (see [ResourceValue::compare]) only pass [Comparison::NotEqual]. Panics if the pool doesn't exist.
+    pub fn check(&self, resources: &QueuedResources<PoolID, Value>) -> bool {
+        let (current, ..) = resources.get_ident_data(self.pool);
+        let ordering = current.compare(&self.value);
+        match self.comparison {
+            Comparison::Less => ordering == Some(Ordering::Less),
+            Comparison::LessOrEqual => {
+                matches!(ordering, Some(Ordering::Less) | Some(Ordering::Equal))
+            }
+            Comparison::Equal => ordering == Some(Ordering::Equal),
+            Comparison::NotEqual => ordering != Some(Ordering::Equal),
+            Comparison::GreaterOrEqual => {
+                matches!(ordering, Some(Ordering::Greater) | Some(Ordering::Equal))
+            }
+            Comparison::Greater => ordering == Some(Ordering::Greater),
+        }
+    }
+}
+
+impl<PoolID: Copy + Ord + Debug, Value: ResourceValue> Choice<PoolID, Value> {
+    /// if every condition passes
+    pub fn available(&self, resources: &QueuedResources<PoolID, Value>) -> bool {
+        self.conditions
+            .iter()
+            .all(|condition| condition.check(resources))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct DialogueEvent {
+    pub node: usize,
+    pub event_type: DialogueEventType,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum DialogueEventType {
+    /// the node was entered
+    Entered,
+    /// the choice with the given index was picked in the node
+    Chose(usize),
+    /// the jump with the given index was taken out of the node
+    Jumped(usize),
+}
+
+impl EventType for DialogueEventType {}
+
+impl Event for DialogueEvent {
+    type EventType = DialogueEventType;
+
+    fn get_type(&self) -> &Self::EventType {
+        &self.event_type
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DialogueReaction {
+    /// Picks the choice with the given index in the current node's choices, queueing its effects. The dialogue moves along it on the next update. This doesn't check the choice's conditions, so only pick choices from [Dialogue::available_choices].
+    Choose(usize),
+    /// moves straight to the given node, without a choice
+    GoTo(usize),
+}
+
+impl Reaction for DialogueReaction {}
+
+impl<PoolID, Value> Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue,
+{
+    pub fn new() -> Self {
+        let mut linking = GraphedLinking::new();
+        linking.add_graph(0, []);
+        Self {
+            linking,
+            nodes: Vec::new(),
+            pool_names: BTreeMap::new(),
+            queued_effects: Vec::new(),
+            chosen: None,
+            events: Vec::new(),
+            queued_events: Vec::new(),
+        }
+    }
+
+    /// Adds a node without any choices, returning its index.
+    pub fn add_node(&mut self, title: &str, text: Vec<String>) -> usize {
+        let idx = self.nodes.len();
+        self.nodes.push(DialogueNode {
+            title: title.to_string(),
+            text,
+            choices: Vec::new(),
+            jumps: Vec::new(),
+        });
+        self.linking.graphs[0].add_node(idx);
+        idx
+    }
+
+    /// Adds a choice to a node, along with an edge labelled with the choice's text.
+    pub fn add_choice(&mut self, from: usize, choice: Choice<PoolID, Value>) {
+        let graph = &mut self.linking.graphs[0].graph;
+        if !graph.edge_exists(from, choice.to) {
+            let mut data = EdgeData::new();
+            data.label = Some(choice.text.clone());
+            graph.add_edge_with_data(from, choice.to, data);
+        }
+        self.nodes[from].choices.push(choice);
+    }
+
+    /// Adds a jump to a node, along with an unlabelled edge.
+    pub fn add_jump(&mut self, from: usize, jump: Choice<PoolID, Value>) {
+        let graph = &mut self.linking.graphs[0].graph;
+        if !graph.edge_exists(from, jump.to) {
+            graph.add_edge(from, jump.to);
+        }
+        self.nodes[from].jumps.push(jump);
+    }
+
+    /// index of the node the dialogue is on
+    pub fn current_node(&self) -> usize {
+        self.linking.graphs[0].current_node
+    }
+
+    /// The current node's text, with `{pool}`s replaced by the pools' values.
+    pub fn current_text(&self, resources: &QueuedResources<PoolID, Value>) -> Vec<String>
+    where
+        Value: Display,
+    {
+        self.nodes[self.current_node()]
+            .text
+            .iter()
+            .map(|line| {
+                let mut line = line.clone();
+                for (name, pool) in self.pool_names.iter() {
+                    let pattern = format!("{{{}}}", name);
+                    if line.contains(&pattern) {
+                        let (value, ..) = resources.get_ident_data(*pool);
+                        line = line.replace(&pattern, &value.to_string());
+                    }
+                }
+                line
+            })
+            .collect()
+    }
+
+    /// The choices out of the current node whose conditions pass, by their index in the node's choices.
+    pub fn available_choices(&self, resources: &QueuedResources<PoolID, Value>) -> Vec<usize> {
+        self.nodes[self.current_node()]
+            .choices
+            .iter()
+            .enumerate()
+            .filter(|(_, choice)| choice.available(resources))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// if there's nothing left to pick
+    pub fn is_over(&self, resources: &QueuedResources<PoolID, Value>) -> bool {
+        self.available_choices(resources).is_empty()
+    }
+
+    /// Queues the effects of the choices and jumps taken so far into the resource logic. They're applied on the resource logic's next update.
+    pub fn apply_effects(&mut self, resources: &mut QueuedResources<PoolID, Value>) {
+        for effect in self.queued_effects.drain(..) {
+            resources.handle_predicate(&effect);
+        }
+    }
+
+    /// Moves along the picked choice, if any, then takes jumps out of the node it lands on until none of their conditions pass. Jumps' effects are queued, but aren't applied until after the update, so jumps out of the next node don't see them. Apply the picked choice's effects (see [Dialogue::apply_effects]) and update the resource logic before this so jumps see them.
+    pub fn update(&mut self, resources: &QueuedResources<PoolID, Value>) {
+        self.events = std::mem::take(&mut self.queued_events);
+
+        if let Some(choice) = self.chosen.take() {
+            let from = self.current_node();
+            self.linking.update();
+            if self.linking.just_traversed[0].is_some() {
+                self.events.push(DialogueEvent {
+                    node: from,
+                    event_type: DialogueEventType::Chose(choice),
+                });
+                self.events.push(DialogueEvent {
+                    node: self.current_node(),
+                    event_type: DialogueEventType::Entered,
+                });
+            }
+        }
+
+        // a loop of jumps would never end, so stop after visiting every node once
+        for _ in 0..self.nodes.len() {
+            let from = self.current_node();
+            let jump = self.nodes[from]
+                .jumps
+                .iter()
+                .position(|jump| jump.available(resources));
+            let jump = match jump {
+                Some(jump) => jump,
+                None => break,
+            };
+            let to = self.nodes[from].jumps[jump].to;
+            self.queued_effects
+                .extend_from_slice(&self.nodes[from].jumps[jump].effects);
+            self.linking
+                .handle_predicate(&LinkingReaction::Traverse(0, to));
+            self.events.push(DialogueEvent {
+                node: from,
+                event_type: DialogueEventType::Jumped(jump),
+            });
+            self.events.push(DialogueEvent {
+                node: to,
+                event_type: DialogueEventType::Entered,
+            });
+        }
+    }
+
+    /// Plays the dialogue in a terminal (or anything else that can be read from and written to): prints the current node's text and its available choices, numbered from 1, then reads the number of a choice. Jumps are taken before a node is shown, including out of the node the dialogue starts on. Returns when the dialogue is over or the input runs out.
+    pub fn run_terminal(
+        &mut self,
+        resources: &mut QueuedResources<PoolID, Value>,
+        mut input: impl BufRead,
+        mut output: impl Write,
+    ) -> std::io::Result<()>
+    where
+        Value: Display,
+    {
+        // take any jumps out of the node the dialogue starts on before showing it
+        self.update(resources);
+        loop {
+            if !self.queued_effects.is_empty() {
+                self.apply_effects(resources);
+                resources.update();
+            }
+
+            for line in self.current_text(resources) {
+                writeln!(output, "{}", line)?;
+            }
+            let choices = self.available_choices(resources);
+            if choices.is_empty() {
+                return Ok(());
+            }
+            let node = &self.nodes[self.current_node()];
+            for (i, choice) in choices.iter().enumerate() {
+                writeln!(output, "{}. {}", i + 1, node.choices[*choice].text)?;
+            }
+
+            let choice = loop {
+                let mut line = String::new();
+                if input.read_line(&mut line)? == 0 {
+                    return Ok(());
+                }
+                match line.trim().parse::<usize>() {
+                    Ok(choice) if choice > 0 && choice <= choices.len() => {
+                        break choices[choice - 1];
+                    }
+                    _ => writeln!(output, "enter a valid input please")?,
+                }
+            };
+            writeln!(output)?;
+
+            self.handle_predicate(&DialogueReaction::Choose(choice));
+            self.apply_effects(resources);
+            resources.update();
+            self.update(resources);
+        }
+    }
+}
+
+impl<PoolID, Value> Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue + FromStr,
+{
+    /// Reads a dialogue in the text format described in the [module documentation][self]. `pool` gives the ID of the pool with the given name, or `None` if there isn't one.
+    ///
+    /// Errors have the line number (starting from 1) of the problem.
+    pub fn parse(text: &str, pool: impl Fn(&str) -> Option<PoolID>) -> Result<Self, String> {
+        let mut dialogue = Self::new();
+        // choices and jumps with the line they were on, before their targets are known
+        let mut edges = Vec::new();
+        let mut in_body = false;
+
+        for (line_num, line) in text.lines().enumerate() {
+            let line_num = line_num + 1;
+            let trimmed = line.trim();
+            if trimmed.starts_with("//") {
+                continue;
+            }
+
+            if !in_body {
+                if let Some(title) = trimmed.strip_prefix("title:") {
+                    dialogue.add_node(title.trim(), Vec::new());
+                } else if trimmed == "---" {
+                    if dialogue.nodes.is_empty() {
+                        return Err(format!("line {}: node has no title", line_num));
+                    }
+                    in_body = true;
+                } else if !trimmed.is_empty() {
+                    return Err(format!(
+                        "line {}: expected `title:` or `---`, found \"{}\"",
+                        line_num, trimmed
+                    ));
+                }
+                continue;
+            }
+
+            let node = dialogue.nodes.len() - 1;
+            if trimmed == "===" {
+                let text = &mut dialogue.nodes[node].text;
+                while let Some(line) = text.last() {
+                    if !line.is_empty() {
+                        break;
+                    }
+                    text.pop();
+                }
+                in_body = false;
+                continue;
+            }
+
+            let (choice_text, rest) = match trimmed.strip_prefix("[[") {
+                Some(choice) => {
+                    let end = choice
+                        .find("]]")
+                        .ok_or_else(|| format!("line {}: choice is missing `]]`", line_num))?;
+                    (Some(&choice[..end]), &choice[end + 2..])
+                }
+                None => (None, trimmed),
+            };
+
+            if choice_text.is_none() && !rest.starts_with("<<") {
+                for name in substitutions(rest) {
+                    let id = pool(name)
+                        .ok_or_else(|| format!("line {}: unknown pool \"{}\"", line_num, name))?;
+                    dialogue.pool_names.insert(name.to_string(), id);
+                }
+                if trimmed.is_empty() && dialogue.nodes[node].text.is_empty() {
+                    continue;
+                }
+                dialogue.nodes[node].text.push(trimmed.to_string());
+                continue;
+            }
+
+            let mut conditions = Vec::new();
+            let mut effects = Vec::new();
+            let mut jump = None;
+            for command in commands(rest).map_err(|err| format!("line {}: {}", line_num, err))? {
+                let words = command.split_whitespace().collect::<Vec<_>>();
+                let pool_id = |name: &str| {
+                    let name = name.strip_prefix('$').unwrap_or(name);
+                    pool(name)
+                        .ok_or_else(|| format!("line {}: unknown pool \"{}\"", line_num, name))
+                };
+                let value = |value: &str| {
+                    value
+                        .parse::<Value>()
+                        .map_err(|_| format!("line {}: invalid value \"{}\"", line_num, value))
+                };
+                match words.as_slice() {
+                    ["if", name, op, val] => {
+                        let comparison = match *op {
+                            "<" => Comparison::Less,
+                            "<=" => Comparison::LessOrEqual,
+                            "==" => Comparison::Equal,
+                            "!=" => Comparison::NotEqual,
+                            ">=" => Comparison::GreaterOrEqual,
+                            ">" => Comparison::Greater,
+                            _ => {
+                                return Err(format!(
+                                    "line {}: unknown comparison \"{}\"",
+                                    line_num, op
+                                ))
+                            }
+                        };
+                        conditions.push(Condition {
+                            pool: pool_id(name)?,
+                            comparison,
+                            value: value(val)?,
+                        });
+                    }
+                    ["set", name, op, val] => {
+                        let transaction = match *op {
+                            "+=" => Transaction::Change(value(val)?),
+                            "-=" => Transaction::Change(value(val)?.negate().ok_or_else(|| {
+                                format!("line {}: can't subtract \"{}\"", line_num, val)
+                            })?),
+                            "=" | "to" => Transaction::Set(value(val)?),
+                            _ => {
+                                return Err(format!(
+                                    "line {}: unknown assignment \"{}\"",
+                                    line_num, op
+                                ))
+                            }
+                        };
+                        effects.push((pool_id(name)?, transaction));
+                    }
+                    ["jump", target] => jump = Some(*target),
+                    _ => {
+                        return Err(format!(
+                            "line {}: unknown command \"<<{}>>\"",
+                            line_num, command
+                        ))
+                    }
+                }
+            }
+
+            let (text, target, is_jump) = match (choice_text, jump) {
+                (Some(_), Some(_)) => {
+                    return Err(format!("line {}: a choice can't also be a jump", line_num))
+                }
+                (Some(choice), None) => match choice.split_once('|') {
+                    Some((text, target)) => (text.trim(), target.trim(), false),
+                    None => (choice.trim(), choice.trim(), false),
+                },
+                (None, Some(target)) => ("", target, true),
+                (None, None) => {
+                    return Err(format!(
+                        "line {}: conditions and effects need a choice or jump",
+                        line_num
+                    ))
+                }
+            };
+            edges.push((
+                line_num,
+                node,
+                target.to_string(),
+                is_jump,
+                Choice {
+                    text: text.to_string(),
+                    to: 0,
+                    conditions,
+                    effects,
+                },
+            ));
+        }
+
+        if in_body {
+            return Err("last node is missing `===`".to_string());
+        }
+
+        for (line_num, from, target, is_jump, mut choice) in edges {
+            choice.to = dialogue
+                .nodes
+                .iter()
+                .position(|node| node.title == target)
+                .ok_or_else(|| format!("line {}: no node titled \"{}\"", line_num, target))?;
+            if is_jump {
+                dialogue.add_jump(from, choice);
+            } else {
+                dialogue.add_choice(from, choice);
+            }
+        }
+
+        Ok(dialogue)
+    }
+}
+
+/// the insides of the `<<...>>`s in a line, which must only have commands and whitespace
+fn commands(line: &str) -> Result<Vec<&str>, String> {
+    let mut commands = Vec::new();
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let start = rest
+            .strip_prefix("<<")
+            .ok_or_else(|| format!("expected `<<`, found \"{}\"", rest))?;
+        let end = start
+            .find(">>")
+            .ok_or_else(|| "command is missing `>>`".to_string())?;
+        commands.push(start[..end].trim());
+        rest = start[end + 2..].trim_start();
+    }
+    Ok(commands)
+}
+
+/// the names inside the `{...}`s in a line of text
+fn substitutions(line: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = line;
+    while let Some(start) = rest.find('{') {
+        match rest[start + 1..].find('}') {
+            Some(end) => {
+                names.push(&rest[start + 1..start + 1 + end]);
+                rest = &rest[start + end + 2..];
+            }
+            None => break,
+        }
+    }
+    names
+}
+
+impl<PoolID, Value> Logic for Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue,
+{
+    type Event = DialogueEvent;
+    type Reaction = DialogueReaction;
+
+    type Ident = usize;
+    type IdentData = DialogueNode<PoolID, Value>;
+    type Context = QueuedResources<PoolID, Value>;
+
+    fn update(&mut self, resources: &Self::Context) {
+        Dialogue::update(self, resources);
+    }
+
+    fn handle_predicate(&mut self, reaction: &Self::Reaction) {
+        match reaction {
+            DialogueReaction::Choose(choice) => {
+                let node = &self.nodes[self.current_node()];
+                let choice_data = &node.choices[*choice];
+                self.queued_effects.extend_from_slice(&choice_data.effects);
+                self.linking
+                    .handle_predicate(&LinkingReaction::Activate(0, choice_data.to));
+                self.chosen = Some(*choice);
+            }
+            DialogueReaction::GoTo(node) => {
+                self.linking
+                    .handle_predicate(&LinkingReaction::Traverse(0, *node));
+                self.queued_events.push(DialogueEvent {
+                    node: *node,
+                    event_type: DialogueEventType::Entered,
+                });
+            }
+        }
+    }
+
+    fn get_ident_data(&self, ident: Self::Ident) -> Self::IdentData {
+        self.nodes[ident].clone()
+    }
+
+    /// Replaces a node's text and choices. NOTE that edges for new choices aren't added to the graph; use [Dialogue::add_choice] for that.
+    fn update_ident_data(&mut self, ident: Self::Ident, data: Self::IdentData) {
+        self.nodes[ident] = data;
+    }
+}
+
+type QueryIdent<PoolID, Value> = (
+    <Dialogue<PoolID, Value> as Logic>::Ident,
+    <Dialogue<PoolID, Value> as Logic>::IdentData,
+);
+
+impl<PoolID, Value> OutputTable<QueryIdent<PoolID, Value>> for Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue,
+{
+    fn get_table(&self) -> Vec<QueryIdent<PoolID, Value>> {
+        self.nodes.iter().cloned().enumerate().collect()
+    }
+}
+
+impl<PoolID, Value> OutputTable<DialogueEvent> for Dialogue<PoolID, Value>
+where
+    PoolID: Copy + Ord + Debug,
+    Value: ResourceValue,
+{
+    fn get_table(&self) -> Vec<DialogueEvent> {
+        self.events.clone()
+    }
+}
@@ -2,92 +2,394 @@
 //!
 //! Entity-state logics communicate that game entities act in different ways or have different capabilities at different times, in ways that are intrinsic to each such entity. They govern the finite, discrete states of a set of game characters or other entities, update states when necessary, and condition the operators of other logics on entities' discrete states.
 
-use crate::graph::StateMachine;
+use crate::graph::{Guard, StateMachine, Transition};
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::hash::Hash;
 
-/// An entity-state logic for flat entity state machines.
-pub struct FlatEntityState<ID: Copy + Eq> {
+/// An entity-state logic for entity state machines.
+///
+/// States can be nested (see [StateMap]): an entity whose current state is `Falling`, nested in `Airborne`, is in both `Falling` and `Airborne`, and edges leaving `Airborne` apply to `Falling` as well. Graphs added with [add_graph][FlatEntityState::add_graph] have no nesting.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FlatEntityState<ID: Copy + Eq + Hash> {
     /// A vec of state machines
     pub graphs: Vec<StateMachine<ID>>,
+    /// How the states of each state machine are nested in each other
+    pub hierarchies: Vec<StateMap>,
     pub just_traversed: Vec<bool>,
+    /// The states exited during the last update as `(graph, node)`, innermost state first
+    pub exited: Vec<(usize, usize)>,
+    /// The states entered during the last update as `(graph, node)`, outermost state first
+    pub entered: Vec<(usize, usize)>,
 }
 
-impl<ID: Copy + Eq> FlatEntityState<ID> {
+impl<ID: Copy + Eq + Hash> FlatEntityState<ID> {
     pub fn new() -> Self {
         Self {
             graphs: Vec::new(),
+            hierarchies: Vec::new(),
             just_traversed: Vec::new(),
+            exited: Vec::new(),
+            entered: Vec::new(),
         }
     }
 
     /// Updates the entity-state logic.
     ///
-    /// Check the status of all the links from the current node in the condition table. If any of those links are `true`, i.e. that node can be moved to, move the current position.
+    /// Check the guards of all the links from the current node (by default, their status in the condition table), then the links from each state the current node is nested in, from the innermost outwards. If any of those links can be traversed, move the current position along the one with the highest priority. The condition table is cleared afterwards.
     pub fn update(&mut self) {
         self.just_traversed.fill(false);
-        for (graph, traversed) in self.graphs.iter_mut().zip(self.just_traversed.iter_mut()) {
-            for i in graph.graph.get_edges(graph.current_node) {
-                if graph.conditions[i] {
-                    graph.current_node = i;
-                    *traversed = true;
+        self.exited.clear();
+        self.entered.clear();
+
+        let mut transitions = Vec::new();
+        for (i, graph) in self.graphs.iter_mut().enumerate() {
+            graph.tick();
+            let mut state = Some(graph.current_node);
+            while let Some(node) = state {
+                if let Some(next) = graph.next_node_from(node) {
+                    graph.run_action(node, next);
+                    transitions.push((i, next));
                     break;
                 }
+                state = self.hierarchies.get(i).and_then(|map| map.parent(node));
             }
+            graph.clear_conditions();
+        }
+
+        for (graph, node) in transitions {
+            self.transition(graph, node);
         }
     }
 
-    /// Gets the current state of the entity by its index
+    /// Moves the entity to the given state, exiting and entering every state along the way. If the state has substates, moves to its initial substate.
+    ///
+    /// Moving to the current state or a state the entity is already nested in exits and re-enters that state.
+    fn transition(&mut self, graph: usize, target: usize) {
+        let from = self.graphs[graph].current_node;
+        let (exited, entered, to) = match self.hierarchies.get(graph) {
+            Some(map) => {
+                let to = map.resolve_initial(target);
+                let from_path = map.ancestors(from);
+                let target_path = map.ancestors(target);
+                // the innermost state containing both `from` and `target` that isn't being re-entered
+                let common = from_path
+                    .iter()
+                    .find(|state| target_path.contains(state) && **state != target)
+                    .copied();
+                let exited = from_path
+                    .into_iter()
+                    .take_while(|state| Some(*state) != common)
+                    .collect::<Vec<_>>();
+                let mut entered = map
+                    .ancestors(to)
+                    .into_iter()
+                    .take_while(|state| Some(*state) != common)
+                    .collect::<Vec<_>>();
+                entered.reverse();
+                (exited, entered, to)
+            }
+            None => (vec![from], vec![target], target),
+        };
+
+        let machine = &mut self.graphs[graph];
+        for state in entered.iter() {
+            machine.entered_at[*state] = machine.ticks;
+        }
+        machine.current_node = to;
+
+        self.exited
+            .extend(exited.into_iter().map(|state| (graph, state)));
+        self.entered
+            .extend(entered.into_iter().map(|state| (graph, state)));
+        self.just_traversed[graph] = true;
+    }
+
+    /// Gets the current state of the entity by its index. If states are nested, this is the innermost state.
     pub fn get_id_for_entity(&self, ent: <Self as Logic>::Ident) -> ID {
         self.graphs[ent].get_current_node()
     }
 
+    /// How many updates the entity has been in its current (innermost) state for.
+    pub fn ticks_in_state(&self, ent: <Self as Logic>::Ident) -> u64 {
+        let graph = &self.graphs[ent];
+        graph.ticks_in(graph.current_node)
+    }
+
+    /// How many updates the entity has been in the given state for, if it's currently in that state. With nested states, this can be longer than [ticks_in_state][FlatEntityState::ticks_in_state].
+    pub fn ticks_in(&self, ent: <Self as Logic>::Ident, state: ID) -> Option<u64> {
+        if !self.is_in_state(ent, state) {
+            return None;
+        }
+        let graph = &self.graphs[ent];
+        graph
+            .graph
+            .node_idx(&state)
+            .map(|node| graph.ticks_in(node))
+    }
+
+    /// If the entity can eventually get to the given state from its current state, ignoring conditions. Edges out of the states the current state is nested in count too.
+    pub fn can_reach(&self, ent: <Self as Logic>::Ident, state: ID) -> bool {
+        let graph = &self.graphs[ent];
+        let target = match graph.graph.node_idx(&state) {
+            Some(target) => target,
+            None => return false,
+        };
+        let starts = match self.hierarchies.get(ent) {
+            Some(map) => map.ancestors(graph.current_node),
+            None => vec![graph.current_node],
+        };
+        starts
+            .into_iter()
+            .any(|start| graph.graph.is_reachable(start, target))
+    }
+
+    /// Adds an edge that's traversed automatically once the entity has been in the state `from` for `ticks` updates, ex. "after 30 ticks in `Stunned`, go to `Idle`". If `from` has substates, time spent in any of them counts.
+    ///
+    /// If there's already an edge from `from` to `to`, it keeps its priority, action, and weight, and can be traversed either when its guard passes or when the time is up. Nothing is added if either state isn't in the entity's graph.
+    pub fn add_timeout(
+        &mut self,
+        ent: <Self as Logic>::Ident,
+        from: ID,
+        ticks: u64,
+        to: ID,
+    ) -> Result<(), EntityStateError> {
+        let graph = &mut self.graphs[ent];
+        let from = graph
+            .graph
+            .node_idx(&from)
+            .ok_or(EntityStateError::TimeoutStateNotFound)?;
+        let to = graph
+            .graph
+            .node_idx(&to)
+            .ok_or(EntityStateError::TimeoutStateNotFound)?;
+        if !graph.graph.edge_exists(from, to) {
+            graph.add_transition(
+                from,
+                to,
+                Transition {
+                    guard: Guard::After(ticks),
+                    ..Transition::new()
+                },
+            );
+            return Ok(());
+        }
+        let transition = graph
+            .transitions
+            .entry((from, to))
+            .or_insert_with(Transition::new);
+        let guard = std::mem::replace(&mut transition.guard, Guard::Never);
+        transition.guard = Guard::Any(vec![guard, Guard::After(ticks)]);
+        Ok(())
+    }
+
+    /// Gets every state the entity is currently in, from the outermost state to the innermost.
+    pub fn get_state_path(&self, ent: <Self as Logic>::Ident) -> Vec<ID> {
+        let graph = &self.graphs[ent];
+        let mut path = match self.hierarchies.get(ent) {
+            Some(map) => map.ancestors(graph.current_node),
+            None => vec![graph.current_node],
+        };
+        path.reverse();
+        path.into_iter()
+            .map(|node| graph.graph.nodes()[node])
+            .collect()
+    }
+
+    /// Checks if the entity is in the given state, either directly or because its current state is nested in it.
+    pub fn is_in_state(&self, ent: <Self as Logic>::Ident, state: ID) -> bool {
+        self.get_state_path(ent).contains(&state)
+    }
+
     /// Adds a map of nodes to the logic.
     ///
-    /// `starting_pos` is where the node the graph traversal starts on. `edges` is a list of adjacency lists. All conditions are set to false.
+    /// `starting_pos` is where the node the graph traversal starts on. `edges` is a list of adjacency lists. All conditions are set to false. Nothing is added if an edge leads to a state that isn't listed.
     pub fn add_graph<const NUM_NODES: usize>(
         &mut self,
         starting_pos: usize,
         edges: [(ID, &[ID]); NUM_NODES],
-    ) {
+    ) -> Result<(), EntityStateError> {
+        if starting_pos >= NUM_NODES {
+            return Err(EntityStateError::StartNotFound(starting_pos));
+        }
         let mut graph = StateMachine::new();
         let (ids, edges): (Vec<_>, Vec<_>) = edges.iter().cloned().unzip();
         graph.add_nodes(ids.as_slice());
         graph.current_node = starting_pos;
         for (from, node_edges) in edges.iter().enumerate() {
             for to in node_edges.iter() {
-                graph
-                    .graph
-                    .add_edge(from, ids.iter().position(|id| to == id).unwrap());
+                let to = ids
+                    .iter()
+                    .position(|id| to == id)
+                    .ok_or(EntityStateError::UnknownState(from))?;
+                graph.graph.add_edge(from, to);
+            }
+        }
+        self.graphs.push(graph);
+        self.hierarchies.push(StateMap {
+            states: vec![State::default(); NUM_NODES],
+        });
+        self.just_traversed.push(false);
+        Ok(())
+    }
+
+    /// Adds a map of nested nodes to the logic.
+    ///
+    /// Each state is given as `(id, parent, edges)`, where `parent` is the state it's nested in, if any. The first substate listed for a state is its initial substate, i.e. the one moved to when an edge leads to the parent state. `starting_pos` is resolved to its initial substate the same way. All conditions are set to false.
+    ///
+    /// Nothing is added if a parent or edge names a state that isn't listed, or if a state is nested in itself.
+    pub fn add_hierarchical_graph<const NUM_NODES: usize>(
+        &mut self,
+        starting_pos: usize,
+        states: [(ID, Option<ID>, &[ID]); NUM_NODES],
+    ) -> Result<(), EntityStateError> {
+        if starting_pos >= NUM_NODES {
+            return Err(EntityStateError::StartNotFound(starting_pos));
+        }
+        let mut graph = StateMachine::new();
+        let ids = states.iter().map(|(id, ..)| *id).collect::<Vec<_>>();
+        let idx_of = |id: &ID| ids.iter().position(|other| other == id);
+        graph.add_nodes(ids.as_slice());
+
+        let mut map = StateMap {
+            states: vec![State::default(); NUM_NODES],
+        };
+        for (from, (_, parent, edges)) in states.iter().enumerate() {
+            if let Some(parent) = parent {
+                let parent = idx_of(parent).ok_or(EntityStateError::UnknownState(from))?;
+                map.states[from].parent = Some(parent);
+                if map.states[parent].initial.is_none() {
+                    map.states[parent].initial = Some(from);
+                }
+            }
+            for to in edges.iter() {
+                let to = idx_of(to).ok_or(EntityStateError::UnknownState(from))?;
+                graph.graph.add_edge(from, to);
             }
         }
+        if let Some(state) = (0..NUM_NODES).find(|state| map.is_nested_in_itself(*state)) {
+            return Err(EntityStateError::ParentCycle(state));
+        }
+        graph.current_node = map.resolve_initial(starting_pos);
+
         self.graphs.push(graph);
+        self.hierarchies.push(map);
         self.just_traversed.push(false);
+        Ok(())
     }
 }
 
-/// A representation of a map of states.
-pub struct StateMap<ID> {
-    pub states: Vec<State<ID>>,
+/// Why a graph or timeout couldn't be added to an entity-state logic. States are given by their index in the list passed in.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntityStateError {
+    /// the starting state isn't in the list
+    StartNotFound(usize),
+    /// this state's parent or one of its edges names a state that isn't in the list
+    UnknownState(usize),
+    /// this state is nested in itself, directly or through its parents
+    ParentCycle(usize),
+    /// a timeout leads from or to a state that isn't in the entity's graph
+    TimeoutStateNotFound,
+}
+
+impl fmt::Display for EntityStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EntityStateError::StartNotFound(state) => {
+                write!(f, "starting state {} isn't in the graph", state)
+            }
+            EntityStateError::UnknownState(state) => write!(
+                f,
+                "state {} has a parent or edge that isn't in the graph",
+                state
+            ),
+            EntityStateError::ParentCycle(state) => {
+                write!(f, "state {} is nested in itself", state)
+            }
+            EntityStateError::TimeoutStateNotFound => {
+                write!(f, "timeout state isn't in the graph")
+            }
+        }
+    }
+}
+
+impl std::error::Error for EntityStateError {}
+
+/// How the states of a state machine are nested in each other. Indices run parallel with the nodes of the state machine's graph.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct StateMap {
+    pub states: Vec<State>,
+}
+
+impl StateMap {
+    /// The state the given state is nested in, if any.
+    pub fn parent(&self, state: usize) -> Option<usize> {
+        self.states.get(state).and_then(|state| state.parent)
+    }
+
+    /// The given state followed by every state it's nested in, innermost first.
+    pub fn ancestors(&self, state: usize) -> Vec<usize> {
+        let mut ancestors = vec![state];
+        let mut state = state;
+        // a map with a cycle in it can still be loaded or edited by hand, so stop once every state has been visited
+        while let Some(parent) = self.parent(state) {
+            if ancestors.len() > self.states.len() {
+                break;
+            }
+            ancestors.push(parent);
+            state = parent;
+        }
+        ancestors
+    }
+
+    /// If following the state's parents leads back to it.
+    pub fn is_nested_in_itself(&self, state: usize) -> bool {
+        let mut current = state;
+        for _ in 0..self.states.len() {
+            match self.parent(current) {
+                Some(parent) if parent == state => return true,
+                Some(parent) => current = parent,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Follows initial substates down from the given state until reaching a state without substates.
+    pub fn resolve_initial(&self, state: usize) -> usize {
+        let mut state = state;
+        while let Some(initial) = self.states.get(state).and_then(|state| state.initial) {
+            state = initial;
+        }
+        state
+    }
 }
 
 /// A state in a state machine.
-pub struct State<ID> {
-    pub id: ID,
-    /// The edges to the states that the entity can move to from the current state.
-    pub edges: Vec<usize>,
+#[derive(Clone, Copy, Default, Serialize, Deserialize)]
+pub struct State {
+    /// The state this state is nested in, if any.
+    pub parent: Option<usize>,
+    /// The substate that's moved to when this state is entered, if this state has substates.
+    pub initial: Option<usize>,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct EntityEvent {
     pub graph: usize,
     pub node: usize,
     event_type: EntityEventType,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum EntityEventType {
     Activated,
     Traversed,
+    /// the entity entered this state, including states that the new state is nested in
+    Entered,
+    /// the entity left this state, including states that the old state is nested in
+    Exited,
 }
 impl EventType for EntityEventType {}
 
@@ -98,6 +400,7 @@ impl Event for EntityEvent {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EntityReaction {
     Activate(usize, usize),
     Traverse(usize, usize),
@@ -105,7 +408,7 @@ pub enum EntityReaction {
 
 impl Reaction for EntityReaction {}
 
-impl<ID: Copy + Eq> Logic for FlatEntityState<ID> {
+impl<ID: Copy + Eq + Hash> Logic for FlatEntityState<ID> {
     type Event = EntityEvent;
     type Reaction = EntityReaction;
 
@@ -113,13 +416,18 @@ impl<ID: Copy + Eq> Logic for FlatEntityState<ID> {
     type Ident = usize;
     /// current position in logic
     type IdentData = ID;
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        FlatEntityState::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
             EntityReaction::Activate(graph, node) => self.graphs[*graph].conditions[*node] = true,
             EntityReaction::Traverse(graph, node) => {
-                self.graphs[*graph].set_current_node(*node);
-                self.just_traversed[*graph] = true;
+                self.transition(*graph, *node);
+                self.graphs[*graph].conditions.fill(false);
             }
         }
     }
@@ -129,10 +437,9 @@ impl<ID: Copy + Eq> Logic for FlatEntityState<ID> {
     }
 
     fn update_ident_data(&mut self, ident: Self::Ident, data: Self::IdentData) {
-        let graph = &mut self.graphs[ident];
-        let node = graph.graph.nodes.iter().position(|id| *id == data);
-        if let Some(idx) = node {
-            graph.current_node = idx;
+        // the same as traversing to the state, so nested states are resolved and entered/exited like in an update
+        if let Some(node) = self.graphs[ident].graph.node_idx(&data) {
+            self.transition(ident, node);
         }
     }
 }
@@ -141,7 +448,7 @@ type QueryIdent<ID> = (
     <FlatEntityState<ID> as Logic>::Ident,
     <FlatEntityState<ID> as Logic>::IdentData,
 );
-impl<ID: Copy + Eq> OutputTable<QueryIdent<ID>> for FlatEntityState<ID> {
+impl<ID: Copy + Eq + Hash> OutputTable<QueryIdent<ID>> for FlatEntityState<ID> {
     fn get_table(&self) -> Vec<QueryIdent<ID>> {
         (0..self.graphs.len())
             .map(|idx| (idx, self.get_ident_data(idx)))
@@ -151,7 +458,7 @@ impl<ID: Copy + Eq> OutputTable<QueryIdent<ID>> for FlatEntityState<ID> {
 
 type QueryEvent<ID> = <FlatEntityState<ID> as Logic>::Event;
 
-impl<ID: Copy + Eq> OutputTable<QueryEvent<ID>> for FlatEntityState<ID> {
+impl<ID: Copy + Eq + Hash> OutputTable<QueryEvent<ID>> for FlatEntityState<ID> {
     fn get_table(&self) -> Vec<QueryEvent<ID>> {
         let mut events = Vec::new();
         for (i, (graph, traversed)) in self
@@ -168,7 +475,23 @@ impl<ID: Copy + Eq> OutputTable<QueryEvent<ID>> for FlatEntityState<ID> {
                 };
                 events.push(event);
             }
-            for (node, activated) in graph.conditions.iter().enumerate() {
+            for (_, node) in self.exited.iter().filter(|(graph, _)| *graph == i) {
+                let event = EntityEvent {
+                    graph: i,
+                    node: *node,
+                    event_type: EntityEventType::Exited,
+                };
+                events.push(event);
+            }
+            for (_, node) in self.entered.iter().filter(|(graph, _)| *graph == i) {
+                let event = EntityEvent {
+                    graph: i,
+                    node: *node,
+                    event_type: EntityEventType::Entered,
+                };
+                events.push(event);
+            }
+            for (node, activated) in graph.last_conditions.iter().enumerate() {
                 if *activated && node != graph.current_node {
                     let event = EntityEvent {
                         graph: i,
@@ -1,31 +1,112 @@
 //! Various structs related to graphs/state machines.
 
-/// State machine with links represented by a directed graph with an adjacency matrix.
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, BinaryHeap, HashMap, VecDeque};
+use std::hash::Hash;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+mod export;
+pub use export::{EdgeRecord, GuardData, StateMachineData, StateMachineDataError};
+
+/// State machine with links represented by a directed [Graph].
 ///
-/// Uses a condition table to check if an edge is traversable. If `graph.conditions[node_idx] == true`, then the edge from `graph.nodes[current_node]` to `graph.nodes[node_idx]` is traversable.
+/// Uses a condition table to check if an edge is traversable. By default, if `graph.conditions[node_idx] == true`, then the edge from `graph.nodes()[current_node]` to `graph.nodes()[node_idx]` is traversable. Edges can also be given a [Transition] with a different [Guard], a priority, and an action.
 ///
 /// should definitely eventually separate the condition table part from the actual graph representation
-pub struct StateMachine<NodeID: Copy + Eq> {
+#[derive(Clone)]
+pub struct StateMachine<NodeID: Copy + Eq + Hash> {
     /// graph
     pub graph: Graph<NodeID>,
     /// index of the current node we're on
     pub current_node: usize,
     /// condition tables for the status of links in the current node in the graph
     pub conditions: Vec<bool>,
+    /// the condition table as it was before it was last cleared, i.e. the conditions the last update acted on
+    pub last_conditions: Vec<bool>,
+    /// guards, priorities, and actions of edges, keyed by `(from, to)`. Edges without an entry here use [Transition::new].
+    pub transitions: BTreeMap<(usize, usize), Transition<NodeID>>,
+    /// how many times the state machine has ticked
+    pub ticks: u64,
+    /// the tick each node was last entered on
+    pub entered_at: Vec<u64>,
 }
 
-impl<NodeID: Copy + Eq> StateMachine<NodeID> {
+impl<NodeID: Copy + Eq + Hash> StateMachine<NodeID> {
     pub fn new() -> Self {
         Self {
             graph: Graph::new(),
             current_node: 0,
             conditions: Vec::new(),
+            last_conditions: Vec::new(),
+            transitions: BTreeMap::new(),
+            ticks: 0,
+            entered_at: Vec::new(),
+        }
+    }
+
+    /// Advances the state machine's clock by one tick.
+    pub fn tick(&mut self) {
+        self.ticks += 1;
+    }
+
+    /// How many ticks it's been since the given node was last entered.
+    pub fn ticks_in(&self, node: usize) -> u64 {
+        self.ticks - self.entered_at[node]
+    }
+
+    /// Moves to the given node and records when it was entered, without resetting the condition table.
+    pub fn enter(&mut self, node: usize) {
+        self.current_node = node;
+        self.entered_at[node] = self.ticks;
+    }
+
+    /// Adds an edge with the given guard, priority, and action.
+    pub fn add_transition(&mut self, from: usize, to: usize, transition: Transition<NodeID>) {
+        self.graph.add_edge(from, to);
+        self.transitions.insert((from, to), transition);
+    }
+
+    /// Picks the edge to traverse out of the given node, if any: out of every edge whose guard passes, the one with the highest priority. Ties go to the edge to the node with the lowest index.
+    pub fn next_node_from(&self, node: usize) -> Option<usize> {
+        let mut next: Option<(usize, i32)> = None;
+        for to in self.graph.get_edges(node) {
+            let (guard, priority) = match self.transitions.get(&(node, to)) {
+                Some(transition) => (&transition.guard, transition.priority),
+                None => (&Guard::Activated, 0),
+            };
+            let higher_priority = match next {
+                Some((_, max)) => priority > max,
+                None => true,
+            };
+            if higher_priority && guard.check(self, node, to) {
+                next = Some((to, priority));
+            }
+        }
+        next.map(|(to, _)| to)
+    }
+
+    /// Runs the action of the edge from `from` to `to`, if it has one.
+    pub fn run_action(&self, from: usize, to: usize) {
+        if let Some(action) = self
+            .transitions
+            .get(&(from, to))
+            .and_then(|transition| transition.action.as_ref())
+        {
+            action(self.graph.nodes[from], self.graph.nodes[to]);
         }
     }
 
+    /// Clears the condition table, keeping a copy in `last_conditions`.
+    pub fn clear_conditions(&mut self) {
+        self.last_conditions.clone_from(&self.conditions);
+        self.conditions.fill(false);
+    }
+
     /// set current node, reset condition table
     pub fn set_current_node(&mut self, node: usize) {
-        self.current_node = node;
+        self.enter(node);
         self.conditions.fill(false);
     }
 
@@ -34,65 +115,598 @@ impl<NodeID: Copy + Eq> StateMachine<NodeID> {
     }
 
     pub fn add_node(&mut self, node: NodeID) {
-        self.graph.nodes.push(node);
-        self.resize_matrix();
+        self.graph.add_node(node);
+        self.resize_tables();
     }
 
     /// add multiple nodes at once to avoid resizing the adjacency matrix multiple times
     pub fn add_nodes(&mut self, nodes: &[NodeID]) {
-        for node in nodes.iter() {
-            self.graph.nodes.push(*node);
+        self.graph.add_nodes(nodes);
+        self.resize_tables();
+    }
+
+    /// Removes a node, along with its edges and its entries in the condition tables. NOTE that this shifts the indices of every node after it down by one. Guards that check a node by index are updated to match, and guards that checked the removed node become [Guard::Never]. If the current node is removed, the state machine goes back to its first node.
+    ///
+    /// A state machine always has a current node, so its last node can't be removed. Returns false if nothing was removed.
+    pub fn remove_node(&mut self, node: usize) -> bool {
+        if node >= self.graph.nodes.len() || self.graph.nodes.len() == 1 {
+            return false;
+        }
+        self.graph.remove_node(node);
+        self.conditions.remove(node);
+        self.last_conditions.remove(node);
+        self.entered_at.remove(node);
+
+        let shift = |idx: usize| if idx > node { idx - 1 } else { idx };
+        self.transitions = std::mem::take(&mut self.transitions)
+            .into_iter()
+            .filter(|((from, to), _)| *from != node && *to != node)
+            .map(|((from, to), mut transition)| {
+                transition.guard = transition.guard.without_node(node);
+                ((shift(from), shift(to)), transition)
+            })
+            .collect();
+
+        if self.current_node == node {
+            self.current_node = 0;
+            self.entered_at[0] = self.ticks;
+        } else {
+            self.current_node = shift(self.current_node);
         }
-        self.resize_matrix();
+        true
     }
 
-    /// resize adjacency matrix to the current number of nodes in the graph
-    fn resize_matrix(&mut self) {
+    /// Removes an edge along with its guard, priority, and action.
+    pub fn remove_edge(&mut self, from: usize, to: usize) {
+        self.graph.remove_edge(from, to);
+        self.transitions.remove(&(from, to));
+    }
+
+    /// resize condition tables to the current number of nodes in the graph
+    fn resize_tables(&mut self) {
         let nodes = self.graph.nodes.len();
-        for row in self.graph.edges.iter_mut() {
-            row.resize_with(nodes, || false);
-        }
-        self.graph.edges.resize_with(nodes, || vec![false; nodes]);
         self.conditions.resize_with(nodes, || false);
+        self.last_conditions.resize_with(nodes, || false);
+        self.entered_at.resize_with(nodes, || 0);
     }
 }
 
+/// The guard, priority, and action of an edge in a [StateMachine].
+pub struct Transition<NodeID: Copy + Eq + Hash> {
+    /// when the edge can be traversed
+    pub guard: Guard<NodeID>,
+    /// when more than one edge out of a node can be traversed, the one with the highest priority is
+    pub priority: i32,
+    /// called with the IDs of the nodes the edge goes from and to when the edge is traversed
+    #[allow(clippy::type_complexity)]
+    pub action: Option<Rc<dyn Fn(NodeID, NodeID)>>,
+}
+
+impl<NodeID: Copy + Eq + Hash> Transition<NodeID> {
+    /// A transition that's traversable when its target node is activated, with a priority of 0 and no action.
+    pub fn new() -> Self {
+        Self {
+            guard: Guard::Activated,
+            priority: 0,
+            action: None,
+        }
+    }
+}
+
+impl<NodeID: Copy + Eq + Hash> Clone for Transition<NodeID> {
+    fn clone(&self) -> Self {
+        Self {
+            guard: self.guard.clone(),
+            priority: self.priority,
+            action: self.action.clone(),
+        }
+    }
+}
+
+/// When an edge in a [StateMachine] can be traversed.
+pub enum Guard<NodeID: Copy + Eq + Hash> {
+    /// the edge's target node is activated in the condition table. This is the default for every edge.
+    Activated,
+    /// the given node is activated in the condition table
+    NodeActivated(usize),
+    /// always traversable, as soon as the edge's source node is reached
+    Always,
+    /// never traversable
+    Never,
+    /// the edge's source node has been entered for at least the given number of ticks. On its own this makes the edge a timeout ("after 30 ticks in `Stunned`, go to `Idle`"); combined with [Guard::Activated] in [Guard::All] it gives the source node a minimum duration.
+    After(u64),
+    /// every guard passes
+    All(Vec<Guard<NodeID>>),
+    /// at least one guard passes
+    Any(Vec<Guard<NodeID>>),
+    /// the guard doesn't pass
+    Not(Box<Guard<NodeID>>),
+    /// the closure returns true. It's given the state machine and the index of the edge's target node.
+    #[allow(clippy::type_complexity)]
+    Predicate(Rc<dyn Fn(&StateMachine<NodeID>, usize) -> bool>),
+}
+
+impl<NodeID: Copy + Eq + Hash> Guard<NodeID> {
+    /// checks the guard for an edge leading from `from` to `to`
+    pub fn check(&self, machine: &StateMachine<NodeID>, from: usize, to: usize) -> bool {
+        match self {
+            Guard::Activated => machine.conditions[to],
+            Guard::NodeActivated(node) => machine.conditions[*node],
+            Guard::Always => true,
+            Guard::Never => false,
+            Guard::After(ticks) => machine.ticks_in(from) >= *ticks,
+            Guard::All(guards) => guards.iter().all(|guard| guard.check(machine, from, to)),
+            Guard::Any(guards) => guards.iter().any(|guard| guard.check(machine, from, to)),
+            Guard::Not(guard) => !guard.check(machine, from, to),
+            Guard::Predicate(predicate) => predicate(machine, to),
+        }
+    }
+}
+
+impl<NodeID: Copy + Eq + Hash> Guard<NodeID> {
+    /// the guard after the given node is removed from its state machine: indices after it shift down by one, and since the removed node can never be activated again, checks for it become [Guard::Never]
+    fn without_node(self, removed: usize) -> Self {
+        match self {
+            Guard::NodeActivated(node) if node == removed => Guard::Never,
+            Guard::NodeActivated(node) if node > removed => Guard::NodeActivated(node - 1),
+            Guard::All(guards) => Guard::All(
+                guards
+                    .into_iter()
+                    .map(|guard| guard.without_node(removed))
+                    .collect(),
+            ),
+            Guard::Any(guards) => Guard::Any(
+                guards
+                    .into_iter()
+                    .map(|guard| guard.without_node(removed))
+                    .collect(),
+            ),
+            Guard::Not(guard) => Guard::Not(Box::new(guard.without_node(removed))),
+            guard => guard,
+        }
+    }
+}
+
+impl<NodeID: Copy + Eq + Hash> Clone for Guard<NodeID> {
+    fn clone(&self) -> Self {
+        match self {
+            Guard::Activated => Guard::Activated,
+            Guard::NodeActivated(node) => Guard::NodeActivated(*node),
+            Guard::Always => Guard::Always,
+            Guard::Never => Guard::Never,
+            Guard::After(ticks) => Guard::After(*ticks),
+            Guard::All(guards) => Guard::All(guards.clone()),
+            Guard::Any(guards) => Guard::Any(guards.clone()),
+            Guard::Not(guard) => Guard::Not(guard.clone()),
+            Guard::Predicate(predicate) => Guard::Predicate(predicate.clone()),
+        }
+    }
+}
+
+/// Data attached to an edge of a [Graph].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EdgeData {
+    /// the cost of traversing the edge, used when finding shortest paths. Should not be negative.
+    pub weight: f32,
+    /// an optional label, ex. the text of a dialogue choice
+    pub label: Option<String>,
+}
+
+impl EdgeData {
+    /// An edge with a weight of 1.0 and no label.
+    pub fn new() -> Self {
+        Self {
+            weight: 1.0,
+            label: None,
+        }
+    }
+}
+
+/// How a [Graph] stores its edges. Both support the same operations.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GraphStorage {
+    /// Space for every possible edge. Checking for a single edge is fast, but memory grows with the square of the number of nodes.
+    AdjacencyMatrix,
+    /// Each node keeps a list of its outgoing edges. Better for large graphs with few edges per node, ex. rooms linked together.
+    AdjacencyList,
+}
+
 #[derive(Clone)]
-/// A graph implemented with an adjacency matrix
-pub struct Graph<NodeID: Clone + Eq> {
-    /// list of nodes in the graph. Possibly unnecessary, I can't decide if I want to remove this or not
-    pub nodes: Vec<NodeID>,
-    /// adjacency matrix
-    pub edges: Vec<Vec<bool>>,
+enum Edges {
+    Matrix(Vec<Vec<Option<EdgeData>>>),
+    /// kept sorted by destination node
+    List(Vec<Vec<(usize, EdgeData)>>),
 }
 
-impl<NodeID: Eq + Clone> Graph<NodeID> {
+#[derive(Clone)]
+/// A directed graph. Edges are stored either in an adjacency matrix or in adjacency lists (see [GraphStorage]), and nodes and edges are referred to by their indices in [Graph::nodes].
+pub struct Graph<NodeID: Clone + Eq + Hash> {
+    /// list of nodes in the graph
+    nodes: Vec<NodeID>,
+    /// the index of each node by its ID, kept up to date with `nodes`. If an ID is in the graph more than once, this is its first index.
+    indices: HashMap<NodeID, usize>,
+    edges: Edges,
+}
+
+impl<NodeID: Eq + Clone + Hash> Graph<NodeID> {
+    /// A graph using an adjacency matrix.
     pub fn new() -> Self {
+        Self::with_storage(GraphStorage::AdjacencyMatrix)
+    }
+
+    pub fn with_storage(storage: GraphStorage) -> Self {
         Self {
             nodes: Vec::new(),
-            edges: Vec::new(),
+            indices: HashMap::new(),
+            edges: match storage {
+                GraphStorage::AdjacencyMatrix => Edges::Matrix(Vec::new()),
+                GraphStorage::AdjacencyList => Edges::List(Vec::new()),
+            },
         }
     }
 
+    pub fn storage(&self) -> GraphStorage {
+        match self.edges {
+            Edges::Matrix(_) => GraphStorage::AdjacencyMatrix,
+            Edges::List(_) => GraphStorage::AdjacencyList,
+        }
+    }
+
+    /// Converts the graph to the given kind of storage, keeping all of its edges.
+    pub fn set_storage(&mut self, storage: GraphStorage) {
+        if storage == self.storage() {
+            return;
+        }
+        let mut edges = Vec::new();
+        for from in 0..self.nodes.len() {
+            for to in self.get_edges(from) {
+                edges.push((from, to, self.edge_data(from, to).cloned().unwrap()));
+            }
+        }
+        let nodes = std::mem::take(&mut self.nodes);
+        *self = Self::with_storage(storage);
+        self.add_nodes(&nodes);
+        for (from, to, data) in edges {
+            self.add_edge_with_data(from, to, data);
+        }
+    }
+
+    /// The nodes in the graph, by index.
+    pub fn nodes(&self) -> &[NodeID] {
+        &self.nodes
+    }
+
+    /// Adds a node without any edges, returning its index.
+    pub fn add_node(&mut self, node: NodeID) -> usize {
+        let idx = self.nodes.len();
+        self.indices.entry(node.clone()).or_insert(idx);
+        self.nodes.push(node);
+        self.resize();
+        idx
+    }
+
+    /// add multiple nodes at once to avoid resizing the adjacency matrix multiple times
+    pub fn add_nodes(&mut self, nodes: &[NodeID]) {
+        for node in nodes {
+            let idx = self.nodes.len();
+            self.indices.entry(node.clone()).or_insert(idx);
+            self.nodes.push(node.clone());
+        }
+        self.resize();
+    }
+
+    /// Changes the ID of the node at the given index, keeping its edges.
+    pub fn set_node(&mut self, idx: usize, node: NodeID) {
+        self.nodes[idx] = node;
+        self.reindex();
+    }
+
+    /// rebuilds the index map from `nodes`
+    fn reindex(&mut self) {
+        self.indices.clear();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            self.indices.entry(node.clone()).or_insert(idx);
+        }
+    }
+
+    /// resize edge storage to the current number of nodes in the graph
+    fn resize(&mut self) {
+        let nodes = self.nodes.len();
+        match &mut self.edges {
+            Edges::Matrix(matrix) => {
+                for row in matrix.iter_mut() {
+                    row.resize_with(nodes, || None);
+                }
+                matrix.resize_with(nodes, || vec![None; nodes]);
+            }
+            Edges::List(lists) => lists.resize_with(nodes, Vec::new),
+        }
+    }
+
+    /// Removes a node and all edges to and from it. NOTE that, like removing from a `Vec`, this shifts the indices of every node after it down by one.
+    pub fn remove_node(&mut self, node: usize) {
+        self.nodes.remove(node);
+        self.reindex();
+        match &mut self.edges {
+            Edges::Matrix(matrix) => {
+                matrix.remove(node);
+                for row in matrix.iter_mut() {
+                    row.remove(node);
+                }
+            }
+            Edges::List(lists) => {
+                lists.remove(node);
+                for list in lists.iter_mut() {
+                    list.retain(|(to, _)| *to != node);
+                    for (to, _) in list.iter_mut() {
+                        if *to > node {
+                            *to -= 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds an edge with a weight of 1.0 and no label.
     pub fn add_edge(&mut self, from: usize, to: usize) {
-        self.edges[from][to] = true;
+        self.add_edge_with_data(from, to, EdgeData::new());
     }
 
-    pub fn edge_exists(&mut self, from: usize, to: usize) -> bool {
-        self.edges[from][to]
+    /// Adds an edge with the given weight and label, replacing the edge's data if it already exists.
+    pub fn add_edge_with_data(&mut self, from: usize, to: usize, data: EdgeData) {
+        match &mut self.edges {
+            Edges::Matrix(matrix) => matrix[from][to] = Some(data),
+            Edges::List(lists) => {
+                let list = &mut lists[from];
+                match list.binary_search_by_key(&to, |(to, _)| *to) {
+                    Ok(i) => list[i].1 = data,
+                    Err(i) => list.insert(i, (to, data)),
+                }
+            }
+        }
+    }
+
+    pub fn remove_edge(&mut self, from: usize, to: usize) {
+        match &mut self.edges {
+            Edges::Matrix(matrix) => matrix[from][to] = None,
+            Edges::List(lists) => lists[from].retain(|(other, _)| *other != to),
+        }
+    }
+
+    pub fn edge_exists(&self, from: usize, to: usize) -> bool {
+        self.edge_data(from, to).is_some()
+    }
+
+    /// The weight and label of an edge, if it exists
+    pub fn edge_data(&self, from: usize, to: usize) -> Option<&EdgeData> {
+        match &self.edges {
+            Edges::Matrix(matrix) => matrix[from][to].as_ref(),
+            Edges::List(lists) => lists[from]
+                .binary_search_by_key(&to, |(to, _)| *to)
+                .ok()
+                .map(|i| &lists[from][i].1),
+        }
+    }
+
+    pub fn edge_data_mut(&mut self, from: usize, to: usize) -> Option<&mut EdgeData> {
+        match &mut self.edges {
+            Edges::Matrix(matrix) => matrix[from][to].as_mut(),
+            Edges::List(lists) => {
+                let list = &mut lists[from];
+                match list.binary_search_by_key(&to, |(to, _)| *to) {
+                    Ok(i) => Some(&mut list[i].1),
+                    Err(_) => None,
+                }
+            }
+        }
     }
 
     /// The edges connected to a node, by their indices (not IDs)
-    pub fn get_edges(&mut self, node: usize) -> Vec<usize> {
-        self.edges[node]
-            .iter()
-            .enumerate()
-            .filter_map(|(i, linked)| if *linked { Some(i) } else { None })
-            .collect::<Vec<_>>()
+    pub fn get_edges(&self, node: usize) -> Vec<usize> {
+        match &self.edges {
+            Edges::Matrix(matrix) => matrix[node]
+                .iter()
+                .enumerate()
+                .filter_map(|(i, linked)| linked.as_ref().map(|_| i))
+                .collect::<Vec<_>>(),
+            Edges::List(lists) => lists[node].iter().map(|(to, _)| *to).collect(),
+        }
     }
 
     /// The index in the graph of the given node
     pub fn node_idx(&self, node: &NodeID) -> Option<usize> {
-        self.nodes.iter().position(|id| id == node)
+        self.indices.get(node).copied()
+    }
+
+    /// Every node reachable from `start`, including `start`, in breadth-first order.
+    pub fn bfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut order = Vec::new();
+        let mut queue = VecDeque::new();
+        visited[start] = true;
+        queue.push_back(start);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            for next in self.get_edges(node) {
+                if !visited[next] {
+                    visited[next] = true;
+                    queue.push_back(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// Every node reachable from `start`, including `start`, in depth-first (preorder) order.
+    pub fn dfs(&self, start: usize) -> Vec<usize> {
+        let mut visited = vec![false; self.nodes.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![start];
+        while let Some(node) = stack.pop() {
+            if visited[node] {
+                continue;
+            }
+            visited[node] = true;
+            order.push(node);
+            // reversed so that lower indices are visited first
+            for next in self.get_edges(node).into_iter().rev() {
+                if !visited[next] {
+                    stack.push(next);
+                }
+            }
+        }
+        order
+    }
+
+    /// If there's a path from `from` to `to`.
+    pub fn is_reachable(&self, from: usize, to: usize) -> bool {
+        self.bfs(from).contains(&to)
+    }
+
+    /// The cheapest path from `from` to `to` using Dijkstra's algorithm, as the path's total weight and the nodes along it (including both ends).
+    pub fn shortest_path(&self, from: usize, to: usize) -> Option<(f32, Vec<usize>)> {
+        self.astar(from, to, |_| 0.0)
+    }
+
+    /// The cheapest path from `from` to `to` using A*, as the path's total weight and the nodes along it (including both ends). `heuristic` estimates the cost from a node to `to`, and must never overestimate it for the path to be the cheapest.
+    pub fn astar(
+        &self,
+        from: usize,
+        to: usize,
+        heuristic: impl Fn(usize) -> f32,
+    ) -> Option<(f32, Vec<usize>)> {
+        let mut costs = vec![f32::INFINITY; self.nodes.len()];
+        let mut came_from = vec![None; self.nodes.len()];
+        let mut open = BinaryHeap::new();
+        costs[from] = 0.0;
+        open.push(Candidate {
+            estimate: heuristic(from),
+            node: from,
+        });
+
+        while let Some(Candidate { estimate, node }) = open.pop() {
+            if node == to {
+                let mut path = vec![to];
+                let mut node = to;
+                while let Some(prev) = came_from[node] {
+                    path.push(prev);
+                    node = prev;
+                }
+                path.reverse();
+                return Some((costs[to], path));
+            }
+            // stale entry; a cheaper path to this node was already found
+            if estimate > costs[node] + heuristic(node) {
+                continue;
+            }
+            for next in self.get_edges(node) {
+                let cost = costs[node] + self.edge_data(node, next).unwrap().weight;
+                if cost < costs[next] {
+                    costs[next] = cost;
+                    came_from[next] = Some(node);
+                    open.push(Candidate {
+                        estimate: cost + heuristic(next),
+                        node: next,
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    /// The strongly connected components of the graph, i.e. the groups of nodes that can all reach each other, using Tarjan's algorithm. Components are listed in reverse topological order: no component has an edge to a component listed after it.
+    ///
+    /// The search keeps its own stack instead of recursing, so large graphs don't overflow the call stack.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let len = self.nodes.len();
+        let mut next_index = 0;
+        let mut indices: Vec<Option<usize>> = vec![None; len];
+        let mut lowlinks = vec![0; len];
+        let mut on_stack = vec![false; len];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+
+        for root in 0..len {
+            if indices[root].is_some() {
+                continue;
+            }
+            // each frame is a node being visited, its edges, and how many of them have been followed
+            let mut call_stack = vec![(root, self.get_edges(root), 0)];
+            indices[root] = Some(next_index);
+            lowlinks[root] = next_index;
+            next_index += 1;
+            stack.push(root);
+            on_stack[root] = true;
+
+            while let Some((node, edges, next_edge)) = call_stack.last_mut() {
+                let node = *node;
+                if let Some(&next) = edges.get(*next_edge) {
+                    *next_edge += 1;
+                    match indices[next] {
+                        None => {
+                            indices[next] = Some(next_index);
+                            lowlinks[next] = next_index;
+                            next_index += 1;
+                            stack.push(next);
+                            on_stack[next] = true;
+                            call_stack.push((next, self.get_edges(next), 0));
+                        }
+                        Some(index) if on_stack[next] => {
+                            lowlinks[node] = lowlinks[node].min(index);
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+
+                // every edge has been followed, so the node is done
+                call_stack.pop();
+                if let Some((parent, ..)) = call_stack.last() {
+                    lowlinks[*parent] = lowlinks[*parent].min(lowlinks[node]);
+                }
+                if Some(lowlinks[node]) == indices[node] {
+                    let mut component = Vec::new();
+                    while let Some(other) = stack.pop() {
+                        on_stack[other] = false;
+                        component.push(other);
+                        if other == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+        components
+    }
+}
+
+/// A node waiting to be visited in [Graph::astar], ordered so that the lowest estimate is popped first from a `BinaryHeap`.
+struct Candidate {
+    estimate: f32,
+    node: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .estimate
+            .total_cmp(&self.estimate)
+            .then_with(|| other.node.cmp(&self.node))
     }
 }
@@ -0,0 +1,355 @@
+//! Exporting [StateMachine]s to Graphviz DOT and JSON, and importing them from JSON, so graphs can be inspected while debugging or authored outside of Rust.
+//!
+//! [Guard::Predicate] guards and transition actions are closures, so they can't be exported---edges with them are exported without a guard, and come back with the default [Guard::Activated].
+//!
+//! [StateMachine] implements `Serialize` and `Deserialize` through [StateMachineData], so logics holding state machines can be saved with serde. The same goes for closures there.
+
+use std::fmt::{self, Debug, Write};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::*;
+
+/// A [StateMachine] in a form that can be (de)serialized.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StateMachineData<NodeID> {
+    pub nodes: Vec<NodeID>,
+    pub edges: Vec<EdgeRecord>,
+    /// index of the current node
+    #[serde(default)]
+    pub current_node: usize,
+    /// the nodes activated in the condition table, by index
+    #[serde(default)]
+    pub activated: Vec<usize>,
+    #[serde(default = "default_storage")]
+    pub storage: GraphStorage,
+    /// the nodes activated in the condition table before it was last cleared, by index
+    #[serde(default)]
+    pub last_activated: Vec<usize>,
+    /// how many times the state machine has ticked
+    #[serde(default)]
+    pub ticks: u64,
+    /// the tick each node was last entered on. Every node was entered on tick 0 if this is empty.
+    #[serde(default)]
+    pub entered_at: Vec<u64>,
+}
+
+impl<NodeID> StateMachineData<NodeID> {
+    /// Checks that every node index in the data refers to one of its nodes.
+    pub fn validate(&self) -> Result<(), StateMachineDataError> {
+        let len = self.nodes.len();
+        for (i, edge) in self.edges.iter().enumerate() {
+            if edge.from >= len || edge.to >= len {
+                return Err(StateMachineDataError::Edge(i));
+            }
+            if let Some(guard) = &edge.guard {
+                if !guard.nodes_in(len) {
+                    return Err(StateMachineDataError::Guard(i));
+                }
+            }
+        }
+        if len > 0 && self.current_node >= len {
+            return Err(StateMachineDataError::CurrentNode(self.current_node));
+        }
+        if let Some(node) = self.activated.iter().find(|node| **node >= len) {
+            return Err(StateMachineDataError::Activated(*node));
+        }
+        if let Some(node) = self.last_activated.iter().find(|node| **node >= len) {
+            return Err(StateMachineDataError::LastActivated(*node));
+        }
+        Ok(())
+    }
+}
+
+/// Why a [StateMachineData] couldn't be turned into a [StateMachine]. Edges are given by their index in `edges`.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StateMachineDataError {
+    /// this edge starts or ends at a node that doesn't exist
+    Edge(usize),
+    /// this edge's guard names a node that doesn't exist
+    Guard(usize),
+    /// the current node doesn't exist
+    CurrentNode(usize),
+    /// an activated node doesn't exist
+    Activated(usize),
+    /// a node activated before the condition table was last cleared doesn't exist
+    LastActivated(usize),
+}
+
+impl fmt::Display for StateMachineDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StateMachineDataError::Edge(edge) => {
+                write!(f, "edge {} refers to a node that doesn't exist", edge)
+            }
+            StateMachineDataError::Guard(edge) => {
+                write!(
+                    f,
+                    "the guard on edge {} refers to a node that doesn't exist",
+                    edge
+                )
+            }
+            StateMachineDataError::CurrentNode(node) => {
+                write!(f, "current node {} doesn't exist", node)
+            }
+            StateMachineDataError::Activated(node) => {
+                write!(f, "activated node {} doesn't exist", node)
+            }
+            StateMachineDataError::LastActivated(node) => {
+                write!(f, "last activated node {} doesn't exist", node)
+            }
+        }
+    }
+}
+
+impl std::error::Error for StateMachineDataError {}
+
+fn default_storage() -> GraphStorage {
+    GraphStorage::AdjacencyMatrix
+}
+
+/// An edge of a [StateMachineData].
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EdgeRecord {
+    pub from: usize,
+    pub to: usize,
+    #[serde(default = "default_weight")]
+    pub weight: f32,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub priority: i32,
+    #[serde(default)]
+    pub guard: Option<GuardData>,
+}
+
+fn default_weight() -> f32 {
+    1.0
+}
+
+/// A [Guard] without closures.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GuardData {
+    Activated,
+    NodeActivated(usize),
+    Always,
+    Never,
+    After(u64),
+    All(Vec<GuardData>),
+    Any(Vec<GuardData>),
+    Not(Box<GuardData>),
+}
+
+impl GuardData {
+    /// `None` if the guard contains a [Guard::Predicate]
+    pub fn from_guard<NodeID: Copy + Eq + Hash>(guard: &Guard<NodeID>) -> Option<Self> {
+        let all = |guards: &[Guard<NodeID>]| -> Option<Vec<GuardData>> {
+            guards.iter().map(Self::from_guard).collect()
+        };
+        Some(match guard {
+            Guard::Activated => GuardData::Activated,
+            Guard::NodeActivated(node) => GuardData::NodeActivated(*node),
+            Guard::Always => GuardData::Always,
+            Guard::Never => GuardData::Never,
+            Guard::After(ticks) => GuardData::After(*ticks),
+            Guard::All(guards) => GuardData::All(all(guards)?),
+            Guard::Any(guards) => GuardData::Any(all(guards)?),
+            Guard::Not(guard) => GuardData::Not(Box::new(Self::from_guard(guard)?)),
+            Guard::Predicate(_) => return None,
+        })
+    }
+
+    pub fn to_guard<NodeID: Copy + Eq + Hash>(&self) -> Guard<NodeID> {
+        match self {
+            GuardData::Activated => Guard::Activated,
+            GuardData::NodeActivated(node) => Guard::NodeActivated(*node),
+            GuardData::Always => Guard::Always,
+            GuardData::Never => Guard::Never,
+            GuardData::After(ticks) => Guard::After(*ticks),
+            GuardData::All(guards) => Guard::All(guards.iter().map(Self::to_guard).collect()),
+            GuardData::Any(guards) => Guard::Any(guards.iter().map(Self::to_guard).collect()),
+            GuardData::Not(guard) => Guard::Not(Box::new(guard.to_guard())),
+        }
+    }
+
+    /// if every node the guard names is less than `len`
+    fn nodes_in(&self, len: usize) -> bool {
+        match self {
+            GuardData::NodeActivated(node) => *node < len,
+            GuardData::All(guards) | GuardData::Any(guards) => {
+                guards.iter().all(|guard| guard.nodes_in(len))
+            }
+            GuardData::Not(guard) => guard.nodes_in(len),
+            _ => true,
+        }
+    }
+}
+
+impl<NodeID: Copy + Eq + Hash> StateMachine<NodeID> {
+    /// Copies the state machine's nodes, edges, current node, condition tables, and clock.
+    pub fn to_data(&self) -> StateMachineData<NodeID> {
+        let mut edges = Vec::new();
+        for from in 0..self.graph.nodes.len() {
+            for to in self.graph.get_edges(from) {
+                let data = self.graph.edge_data(from, to).unwrap();
+                let transition = self.transitions.get(&(from, to));
+                edges.push(EdgeRecord {
+                    from,
+                    to,
+                    weight: data.weight,
+                    label: data.label.clone(),
+                    priority: transition.map(|t| t.priority).unwrap_or(0),
+                    guard: transition.and_then(|t| GuardData::from_guard(&t.guard)),
+                });
+            }
+        }
+
+        StateMachineData {
+            nodes: self.graph.nodes.clone(),
+            edges,
+            current_node: self.current_node,
+            activated: (0..self.conditions.len())
+                .filter(|node| self.conditions[*node])
+                .collect(),
+            storage: self.graph.storage(),
+            last_activated: (0..self.last_conditions.len())
+                .filter(|node| self.last_conditions[*node])
+                .collect(),
+            ticks: self.ticks,
+            entered_at: self.entered_at.clone(),
+        }
+    }
+
+    /// Builds a state machine from exported data. Edges with a guard or a nonzero priority get a [Transition].
+    ///
+    /// Returns an error without building anything if an edge, guard, condition table, or the current node refers to a node that doesn't exist.
+    pub fn from_data(data: &StateMachineData<NodeID>) -> Result<Self, StateMachineDataError> {
+        data.validate()?;
+        let mut machine = Self::new();
+        machine.graph.set_storage(data.storage);
+        machine.add_nodes(&data.nodes);
+        for edge in data.edges.iter() {
+            machine.graph.add_edge_with_data(
+                edge.from,
+                edge.to,
+                EdgeData {
+                    weight: edge.weight,
+                    label: edge.label.clone(),
+                },
+            );
+            if edge.guard.is_some() || edge.priority != 0 {
+                let mut transition = Transition::new();
+                transition.priority = edge.priority;
+                if let Some(guard) = &edge.guard {
+                    transition.guard = guard.to_guard();
+                }
+                machine.transitions.insert((edge.from, edge.to), transition);
+            }
+        }
+        for node in data.activated.iter() {
+            machine.conditions[*node] = true;
+        }
+        for node in data.last_activated.iter() {
+            machine.last_conditions[*node] = true;
+        }
+        machine.ticks = data.ticks;
+        if data.entered_at.len() == data.nodes.len() {
+            machine.entered_at = data.entered_at.clone();
+        }
+        machine.current_node = data.current_node;
+        Ok(machine)
+    }
+
+    /// Writes the state machine as JSON. See [StateMachineData] for the format.
+    pub fn to_json(&self) -> String
+    where
+        NodeID: Serialize,
+    {
+        serde_json::to_string_pretty(&self.to_data()).expect("error writing state machine")
+    }
+
+    /// Reads a state machine from JSON written by [StateMachine::to_json] or by hand. Only `nodes` and `edges` (with `from` and `to`) are required. Data that [StateMachine::from_data] would reject is a JSON error.
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error>
+    where
+        NodeID: DeserializeOwned,
+    {
+        serde_json::from_str(json)
+    }
+
+    /// Writes the state machine as a Graphviz DOT digraph, ex. for `dot -Tsvg`. The current node is drawn with a double outline, activated nodes are filled in, and edges are labelled with their label, weight, guard, and priority where they aren't the defaults.
+    pub fn to_dot(&self) -> String
+    where
+        NodeID: Debug,
+    {
+        let mut dot = String::from("digraph {\n");
+        for (i, node) in self.graph.nodes.iter().enumerate() {
+            let mut attrs = vec![format!("label=\"{}\"", escape(&format!("{:?}", node)))];
+            if i == self.current_node {
+                attrs.push("peripheries=2".to_string());
+            }
+            if self.conditions[i] {
+                attrs.push("style=filled".to_string());
+            }
+            writeln!(dot, "    {} [{}];", i, attrs.join(", ")).unwrap();
+        }
+
+        for edge in self.to_data().edges {
+            let mut label = Vec::new();
+            if let Some(text) = &edge.label {
+                label.push(text.clone());
+            }
+            if edge.weight != 1.0 {
+                label.push(format!("weight: {}", edge.weight));
+            }
+            // edges with a transition but no exportable guard have a predicate
+            let has_transition = self.transitions.contains_key(&(edge.from, edge.to));
+            match &edge.guard {
+                Some(GuardData::Activated) => {}
+                Some(guard) => label.push(format!("{:?}", guard)),
+                None if has_transition => label.push("predicate".to_string()),
+                None => {}
+            }
+            if edge.priority != 0 {
+                label.push(format!("priority: {}", edge.priority));
+            }
+
+            if label.is_empty() {
+                writeln!(dot, "    {} -> {};", edge.from, edge.to).unwrap();
+            } else {
+                writeln!(
+                    dot,
+                    "    {} -> {} [label=\"{}\"];",
+                    edge.from,
+                    edge.to,
+                    label
+                        .iter()
+                        .map(|line| escape(line))
+                        .collect::<Vec<_>>()
+                        .join("\\n")
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+impl<NodeID: Copy + Eq + Hash + Serialize> Serialize for StateMachine<NodeID> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_data().serialize(serializer)
+    }
+}
+
+impl<'de, NodeID: Copy + Eq + Hash + Deserialize<'de>> Deserialize<'de> for StateMachine<NodeID> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = StateMachineData::deserialize(deserializer)?;
+        Self::from_data(&data).map_err(serde::de::Error::custom)
+    }
+}
+
+/// escapes backslashes and quotes so text can go in a DOT string
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
@@ -0,0 +1,99 @@
+//! # Interactions
+//!
+//! A data-driven table of rules: when an event happens, perform some reactions. Rules are plain data, so they can be loaded from JSON (or RON, with the `ron` feature) and changed without recompiling the game.
+//!
+//! The table doesn't know about any particular logic. Games define one type describing the events they care about and one describing the reactions they can perform, usually enums wrapping the events and reactions of each logic, then look up the reactions for each event produced during a frame and apply them to their logics.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// A rule: when `when` happens, perform every reaction in `then`, in order.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Interaction<Event, Reaction> {
+    pub when: Event,
+    pub then: Vec<Reaction>,
+}
+
+/// A list of [Interaction]s. Serialized as a list of `{ "when": ..., "then": [...] }` objects.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InteractionTable<Event, Reaction> {
+    pub interactions: Vec<Interaction<Event, Reaction>>,
+}
+
+impl<Event: PartialEq, Reaction> InteractionTable<Event, Reaction> {
+    pub fn new() -> Self {
+        Self {
+            interactions: Vec::new(),
+        }
+    }
+
+    /// Adds a rule performing `reaction` when `event` happens. Adding more reactions for the same event adds them to the existing rule.
+    pub fn add_interaction(&mut self, event: Event, reaction: Reaction) {
+        match self
+            .interactions
+            .iter_mut()
+            .find(|interaction| interaction.when == event)
+        {
+            Some(interaction) => interaction.then.push(reaction),
+            None => self.interactions.push(Interaction {
+                when: event,
+                then: vec![reaction],
+            }),
+        }
+    }
+
+    /// Removes every rule for the given event.
+    pub fn remove_interactions(&mut self, event: &Event) {
+        self.interactions
+            .retain(|interaction| interaction.when != *event);
+    }
+
+    /// The reactions to perform when the given event happens, in the order they were added.
+    pub fn get_reactions(&self, event: &Event) -> Vec<&Reaction> {
+        self.interactions
+            .iter()
+            .filter(|interaction| interaction.when == *event)
+            .flat_map(|interaction| interaction.then.iter())
+            .collect()
+    }
+
+    /// The reactions to perform for each of the given events, paired with the event that caused them.
+    pub fn dispatch(&self, events: impl IntoIterator<Item = Event>) -> Vec<(Event, &Reaction)>
+    where
+        Event: Clone,
+    {
+        let mut reactions = Vec::new();
+        for event in events {
+            for reaction in self.get_reactions(&event) {
+                reactions.push((event.clone(), reaction));
+            }
+        }
+        reactions
+    }
+}
+
+impl<Event, Reaction> InteractionTable<Event, Reaction>
+where
+    Event: Serialize + DeserializeOwned,
+    Reaction: Serialize + DeserializeOwned,
+{
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("error writing interactions")
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    #[cfg(feature = "ron")]
+    pub fn to_ron(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("error writing interactions")
+    }
+}
@@ -6,19 +6,32 @@
 //!
 //! The descriptions of logics in the modules are lightly modified from Prof Osborn's dissertation.
 //!
-//! Requires at least Rust 1.51---if this doesn't compile, update your rustc.
+//! The logics only depend on glam for their math, so they build and run headless with `--no-default-features`. The default `macroquad-render` feature adds the `animation` module and macroquad keyboard input. Check that they still do with `cargo test -p asterism --no-default-features`.
+//!
+//! Requires at least Rust 1.62---if this doesn't compile, update your rustc.
 #![allow(clippy::new_without_default)]
 #![allow(clippy::upper_case_acronyms)]
+pub mod ai;
+#[cfg(feature = "macroquad-render")]
 pub mod animation;
+pub mod bindings;
 pub mod collision;
 pub mod control;
+pub mod dialogue;
 pub mod entity_state;
 pub mod graph;
+pub mod interactions;
 pub mod linking;
+pub mod network;
 pub mod physics;
+pub mod registry;
 pub mod resources;
+pub mod rollback;
+pub mod scheduler;
 pub mod tables;
+pub mod terminal;
 
+pub use registry::DynLogic;
 pub use tables::OutputTable;
 
 /// An operational logic
@@ -36,6 +49,22 @@ pub trait Logic:
     /// the data of the logic associated with its identity (`<Self as Logic>::Ident`).
     type IdentData: Clone;
 
+    /// what the logic needs to update, ex. an input handler. `()` for logics that don't need anything.
+    type Context;
+
+    /// updates the logic, producing new events
+    fn update(&mut self, context: &Self::Context);
+
+    /// the events produced during the last update
+    fn events(&self) -> Vec<Self::Event> {
+        OutputTable::<Self::Event>::get_table(self)
+    }
+
+    /// the data of every ""entity"" of the logic
+    fn idents(&self) -> Vec<(Self::Ident, Self::IdentData)> {
+        OutputTable::<(Self::Ident, Self::IdentData)>::get_table(self)
+    }
+
     /// processes the reaction if a predicate condition is met
     fn handle_predicate(&mut self, reaction: &Self::Reaction);
 
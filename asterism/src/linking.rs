@@ -5,41 +5,66 @@
 //! Linking logics are incredibly broad and have a wide range of uses.
 use crate::graph::StateMachine;
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
+use serde::{Deserialize, Serialize};
+use std::hash::Hash;
 
 /// A generic linking logic. See [StateMachine][crate::graph::StateMachine] documentation for more information.
 ///
 /// I think this is the exact same code as FlatEntityState actually. The difference might make become more clear when rendering?
-pub struct GraphedLinking<NodeID: Copy + Eq> {
+#[derive(Clone, Serialize, Deserialize)]
+pub struct GraphedLinking<NodeID: Copy + Eq + Hash> {
     /// A vec of state machines
     pub graphs: Vec<StateMachine<NodeID>>,
     /// If the state machine has just traversed an edge or not
     pub just_traversed: Vec<Option<usize>>,
+    /// Nodes and edges added or removed before the last update
+    pub mutations: Vec<LinkingEvent>,
+    /// Nodes and edges added or removed since the last update, reported after the next one
+    queued_mutations: Vec<LinkingEvent>,
 }
 
-impl<NodeID: Copy + Eq> GraphedLinking<NodeID> {
+impl<NodeID: Copy + Eq + Hash> GraphedLinking<NodeID> {
     pub fn new() -> Self {
         Self {
             graphs: Vec::new(),
             just_traversed: Vec::new(),
+            mutations: Vec::new(),
+            queued_mutations: Vec::new(),
         }
     }
 
     /// Updates the linking logic.
     ///
-    /// Check the status of all the links from the current node in the condition table. If any of those links are `true`, i.e. that node can be moved to, move the current position.
+    /// Check the guards of all the links from the current node (by default, their status in the condition table). If any of those links can be traversed, move the current position along the one with the highest priority. The condition table is cleared afterwards.
     pub fn update(&mut self) {
         self.just_traversed.fill(None);
+        self.mutations = std::mem::take(&mut self.queued_mutations);
         for (graph, traversed) in self.graphs.iter_mut().zip(self.just_traversed.iter_mut()) {
-            for i in graph.graph.get_edges(graph.current_node) {
-                if graph.conditions[i] {
-                    *traversed = Some(graph.current_node);
-                    graph.current_node = i;
-                    break;
-                }
+            graph.tick();
+            if let Some(next) = graph.next_node_from(graph.current_node) {
+                graph.run_action(graph.current_node, next);
+                *traversed = Some(graph.current_node);
+                graph.enter(next);
             }
+            graph.clear_conditions();
         }
     }
 
+    /// The nodes that can eventually be reached from the current node of the given graph, ignoring conditions, in breadth-first order.
+    pub fn reachable(&self, graph: usize) -> Vec<usize> {
+        let graph = &self.graphs[graph];
+        graph.graph.bfs(graph.current_node)
+    }
+
+    /// The cheapest path from the current node of the given graph to `node`, using the weights of the edges. See [Graph::shortest_path][crate::graph::Graph::shortest_path].
+    pub fn path_to(&self, graph: usize, node: usize) -> Option<Vec<usize>> {
+        let graph = &self.graphs[graph];
+        graph
+            .graph
+            .shortest_path(graph.current_node, node)
+            .map(|(_, path)| path)
+    }
+
     /// Adds a map of nodes to the logic.
     ///
     /// `starting_pos` is where the node the graph traversal starts on. `edges` is a list of adjacency lists. All conditions are set to false.
@@ -66,17 +91,22 @@ impl<NodeID: Copy + Eq> GraphedLinking<NodeID> {
     }
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct LinkingEvent {
     pub graph: usize,
     pub node: usize,
     pub event_type: LinkingEventType,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum LinkingEventType {
     Activated,
     Traversed(usize), // last node (which edge)
+    NodeAdded,
+    /// the index the node had before it was removed
+    NodeRemoved,
+    EdgeAdded(usize), // node the edge comes from
+    EdgeRemoved(usize),
 }
 impl EventType for LinkingEventType {}
 
@@ -87,25 +117,34 @@ impl Event for LinkingEvent {
     }
 }
 
-pub enum LinkingReaction {
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LinkingReaction<NodeID> {
     Activate(usize, usize),
     Traverse(usize, usize),
-    // AddNode(usize),
-    // AddEdge(usize, (usize, usize))
-    // RemoveNode(usize),
-    // RemoveEdge(usize, (usize, usize)),
+    /// adds a node with the given ID to a graph, without any edges
+    AddNode(usize, NodeID),
+    /// adds an edge `(from, to)` to a graph
+    AddEdge(usize, (usize, usize)),
+    /// removes a node and all of its edges from a graph. NOTE that this shifts the indices of every node after it down by one. If the graph's current node is removed, it goes back to its first node. Ignored if it's the graph's only node.
+    RemoveNode(usize, usize),
+    RemoveEdge(usize, (usize, usize)),
 }
 
-impl Reaction for LinkingReaction {}
+impl<NodeID> Reaction for LinkingReaction<NodeID> {}
 
-impl<NodeID: Copy + Eq> Logic for GraphedLinking<NodeID> {
+impl<NodeID: Copy + Eq + Hash> Logic for GraphedLinking<NodeID> {
     type Event = LinkingEvent;
-    type Reaction = LinkingReaction;
+    type Reaction = LinkingReaction<NodeID>;
 
     /// index of graph
     type Ident = usize;
     /// list of graph nodes and edges
     type IdentData = NodeID;
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        GraphedLinking::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
@@ -114,6 +153,46 @@ impl<NodeID: Copy + Eq> Logic for GraphedLinking<NodeID> {
                 self.just_traversed[*graph] = Some(self.graphs[*graph].current_node);
                 self.graphs[*graph].set_current_node(*node);
             }
+            LinkingReaction::AddNode(graph, id) => {
+                self.graphs[*graph].add_node(*id);
+                self.queued_mutations.push(LinkingEvent {
+                    graph: *graph,
+                    node: self.graphs[*graph].graph.nodes().len() - 1,
+                    event_type: LinkingEventType::NodeAdded,
+                });
+            }
+            LinkingReaction::AddEdge(graph, (from, to)) => {
+                self.graphs[*graph].graph.add_edge(*from, *to);
+                self.queued_mutations.push(LinkingEvent {
+                    graph: *graph,
+                    node: *to,
+                    event_type: LinkingEventType::EdgeAdded(*from),
+                });
+            }
+            LinkingReaction::RemoveNode(graph, node) => {
+                if !self.graphs[*graph].remove_node(*node) {
+                    return;
+                }
+                let traversed = &mut self.just_traversed[*graph];
+                match traversed {
+                    Some(last) if *last == *node => *traversed = None,
+                    Some(last) if *last > *node => *last -= 1,
+                    _ => {}
+                }
+                self.queued_mutations.push(LinkingEvent {
+                    graph: *graph,
+                    node: *node,
+                    event_type: LinkingEventType::NodeRemoved,
+                });
+            }
+            LinkingReaction::RemoveEdge(graph, (from, to)) => {
+                self.graphs[*graph].remove_edge(*from, *to);
+                self.queued_mutations.push(LinkingEvent {
+                    graph: *graph,
+                    node: *to,
+                    event_type: LinkingEventType::EdgeRemoved(*from),
+                });
+            }
         }
     }
 
@@ -123,9 +202,9 @@ impl<NodeID: Copy + Eq> Logic for GraphedLinking<NodeID> {
 
     fn update_ident_data(&mut self, ident: Self::Ident, data: Self::IdentData) {
         let graph = &mut self.graphs[ident];
-        let node = graph.graph.nodes.iter().position(|id| *id == data);
+        let node = graph.graph.node_idx(&data);
         if let Some(idx) = node {
-            graph.current_node = idx;
+            graph.enter(idx);
         }
     }
 }
@@ -135,7 +214,7 @@ type QueryIdent<ID> = (
     <GraphedLinking<ID> as Logic>::IdentData,
 );
 
-impl<ID: Copy + Eq> OutputTable<QueryIdent<ID>> for GraphedLinking<ID> {
+impl<ID: Copy + Eq + Hash> OutputTable<QueryIdent<ID>> for GraphedLinking<ID> {
     fn get_table(&self) -> Vec<QueryIdent<ID>> {
         (0..self.graphs.len())
             .map(|idx| (idx, self.get_ident_data(idx)))
@@ -145,9 +224,9 @@ impl<ID: Copy + Eq> OutputTable<QueryIdent<ID>> for GraphedLinking<ID> {
 
 type QueryEvent<ID> = <GraphedLinking<ID> as Logic>::Event;
 
-impl<ID: Copy + Eq> OutputTable<QueryEvent<ID>> for GraphedLinking<ID> {
+impl<ID: Copy + Eq + Hash> OutputTable<QueryEvent<ID>> for GraphedLinking<ID> {
     fn get_table(&self) -> Vec<QueryEvent<ID>> {
-        let mut events = Vec::new();
+        let mut events = self.mutations.clone();
         for (i, (graph, traversed)) in self
             .graphs
             .iter()
@@ -162,7 +241,7 @@ impl<ID: Copy + Eq> OutputTable<QueryEvent<ID>> for GraphedLinking<ID> {
                 };
                 events.push(event);
             }
-            for (node, activated) in graph.conditions.iter().enumerate() {
+            for (node, activated) in graph.last_conditions.iter().enumerate() {
                 if *activated && node != graph.current_node {
                     let event = LinkingEvent {
                         graph: i,
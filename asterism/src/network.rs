@@ -0,0 +1,295 @@
+//! # Network control
+//!
+//! Control logics map inputs onto actions, and the inputs don't have to come from the same computer. [NetworkControl] sends the local player's [Values] to a remote peer every frame and fills in the remote player's set of mappings in a [KeyboardControl] with the values it gets back, so both peers see the same input on the same frame.
+//!
+//! Input is synchronized with a fixed delay: input read on frame `f` is used on frame `f + delay` on both peers, and the first `delay` frames have no input. As long as packets arrive within `delay` frames, neither peer waits. When they don't, [NetworkControl::update] returns false and the game should skip running that frame until the remote input shows up.
+//!
+//! Packets are sent over a [Transport]: [UdpTransport] for real networks, or [LoopbackTransport] for two peers in the same process, ex. when testing. UDP packets can be lost, so every packet repeats all the input the peer hasn't acknowledged yet. Packets that can't be decoded are dropped like lost ones.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+use serde::{Deserialize, Serialize};
+
+use crate::control::{InputWrapper, KeyboardControl, Values};
+
+/// Sends and receives packets to and from a single peer.
+pub trait Transport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()>;
+
+    /// the next packet received, or `None` if there aren't any. Shouldn't block.
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>>;
+}
+
+/// the largest packet [UdpTransport] can receive
+pub const MAX_PACKET_SIZE: usize = 65507;
+
+pub struct UdpTransport {
+    socket: UdpSocket,
+    buf: Vec<u8>,
+}
+
+impl UdpTransport {
+    /// Binds to a local address. Use port 0 to pick any free port, see [UdpTransport::local_addr].
+    pub fn bind(local: impl ToSocketAddrs) -> io::Result<Self> {
+        let socket = UdpSocket::bind(local)?;
+        socket.set_nonblocking(true)?;
+        Ok(Self {
+            socket,
+            buf: vec![0; MAX_PACKET_SIZE],
+        })
+    }
+
+    /// Only sends to and receives from `peer` from now on.
+    pub fn connect(&self, peer: impl ToSocketAddrs) -> io::Result<()> {
+        self.socket.connect(peer)
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+}
+
+impl Transport for UdpTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.socket.send(packet).map(|_| ())
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self.socket.recv(&mut self.buf) {
+            Ok(len) => Ok(Some(self.buf[..len].to_vec())),
+            // some platforms report packets sent before the peer was listening as refused, but the peer may just not have started yet
+            Err(err)
+                if err.kind() == io::ErrorKind::WouldBlock
+                    || err.kind() == io::ErrorKind::ConnectionRefused =>
+            {
+                Ok(None)
+            }
+            Err(err) => Err(err),
+        }
+    }
+}
+
+/// One end of an in-memory connection. Packets always arrive, in order.
+pub struct LoopbackTransport {
+    sender: Sender<Vec<u8>>,
+    receiver: Receiver<Vec<u8>>,
+}
+
+impl LoopbackTransport {
+    /// two transports connected to each other
+    pub fn pair() -> (Self, Self) {
+        let (a_sender, b_receiver) = channel();
+        let (b_sender, a_receiver) = channel();
+        (
+            Self {
+                sender: a_sender,
+                receiver: a_receiver,
+            },
+            Self {
+                sender: b_sender,
+                receiver: b_receiver,
+            },
+        )
+    }
+}
+
+impl Transport for LoopbackTransport {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.sender
+            .send(packet.to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "loopback peer was dropped"))
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        match self.receiver.try_recv() {
+            Ok(packet) => Ok(Some(packet)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                "loopback peer was dropped",
+            )),
+        }
+    }
+}
+
+/// what peers send each other every frame
+#[derive(Serialize, Deserialize)]
+struct InputPacket {
+    /// the first frame of the receiver's input the sender doesn't have yet
+    ack: u64,
+    /// the frame of the first input
+    first: u64,
+    /// the sender's input for consecutive frames
+    inputs: Vec<Vec<Values>>,
+}
+
+/// Exchanges input with a remote peer for one set of mappings each.
+pub struct NetworkControl<T: Transport> {
+    pub transport: T,
+    /// the set of mappings the local player's input is read from and sent to the peer
+    pub local_set: usize,
+    /// the set of mappings filled in with the peer's input
+    pub remote_set: usize,
+    /// how many frames after it's read input is used
+    pub delay: u64,
+    /// the next frame to fill in input for
+    frame: u64,
+    /// local input by the frame it'll be used on, kept until the peer has it
+    local: BTreeMap<u64, Vec<Values>>,
+    /// remote input by the frame it'll be used on
+    remote: BTreeMap<u64, Vec<Values>>,
+    /// the first frame of local input the peer doesn't have yet
+    peer_ack: u64,
+    /// the local set's values as the keyboard last left them, before they were replaced with delayed input
+    live: Vec<Values>,
+}
+
+impl<T: Transport> NetworkControl<T> {
+    /// Both peers need the same delay, with their local and remote sets swapped.
+    pub fn new(transport: T, local_set: usize, remote_set: usize, delay: u64) -> Self {
+        Self {
+            transport,
+            local_set,
+            remote_set,
+            delay,
+            frame: 0,
+            local: BTreeMap::new(),
+            remote: BTreeMap::new(),
+            peer_ack: 0,
+            live: Vec::new(),
+        }
+    }
+
+    /// the next frame input will be filled in for, i.e. how many frames have been run
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Updates the control logic, sends the local input, and fills in both players' sets of mappings with the input for the current frame. Call instead of [KeyboardControl::update].
+    ///
+    /// Returns false if the peer's input for this frame hasn't arrived, in which case the sets aren't filled in and the frame shouldn't be run. Input read while waiting is sent with the next frame's.
+    pub fn update<ID, Wrapper>(
+        &mut self,
+        control: &mut KeyboardControl<ID, Wrapper>,
+        context: &Wrapper::InputHelper,
+    ) -> io::Result<bool>
+    where
+        ID: Copy + Eq + Ord,
+        Wrapper: InputWrapper,
+    {
+        // the keyboard changes values relative to what they were, so it needs the local set back as it left it
+        if let Some(values) = control.values.get_mut(self.local_set) {
+            if values.len() == self.live.len() {
+                values.clone_from(&self.live);
+            }
+        }
+        control.update(context);
+        self.live = control
+            .values
+            .get(self.local_set)
+            .cloned()
+            .unwrap_or_default();
+
+        let target = self.frame + self.delay;
+        if !self.local.contains_key(&target) {
+            let input = self.read_live();
+            self.local.insert(target, input);
+        }
+
+        while let Some(packet) = self.transport.recv()? {
+            self.receive(&packet);
+        }
+        self.send()?;
+
+        let (local, remote) = if self.frame < self.delay {
+            (
+                no_input(control, self.local_set),
+                no_input(control, self.remote_set),
+            )
+        } else {
+            match self.remote.remove(&self.frame) {
+                Some(remote) => (self.local[&self.frame].clone(), remote),
+                None => return Ok(false),
+            }
+        };
+        control.replay_set(self.local_set, &local);
+        control.replay_set(self.remote_set, &remote);
+        self.frame += 1;
+
+        // the peer has everything before its ack, so stop resending it
+        let keep = self.peer_ack.min(self.frame);
+        self.local.retain(|frame, _| *frame >= keep);
+        Ok(true)
+    }
+
+    /// The live values, changed relative to the last input sent instead of the last frame. Frames spent waiting on the peer aren't sent, so this keeps presses and releases during them.
+    fn read_live(&self) -> Vec<Values> {
+        let last = self.local.values().next_back();
+        self.live
+            .iter()
+            .enumerate()
+            .map(|(i, values)| {
+                let last = last
+                    .and_then(|last| last.get(i))
+                    .map_or(0.0, |last| last.value);
+                Values {
+                    changed_by: values.value - last,
+                    value: values.value,
+                }
+            })
+            .collect()
+    }
+
+    /// Stores the peer's input from a packet. Packets that can't be decoded (ex. corrupted ones, or ones from something else sending to the same port) are ignored, since the input in them will be resent.
+    fn receive(&mut self, packet: &[u8]) {
+        let packet: InputPacket = match serde_json::from_slice(packet) {
+            Ok(packet) => packet,
+            Err(_) => return,
+        };
+        self.peer_ack = self.peer_ack.max(packet.ack);
+        for (frame, input) in (packet.first..).zip(packet.inputs) {
+            if frame >= self.frame {
+                self.remote.entry(frame).or_insert(input);
+            }
+        }
+    }
+
+    /// sends every frame of local input the peer hasn't acknowledged
+    fn send(&mut self) -> io::Result<()> {
+        let first = self.peer_ack.max(self.delay);
+        let packet = InputPacket {
+            ack: self.ack(),
+            first,
+            inputs: self
+                .local
+                .range(first..)
+                .map(|(_, input)| input.clone())
+                .collect(),
+        };
+        let packet = serde_json::to_vec(&packet)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        self.transport.send(&packet)
+    }
+
+    /// the first frame of remote input that hasn't arrived yet
+    fn ack(&self) -> u64 {
+        let mut frame = self.frame.max(self.delay);
+        while self.remote.contains_key(&frame) {
+            frame += 1;
+        }
+        frame
+    }
+}
+
+/// nothing pressed in a set of mappings, for the frames before any input is used
+fn no_input<ID, Wrapper>(control: &KeyboardControl<ID, Wrapper>, set: usize) -> Vec<Values>
+where
+    ID: Copy + Eq + Ord,
+    Wrapper: InputWrapper,
+{
+    let len = control.values.get(set).map_or(0, Vec::len);
+    vec![Values::new(); len]
+}
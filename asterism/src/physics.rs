@@ -3,16 +3,18 @@
 //! Physics logics communicate that physical laws govern the movement of some in-game entities. They update and honor objects' physical properties like position, velocity, density, etc., according to physical laws integrated over time.
 
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
-use macroquad::math::Vec2;
+use glam::Vec2;
+use serde::{Deserialize, Serialize};
 
 /// A physics logic using 2d points.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct PointPhysics {
     pub positions: Vec<Vec2>,
     pub velocities: Vec<Vec2>,
     pub accelerations: Vec<Vec2>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct PointPhysData {
     pub pos: Vec2,
     pub vel: Vec2,
@@ -25,6 +27,11 @@ impl Logic for PointPhysics {
 
     type Ident = usize;
     type IdentData = PointPhysData;
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        PointPhysics::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
@@ -98,7 +105,7 @@ impl PointPhysics {
     }
 }
 
-#[derive(PartialEq, Clone, Copy, Debug)]
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum PhysicsReaction {
     SetPos(usize, Vec2),
     SetVel(usize, Vec2),
@@ -108,7 +115,7 @@ pub enum PhysicsReaction {
 }
 impl Reaction for PhysicsReaction {}
 
-#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct PhysicsEvent {
     ent: usize,
     event_type: PhysicsEventType,
@@ -121,7 +128,7 @@ impl Event for PhysicsEvent {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
 pub enum PhysicsEventType {
     VelChange,
     PosChange,
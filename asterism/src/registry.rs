@@ -0,0 +1,245 @@
+//! # Registry
+//!
+//! [Logic] can't be made into a trait object: its events, reactions, idents, and update contexts are associated types. [DynLogic] is an object-safe version of it that passes them around as [Any], so logics of different types can be stored together in a [LogicRegistry] and looked at without knowing what they are, ex. by a debugger listing every logic's events or a save system going through a game's logics.
+//!
+//! Every [Logic] whose types are `'static` is a [DynLogic].
+
+use std::any::{type_name, Any, TypeId};
+use std::fmt::{self, Debug};
+
+use crate::Logic;
+
+/// An object-safe [Logic].
+pub trait DynLogic: Any {
+    /// the type name of the logic
+    fn logic_type(&self) -> &'static str;
+
+    /// Updates the logic if `context` is the logic's `Context` type, otherwise returns [DynLogicError::MismatchedContext].
+    fn update(&mut self, context: &dyn Any) -> Result<(), DynLogicError>;
+
+    /// Handles a reaction if it's the logic's `Reaction` type, otherwise returns [DynLogicError::MismatchedReaction].
+    fn handle_reaction(&mut self, reaction: &dyn Any) -> Result<(), DynLogicError>;
+
+    /// the [TypeId] of the logic's `Event` type
+    fn event_type(&self) -> TypeId;
+
+    /// the events produced during the last update, as the logic's `Event` type
+    fn events(&self) -> Vec<Box<dyn Any>>;
+
+    /// the events produced during the last update, formatted with [Debug]
+    fn debug_events(&self) -> Vec<String>;
+
+    fn as_any(&self) -> &dyn Any;
+
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+impl<L> DynLogic for L
+where
+    L: Logic + 'static,
+    L::Event: Debug + 'static,
+    L::Reaction: 'static,
+    L::Context: 'static,
+{
+    fn logic_type(&self) -> &'static str {
+        type_name::<L>()
+    }
+
+    fn update(&mut self, context: &dyn Any) -> Result<(), DynLogicError> {
+        let context = expect_context::<L, L::Context>(context)?;
+        Logic::update(self, context);
+        Ok(())
+    }
+
+    fn handle_reaction(&mut self, reaction: &dyn Any) -> Result<(), DynLogicError> {
+        let reaction =
+            reaction
+                .downcast_ref::<L::Reaction>()
+                .ok_or(DynLogicError::MismatchedReaction {
+                    logic: type_name::<L>(),
+                    expected: type_name::<L::Reaction>(),
+                })?;
+        self.handle_predicate(reaction);
+        Ok(())
+    }
+
+    fn event_type(&self) -> TypeId {
+        TypeId::of::<L::Event>()
+    }
+
+    fn events(&self) -> Vec<Box<dyn Any>> {
+        Logic::events(self)
+            .into_iter()
+            .map(|event| Box::new(event) as Box<dyn Any>)
+            .collect()
+    }
+
+    fn debug_events(&self) -> Vec<String> {
+        Logic::events(self)
+            .iter()
+            .map(|event| format!("{:?}", event))
+            .collect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+/// checks that an update context is the type a logic needs
+fn expect_context<L, Context: 'static>(context: &dyn Any) -> Result<&Context, DynLogicError> {
+    context
+        .downcast_ref::<Context>()
+        .ok_or(DynLogicError::MismatchedContext {
+            logic: type_name::<L>(),
+            expected: type_name::<Context>(),
+        })
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum DynLogicError {
+    LogicNotFound(String),
+    MismatchedReaction {
+        logic: &'static str,
+        expected: &'static str,
+    },
+    MismatchedContext {
+        logic: &'static str,
+        expected: &'static str,
+    },
+    /// the logic's events aren't the requested type
+    MismatchedEvents {
+        logic: &'static str,
+        requested: &'static str,
+    },
+}
+
+impl fmt::Display for DynLogicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DynLogicError::LogicNotFound(name) => write!(f, "logic {:?} not found", name),
+            DynLogicError::MismatchedReaction { logic, expected } => {
+                write!(f, "{} expected a reaction of type {}", logic, expected)
+            }
+            DynLogicError::MismatchedContext { logic, expected } => {
+                write!(
+                    f,
+                    "{} expected an update context of type {}",
+                    logic, expected
+                )
+            }
+            DynLogicError::MismatchedEvents { logic, requested } => {
+                write!(f, "{} doesn't produce events of type {}", logic, requested)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DynLogicError {}
+
+/// Owns logics of any type by name, in the order they were registered.
+pub struct LogicRegistry {
+    /// each logic with its name and the [TypeId] of its events
+    logics: Vec<(String, TypeId, Box<dyn DynLogic>)>,
+}
+
+impl LogicRegistry {
+    pub fn new() -> Self {
+        Self { logics: Vec::new() }
+    }
+
+    /// Panics if there's already a logic with this name.
+    pub fn register(&mut self, name: &str, logic: impl DynLogic) {
+        assert!(
+            self.position(name).is_err(),
+            "logic {:?} already registered",
+            name
+        );
+        let event_type = logic.event_type();
+        self.logics
+            .push((name.to_string(), event_type, Box::new(logic)));
+    }
+
+    pub fn remove(&mut self, name: &str) -> Result<Box<dyn DynLogic>, DynLogicError> {
+        let idx = self.position(name)?;
+        Ok(self.logics.remove(idx).2)
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.logics.iter().map(|(name, ..)| name.as_str()).collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &dyn DynLogic)> {
+        self.logics
+            .iter()
+            .map(|(name, _, logic)| (name.as_str(), logic.as_ref()))
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut dyn DynLogic)> {
+        self.logics
+            .iter_mut()
+            .map(|(name, _, logic)| (name.as_str(), logic.as_mut() as &mut dyn DynLogic))
+    }
+
+    pub fn get(&self, name: &str) -> Result<&dyn DynLogic, DynLogicError> {
+        let idx = self.position(name)?;
+        Ok(self.logics[idx].2.as_ref())
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut dyn DynLogic, DynLogicError> {
+        let idx = self.position(name)?;
+        Ok(self.logics[idx].2.as_mut())
+    }
+
+    /// the named logic as its concrete type, or `None` if it isn't one
+    pub fn get_as<L: 'static>(&self, name: &str) -> Option<&L> {
+        self.get(name).ok()?.as_any().downcast_ref()
+    }
+
+    pub fn get_as_mut<L: 'static>(&mut self, name: &str) -> Option<&mut L> {
+        self.get_mut(name).ok()?.as_any_mut().downcast_mut()
+    }
+
+    pub fn update(&mut self, name: &str, context: &dyn Any) -> Result<(), DynLogicError> {
+        self.get_mut(name)?.update(context)
+    }
+
+    /// Updates every logic in order with no context, stopping at the first one that needs one. Logics that need a context should be updated with [LogicRegistry::update] instead.
+    pub fn update_all(&mut self) -> Result<(), DynLogicError> {
+        for (_, _, logic) in self.logics.iter_mut() {
+            logic.update(&())?;
+        }
+        Ok(())
+    }
+
+    pub fn handle_reaction(&mut self, name: &str, reaction: &dyn Any) -> Result<(), DynLogicError> {
+        self.get_mut(name)?.handle_reaction(reaction)
+    }
+
+    /// the named logic's events from its last update, as `Event`. Returns [DynLogicError::MismatchedEvents] if the logic's events aren't `Event`s, even if it has none right now.
+    pub fn events<Event: Copy + 'static>(&self, name: &str) -> Result<Vec<Event>, DynLogicError> {
+        let (_, event_type, logic) = &self.logics[self.position(name)?];
+        if *event_type != TypeId::of::<Event>() {
+            return Err(DynLogicError::MismatchedEvents {
+                logic: logic.logic_type(),
+                requested: type_name::<Event>(),
+            });
+        }
+        Ok(logic
+            .events()
+            .iter()
+            .filter_map(|event| event.downcast_ref::<Event>().copied())
+            .collect())
+    }
+
+    fn position(&self, name: &str) -> Result<usize, DynLogicError> {
+        self.logics
+            .iter()
+            .position(|(logic, ..)| logic == name)
+            .ok_or_else(|| DynLogicError::LogicNotFound(name.to_string()))
+    }
+}
@@ -3,34 +3,50 @@
 //! Resource logics communicate that generic or specific resources can be created, destroyed, converted, or transferred between abstract or concrete locations. They create, destroy, and exchange (usually) discrete quantities of generic or specific resources in or between abstract or concrete locations on demand or over time, and trigger other actions when these transactions take place.
 
 use crate::{tables::OutputTable, Event, EventType, Logic, Reaction};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
 use std::collections::BTreeMap;
-use std::fmt::Debug;
-use std::ops::{Add, AddAssign};
+use std::fmt::{self, Debug, Display};
+use std::str::FromStr;
 
 /// A resource logic that queues transactions, then applies them all at once when updating.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct QueuedResources<ID, Value>
 where
     ID: Copy + Ord + Debug,
-    Value: Add<Output = Value> + AddAssign + Ord + Copy,
+    Value: ResourceValue,
 {
     /// The items involved, and their values.
+    #[serde(with = "pools")]
     pub items: BTreeMap<ID, (Value, Value, Value)>, // value, min, max
     /// Each transaction is a list of items involved in the transaction and the amount they're being changed.
     pub transactions: Vec<(ID, Transaction<Value>)>,
     /// A Vec of all transactions and if they were able to be completed or not. If yes, supply a Vec of the IDs of successful transactions; if no, supply the ID of the pool that caused the error and a reason (see [ResourceError]).
     pub completed: Vec<Result<ID, (ID, ResourceError)>>,
+    /// Values that pools are being watched for. See [Threshold].
+    pub thresholds: Vec<Threshold<ID, Value>>,
+    /// The thresholds crossed during the last update, by their ID.
+    pub crossed: Vec<ThresholdID>,
+    /// the ID the next threshold added gets
+    #[serde(default)]
+    next_threshold: usize,
 }
 
 impl<ID, Value> Logic for QueuedResources<ID, Value>
 where
     ID: Copy + Ord + Debug,
-    Value: Add<Output = Value> + AddAssign + Ord + Copy,
+    Value: ResourceValue,
 {
     type Event = ResourceEvent<ID>;
     type Reaction = ResourceReaction<ID, Value>;
 
     type Ident = ID;
     type IdentData = (Value, Value, Value);
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        QueuedResources::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         self.transactions.push(*reaction);
@@ -55,19 +71,25 @@ where
 impl<ID, Value> QueuedResources<ID, Value>
 where
     ID: Copy + Ord + Debug,
-    Value: Add<Output = Value> + AddAssign + Ord + Copy,
+    Value: ResourceValue,
 {
     pub fn new() -> Self {
         Self {
             items: BTreeMap::new(),
             transactions: Vec::new(),
             completed: Vec::new(),
+            thresholds: Vec::new(),
+            crossed: Vec::new(),
+            next_threshold: 0,
         }
     }
 
     /// Updates the values of resources based on the queued transactions. If a transaction cannot be completed (if the value goes below its min or max), a snapshot of the resources before the transaction occurred is restored, and the transaction is marked as incomplete, and we continue to process the remaining transactions.
+    ///
+    /// Thresholds are checked after every successful transaction, so a pool that crosses a threshold and crosses back within the same update still produces an event.
     pub fn update(&mut self) {
         self.completed.clear();
+        self.crossed.clear();
 
         for exchange in self.transactions.iter() {
             let (item_type, change) = exchange;
@@ -78,9 +100,10 @@ where
             }
 
             let (val, min, max) = self.items.get_mut(&item_type).unwrap();
+            let prev_val = *val;
             match change {
                 Transaction::Change(amt) => {
-                    *val += *amt;
+                    *val = val.add(*amt);
                 }
                 Transaction::Set(amt) => {
                     *val = *amt;
@@ -92,11 +115,43 @@ where
                     *min = *new_min;
                 }
             }
+            let val = *val;
             self.completed.push(Ok(*item_type));
+
+            for threshold in self.thresholds.iter() {
+                if threshold.pool == *item_type && threshold.is_crossed(prev_val, val) {
+                    self.crossed.push(threshold.id);
+                }
+            }
         }
         self.transactions.clear();
     }
 
+    /// Watches a pool for the given value, emitting an event every time the pool crosses or reaches it (see [ThresholdKind]). Returns the threshold's ID, which stays the same when other thresholds are removed.
+    pub fn add_threshold(&mut self, pool: ID, value: Value, kind: ThresholdKind) -> ThresholdID {
+        let id = ThresholdID(self.next_threshold);
+        self.next_threshold += 1;
+        self.thresholds.push(Threshold {
+            id,
+            pool,
+            value,
+            kind,
+        });
+        id
+    }
+
+    /// Stops watching a threshold, including any crossing of it from the last update. Returns false if there's no threshold with that ID.
+    pub fn remove_threshold(&mut self, id: ThresholdID) -> bool {
+        let len = self.thresholds.len();
+        self.thresholds.retain(|threshold| threshold.id != id);
+        self.crossed.retain(|crossed| *crossed != id);
+        self.thresholds.len() != len
+    }
+
+    pub fn get_threshold(&self, id: ThresholdID) -> Option<&Threshold<ID, Value>> {
+        self.thresholds.iter().find(|threshold| threshold.id == id)
+    }
+
     /// Checks if the transaction is possible or not
     fn is_possible(
         &self,
@@ -106,9 +161,10 @@ where
         if let Some((value, min, max)) = self.items.get(item_type) {
             match transaction {
                 Transaction::Change(amt) => {
-                    if *value + *amt > *max {
+                    let new_value = value.add(*amt);
+                    if !new_value.at_most(max) {
                         Err((*item_type, ResourceError::TooBig))
-                    } else if *value + *amt < *min {
+                    } else if !min.at_most(&new_value) {
                         Err((*item_type, ResourceError::TooSmall))
                     } else {
                         Ok(())
@@ -127,20 +183,85 @@ where
     }
 }
 
+/// Pools are saved as a list of `(id, (value, min, max))`, so IDs don't have to be valid map keys (JSON only allows strings).
+mod pools {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+    use std::collections::BTreeMap;
+
+    type Pools<ID, Value> = BTreeMap<ID, (Value, Value, Value)>;
+
+    pub fn serialize<S, ID, Value>(
+        items: &Pools<ID, Value>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        ID: Serialize,
+        Value: Serialize,
+    {
+        serializer.collect_seq(items.iter())
+    }
+
+    pub fn deserialize<'de, D, ID, Value>(deserializer: D) -> Result<Pools<ID, Value>, D::Error>
+    where
+        D: Deserializer<'de>,
+        ID: Deserialize<'de> + Ord,
+        Value: Deserialize<'de>,
+    {
+        let items = Vec::<(ID, (Value, Value, Value))>::deserialize(deserializer)?;
+        Ok(items.into_iter().collect())
+    }
+}
+
 /// A transaction holding the amount the value should change by.
-#[derive(Clone, Copy)]
-pub enum Transaction<Value>
-where
-    Value: Add + AddAssign,
-{
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Transaction<Value: ResourceValue> {
     Change(Value),
     Set(Value),
     SetMax(Value),
     SetMin(Value),
 }
 
+/// Identifies a [Threshold] in a resource logic. IDs aren't reused, so they stay valid when other thresholds are removed.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug, Serialize, Deserialize)]
+pub struct ThresholdID(usize);
+
+/// A value that a pool is being watched for.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Threshold<ID, Value> {
+    pub id: ThresholdID,
+    pub pool: ID,
+    pub value: Value,
+    pub kind: ThresholdKind,
+}
+
+impl<ID, Value: ResourceValue> Threshold<ID, Value> {
+    /// If the pool's value changing from `prev` to `new` counts as crossing this threshold.
+    fn is_crossed(&self, prev: Value, new: Value) -> bool {
+        match self.kind {
+            ThresholdKind::CrossedUp => !self.value.at_most(&prev) && self.value.at_most(&new),
+            ThresholdKind::CrossedDown => !prev.at_most(&self.value) && new.at_most(&self.value),
+            ThresholdKind::Equals => {
+                prev.compare(&self.value) != Some(Ordering::Equal)
+                    && new.compare(&self.value) == Some(Ordering::Equal)
+            }
+        }
+    }
+}
+
+/// When a [Threshold] is triggered. Each kind triggers once per crossing: the value has to move back across the threshold before it can trigger again.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub enum ThresholdKind {
+    /// the value went from below the threshold to at or above it, ex. "when score reaches 10"
+    CrossedUp,
+    /// the value went from above the threshold to at or below it, ex. "when lives hit 0"
+    CrossedDown,
+    /// the value became exactly the threshold
+    Equals,
+}
+
 /// Errors possible when trying to complete a transaction.
-#[derive(Debug, PartialEq, Eq, Copy, Clone)]
+#[derive(Debug, PartialEq, Eq, Copy, Clone, Serialize, Deserialize)]
 pub enum ResourceError {
     PoolNotFound,
     TooBig,
@@ -149,21 +270,27 @@ pub enum ResourceError {
 
 pub type ResourceReaction<ID, Value> = (ID, Transaction<Value>);
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct ResourceEvent<ID> {
     pub pool: ID,
     pub event_type: ResourceEventType,
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Copy)]
+#[derive(Eq, PartialEq, Debug, Clone, Copy, Serialize, Deserialize)]
 pub enum ResourceEventType {
     PoolUpdated,
     TransactionUnsuccessful(ResourceError),
+    /// the threshold with the given ID was crossed upwards. See [ThresholdKind::CrossedUp].
+    ThresholdCrossedUp(ThresholdID),
+    /// the threshold with the given ID was crossed downwards. See [ThresholdKind::CrossedDown].
+    ThresholdCrossedDown(ThresholdID),
+    /// the pool's value became the threshold with the given ID. See [ThresholdKind::Equals].
+    ThresholdEquals(ThresholdID),
 }
 
 impl EventType for ResourceEventType {}
 
-impl<ID: Ord, Value: Add + AddAssign> Reaction for ResourceReaction<ID, Value> {}
+impl<ID: Ord, Value: ResourceValue> Reaction for ResourceReaction<ID, Value> {}
 
 impl<ID: Ord> Event for ResourceEvent<ID> {
     type EventType = ResourceEventType;
@@ -180,7 +307,7 @@ type QueryIdent<ID, Value> = (
 impl<ID, Value> OutputTable<QueryIdent<ID, Value>> for QueuedResources<ID, Value>
 where
     ID: Copy + Ord + Debug,
-    Value: Add<Output = Value> + AddAssign + Ord + Copy,
+    Value: ResourceValue,
 {
     fn get_table(&self) -> Vec<QueryIdent<ID, Value>> {
         self.items
@@ -193,10 +320,11 @@ where
 impl<ID, Value> OutputTable<ResourceEvent<ID>> for QueuedResources<ID, Value>
 where
     ID: Copy + Ord + Debug,
-    Value: Add<Output = Value> + AddAssign + Ord + Copy,
+    Value: ResourceValue,
 {
     fn get_table(&self) -> Vec<ResourceEvent<ID>> {
-        self.completed
+        let mut events = self
+            .completed
             .iter()
             .map(|completed| match completed {
                 Ok(id) => ResourceEvent {
@@ -208,6 +336,167 @@ where
                     event_type: ResourceEventType::TransactionUnsuccessful(*err),
                 },
             })
-            .collect()
+            .collect::<Vec<_>>();
+
+        for id in self.crossed.iter() {
+            // crossed is public, so it could name a threshold that's been removed since
+            let threshold = match self.get_threshold(*id) {
+                Some(threshold) => threshold,
+                None => continue,
+            };
+            let event_type = match threshold.kind {
+                ThresholdKind::CrossedUp => ResourceEventType::ThresholdCrossedUp(*id),
+                ThresholdKind::CrossedDown => ResourceEventType::ThresholdCrossedDown(*id),
+                ThresholdKind::Equals => ResourceEventType::ThresholdEquals(*id),
+            };
+            events.push(ResourceEvent {
+                pool: threshold.pool,
+                event_type,
+            });
+        }
+        events
+    }
+}
+
+/// A value that can be held in a resource pool.
+///
+/// Values only need to be able to start from nothing, be added together, and be compared against a pool's min and max. Comparisons are partial so that vector-like values (ex. red/green/blue mana, or money in several currencies) can be compared component by component: `[1, 5, 2]` is less than `[2, 5, 3]`, but `[1, 5, 2]` and `[2, 4, 3]` can't be ordered, and a pool holding them would reject the transaction.
+pub trait ResourceValue: Copy {
+    /// the empty value, ex. `0` or `[0, 0, 0]`
+    fn zero() -> Self;
+    /// combines two values, ex. `self + other`
+    fn add(self, other: Self) -> Self;
+    /// how `self` compares to `other`, or `None` if they can't be ordered
+    fn compare(&self, other: &Self) -> Option<Ordering>;
+
+    /// the value that cancels `self` out, ex. `-self`, or `None` if it can't be negated (ex. a nonzero unsigned number)
+    fn negate(self) -> Option<Self> {
+        None
+    }
+
+    /// if `self` is less than or equal to `other` in every component
+    fn at_most(&self, other: &Self) -> bool {
+        matches!(
+            self.compare(other),
+            Some(Ordering::Less) | Some(Ordering::Equal)
+        )
+    }
+}
+
+macro_rules! int_resource_value {
+    ($($int:ty),*) => {
+        $(
+            impl ResourceValue for $int {
+                fn zero() -> Self {
+                    0
+                }
+                fn add(self, other: Self) -> Self {
+                    self + other
+                }
+                fn compare(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+                fn negate(self) -> Option<Self> {
+                    self.checked_neg()
+                }
+            }
+        )*
+    };
+}
+
+int_resource_value!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// A float with a total ordering, so it can be used as a [ResourceValue] (ex. an `f32` energy bar) or anywhere else an `Ord` is needed. NaNs are ordered according to [f32::total_cmp], i.e. after positive infinity (or before negative infinity if the sign bit is set).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrdFloat<F>(pub F);
+
+macro_rules! float_resource_value {
+    ($($float:ty),*) => {
+        $(
+            impl PartialEq for OrdFloat<$float> {
+                fn eq(&self, other: &Self) -> bool {
+                    self.cmp(other) == Ordering::Equal
+                }
+            }
+            impl Eq for OrdFloat<$float> {}
+            impl PartialOrd for OrdFloat<$float> {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+            impl Ord for OrdFloat<$float> {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    self.0.total_cmp(&other.0)
+                }
+            }
+
+            impl ResourceValue for OrdFloat<$float> {
+                fn zero() -> Self {
+                    OrdFloat(0.0)
+                }
+                fn add(self, other: Self) -> Self {
+                    OrdFloat(self.0 + other.0)
+                }
+                fn compare(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+                fn negate(self) -> Option<Self> {
+                    Some(OrdFloat(-self.0))
+                }
+            }
+        )*
+    };
+}
+
+float_resource_value!(f32, f64);
+
+/// shows the float on its own, ex. `1.5` rather than `OrdFloat(1.5)`
+impl<F: Display> Display for OrdFloat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<F: FromStr> FromStr for OrdFloat<F> {
+    type Err = F::Err;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        text.parse().map(OrdFloat)
+    }
+}
+
+/// Vector-like values, compared component by component.
+impl<T: ResourceValue, const N: usize> ResourceValue for [T; N] {
+    fn zero() -> Self {
+        [T::zero(); N]
+    }
+
+    fn add(self, other: Self) -> Self {
+        let mut sum = self;
+        for (val, other) in sum.iter_mut().zip(other.iter()) {
+            *val = val.add(*other);
+        }
+        sum
+    }
+
+    fn compare(&self, other: &Self) -> Option<Ordering> {
+        let mut ordering = Ordering::Equal;
+        for (val, other) in self.iter().zip(other.iter()) {
+            match (ordering, val.compare(other)?) {
+                (_, Ordering::Equal) => {}
+                (Ordering::Equal, cmp) => ordering = cmp,
+                (prev, cmp) if prev != cmp => return None,
+                _ => {}
+            }
+        }
+        Some(ordering)
+    }
+
+    fn negate(self) -> Option<Self> {
+        let mut negated = self;
+        for val in negated.iter_mut() {
+            *val = val.negate()?;
+        }
+        Some(negated)
     }
 }
@@ -0,0 +1,263 @@
+//! # Rollback
+//!
+//! Rewinding a game a few frames and playing them again, ex. when a networked player's input arrives late or when debugging what would have happened with different input. Every logic can take a [Snapshot] of its data and restore it later, and a game that can also run a single frame from a recorded input can be driven by a [Rollback], which keeps the last few frames' snapshots and inputs in a ring buffer.
+//!
+//! Resimulating only gives the same results if a frame depends on nothing but the game's snapshot and its input. Input has to come from the recorded frames (see [KeyboardControl::record][crate::control::KeyboardControl::record]) rather than the keyboard, and anything else the game reads, like random numbers or data captured by rule closures, has to be part of the snapshot.
+
+use std::collections::VecDeque;
+use std::fmt::{self, Debug};
+use std::hash::Hash;
+
+use crate::bindings::Bindings;
+use crate::collision::AabbCollision;
+use crate::control::{Action, InputWrapper, KeyboardControl, Values};
+use crate::entity_state::FlatEntityState;
+use crate::linking::GraphedLinking;
+use crate::physics::PointPhysics;
+use crate::resources::{QueuedResources, ResourceValue};
+
+/// Something whose data can be copied out and put back later.
+pub trait Snapshot {
+    type Data;
+
+    fn snapshot(&self) -> Self::Data;
+
+    fn restore(&mut self, data: &Self::Data);
+}
+
+/// logics that are entirely data, so a snapshot is a clone. Restoring reuses their allocations.
+macro_rules! snapshot_by_clone {
+    ($([$($generics:tt)*] $logic:ty),* $(,)?) => {
+        $(
+            impl<$($generics)*> Snapshot for $logic {
+                type Data = Self;
+
+                fn snapshot(&self) -> Self {
+                    self.clone()
+                }
+
+                fn restore(&mut self, data: &Self) {
+                    self.clone_from(data);
+                }
+            }
+        )*
+    };
+}
+
+snapshot_by_clone!(
+    [] PointPhysics,
+    [ID: Copy + Eq] AabbCollision<ID>,
+    [ID: Copy + Ord + Debug, Value: ResourceValue] QueuedResources<ID, Value>,
+    [NodeID: Copy + Eq + Hash] GraphedLinking<NodeID>,
+    [ID: Copy + Eq + Hash] FlatEntityState<ID>,
+    [] Bindings,
+);
+
+/// The input wrapper isn't part of the snapshot: it only holds onto the input library's state between frames.
+impl<ID, Wrapper> Snapshot for KeyboardControl<ID, Wrapper>
+where
+    ID: Copy + Eq + Ord,
+    Wrapper: InputWrapper,
+{
+    type Data = (Vec<Vec<Action<ID, Wrapper::KeyCode>>>, Vec<Vec<Values>>);
+
+    fn snapshot(&self) -> Self::Data {
+        (self.mapping.clone(), self.values.clone())
+    }
+
+    fn restore(&mut self, (mapping, values): &Self::Data) {
+        self.mapping.clone_from(mapping);
+        self.values.clone_from(values);
+    }
+}
+
+/// A game that can be rolled back: a [Snapshot] of everything that changes between frames, plus running a frame from a recorded input.
+pub trait Resimulate: Snapshot {
+    /// everything the game reads from outside itself in a frame, ex. an [InputFrame][crate::control::InputFrame]
+    type Input: Clone;
+
+    /// runs one frame with the given input
+    fn step(&mut self, input: &Self::Input);
+}
+
+/// The last `capacity` frames' data, oldest first. Pushing a frame when the buffer is full drops the oldest one.
+pub struct SnapshotBuffer<Data> {
+    frames: VecDeque<(u64, Data)>,
+    capacity: usize,
+}
+
+impl<Data> SnapshotBuffer<Data> {
+    /// Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        assert!(
+            capacity > 0,
+            "snapshot buffer needs room for at least one frame"
+        );
+        Self {
+            frames: VecDeque::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Adds data for a frame. If the buffer already has data for this frame or later ones, it's replaced, since those frames are being played again.
+    pub fn push(&mut self, frame: u64, data: Data) {
+        self.truncate(frame);
+        if self.frames.len() == self.capacity {
+            self.frames.pop_front();
+        }
+        self.frames.push_back((frame, data));
+    }
+
+    pub fn get(&self, frame: u64) -> Option<&Data> {
+        let idx = self.position(frame)?;
+        Some(&self.frames[idx].1)
+    }
+
+    pub fn get_mut(&mut self, frame: u64) -> Option<&mut Data> {
+        let idx = self.position(frame)?;
+        Some(&mut self.frames[idx].1)
+    }
+
+    /// the oldest frame in the buffer
+    pub fn oldest(&self) -> Option<u64> {
+        self.frames.front().map(|(frame, _)| *frame)
+    }
+
+    /// the newest frame in the buffer
+    pub fn latest(&self) -> Option<u64> {
+        self.frames.back().map(|(frame, _)| *frame)
+    }
+
+    /// removes `frame` and every frame after it
+    pub fn truncate(&mut self, frame: u64) {
+        while let Some(latest) = self.latest() {
+            if latest < frame {
+                break;
+            }
+            self.frames.pop_back();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn position(&self, frame: u64) -> Option<usize> {
+        // frames are consecutive, so the index is the distance from the oldest one
+        let idx = frame.checked_sub(self.oldest()?)? as usize;
+        (idx < self.frames.len()).then_some(idx)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum RollbackError {
+    /// the frame is older than the buffer or hasn't been run yet
+    FrameNotBuffered(u64),
+}
+
+impl fmt::Display for RollbackError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RollbackError::FrameNotBuffered(frame) => {
+                write!(f, "frame {} isn't in the rollback buffer", frame)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RollbackError {}
+
+/// Runs a game frame by frame, keeping what it needs to go back and run recent frames again.
+pub struct Rollback<G: Resimulate> {
+    /// the next frame to run
+    frame: u64,
+    /// the game at the start of each frame, and the input it was run with
+    history: SnapshotBuffer<(G::Data, G::Input)>,
+}
+
+impl<G: Resimulate> Rollback<G> {
+    /// Keeps the last `capacity` frames. Panics if `capacity` is 0.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            frame: 0,
+            history: SnapshotBuffer::new(capacity),
+        }
+    }
+
+    /// the next frame to run, i.e. how many frames have been run
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// the oldest frame that can be rolled back to
+    pub fn oldest(&self) -> Option<u64> {
+        self.history.oldest()
+    }
+
+    /// the input a frame was run with
+    pub fn input(&self, frame: u64) -> Option<&G::Input> {
+        self.history.get(frame).map(|(_, input)| input)
+    }
+
+    /// runs the next frame
+    pub fn advance(&mut self, game: &mut G, input: G::Input) {
+        self.history
+            .push(self.frame, (game.snapshot(), input.clone()));
+        game.step(&input);
+        self.frame += 1;
+    }
+
+    /// Changes the input a past frame was run with. Call [Rollback::resimulate] afterwards to see what happens differently.
+    pub fn set_input(&mut self, frame: u64, input: G::Input) -> Result<(), RollbackError> {
+        let (_, old) = self
+            .history
+            .get_mut(frame)
+            .ok_or(RollbackError::FrameNotBuffered(frame))?;
+        *old = input;
+        Ok(())
+    }
+
+    /// Restores the game to the start of `frame` and runs it back up to the present with the recorded inputs.
+    pub fn resimulate(&mut self, game: &mut G, frame: u64) -> Result<(), RollbackError> {
+        self.restore(game, frame)?;
+        for current in frame..self.frame {
+            if current > frame {
+                // later frames start from a different state if an input changed
+                let snapshot = game.snapshot();
+                self.history.get_mut(current).unwrap().0 = snapshot;
+            }
+            let input = self.history.get(current).unwrap().1.clone();
+            game.step(&input);
+        }
+        Ok(())
+    }
+
+    /// Restores the game to the start of `frame` and forgets every frame from then on, to run them again with new input.
+    pub fn rewind(&mut self, game: &mut G, frame: u64) -> Result<(), RollbackError> {
+        self.restore(game, frame)?;
+        self.history.truncate(frame);
+        self.frame = frame;
+        Ok(())
+    }
+
+    fn restore(&self, game: &mut G, frame: u64) -> Result<(), RollbackError> {
+        let (snapshot, _) = self
+            .history
+            .get(frame)
+            .ok_or(RollbackError::FrameNotBuffered(frame))?;
+        game.restore(snapshot);
+        Ok(())
+    }
+}
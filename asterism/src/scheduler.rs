@@ -0,0 +1,234 @@
+//! # Scheduler
+//!
+//! Every engine runs its logics in the same kind of loop: update a logic, write its outputs into the condition tables, copy data over to the next logic, and repeat for the next one. The scheduler is that loop. Each step is registered as a stage with the data it reads and writes, and one call to [Scheduler::run] runs every stage in order, so the game loop only has to draw.
+//!
+//! Stages are either logic stages, which update a logic and react to what it did, or synthesis stages, which copy data from one logic to another (ex. physics positions into collision). Both are closures over the game, `G`. Stages run in the order they're added unless they're moved around with [Scheduler::set_order], [Scheduler::move_before], or [Scheduler::move_after].
+
+use std::fmt;
+
+/// a step of the frame
+pub type StageFn<G> = Box<dyn FnMut(&mut G)>;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum StageKind {
+    /// updates a logic and processes its outputs
+    Logic,
+    /// moves data between logics
+    Synthesis,
+}
+
+pub struct Stage<G> {
+    pub name: &'static str,
+    pub kind: StageKind,
+    /// the data this stage reads, ex. the name of the logic whose outputs it uses
+    pub inputs: Vec<&'static str>,
+    /// the data this stage writes
+    pub outputs: Vec<&'static str>,
+    /// disabled stages are skipped
+    pub enabled: bool,
+    run: StageFn<G>,
+}
+
+impl<G> Stage<G> {
+    /// adds to the data this stage reads
+    pub fn reads(&mut self, inputs: &[&'static str]) -> &mut Self {
+        self.inputs.extend_from_slice(inputs);
+        self
+    }
+
+    /// adds to the data this stage writes
+    pub fn writes(&mut self, outputs: &[&'static str]) -> &mut Self {
+        self.outputs.extend_from_slice(outputs);
+        self
+    }
+}
+
+impl<G> fmt::Debug for Stage<G> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stage")
+            .field("name", &self.name)
+            .field("kind", &self.kind)
+            .field("inputs", &self.inputs)
+            .field("outputs", &self.outputs)
+            .field("enabled", &self.enabled)
+            .finish()
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ScheduleError {
+    StageNotFound(&'static str),
+    /// `stage` reads `input`, but it's only written by stages that run after it, so it would see last frame's data
+    StaleInput {
+        stage: &'static str,
+        input: &'static str,
+    },
+}
+
+impl fmt::Display for ScheduleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScheduleError::StageNotFound(name) => write!(f, "stage {:?} not found", name),
+            ScheduleError::StaleInput { stage, input } => write!(
+                f,
+                "stage {:?} reads {:?} before any stage writes it",
+                stage, input
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ScheduleError {}
+
+pub struct Scheduler<G> {
+    pub stages: Vec<Stage<G>>,
+}
+
+impl<G> Scheduler<G> {
+    pub fn new() -> Self {
+        Self { stages: Vec::new() }
+    }
+
+    /// Adds a stage that updates a logic to the end of the frame. Returns the stage, to declare what it reads and writes.
+    ///
+    /// Panics if there's already a stage with this name.
+    pub fn add_logic(
+        &mut self,
+        name: &'static str,
+        run: impl FnMut(&mut G) + 'static,
+    ) -> &mut Stage<G> {
+        self.add_stage(name, StageKind::Logic, Box::new(run))
+    }
+
+    /// Adds a stage that moves data from `from` to `to` to the end of the frame.
+    ///
+    /// Panics if there's already a stage with this name.
+    pub fn add_synthesis(
+        &mut self,
+        name: &'static str,
+        from: &[&'static str],
+        to: &[&'static str],
+        run: impl FnMut(&mut G) + 'static,
+    ) -> &mut Stage<G> {
+        self.add_stage(name, StageKind::Synthesis, Box::new(run))
+            .reads(from)
+            .writes(to)
+    }
+
+    fn add_stage(&mut self, name: &'static str, kind: StageKind, run: StageFn<G>) -> &mut Stage<G> {
+        assert!(
+            self.position(name).is_err(),
+            "stage {:?} already exists",
+            name
+        );
+        self.stages.push(Stage {
+            name,
+            kind,
+            inputs: Vec::new(),
+            outputs: Vec::new(),
+            enabled: true,
+            run,
+        });
+        self.stages.last_mut().unwrap()
+    }
+
+    pub fn remove_stage(&mut self, name: &'static str) -> Result<(), ScheduleError> {
+        let idx = self.position(name)?;
+        self.stages.remove(idx);
+        Ok(())
+    }
+
+    pub fn get_stage(&mut self, name: &'static str) -> Result<&mut Stage<G>, ScheduleError> {
+        let idx = self.position(name)?;
+        Ok(&mut self.stages[idx])
+    }
+
+    pub fn set_enabled(&mut self, name: &'static str, enabled: bool) -> Result<(), ScheduleError> {
+        self.get_stage(name)?.enabled = enabled;
+        Ok(())
+    }
+
+    /// the names of the stages, in the order they run
+    pub fn order(&self) -> Vec<&'static str> {
+        self.stages.iter().map(|stage| stage.name).collect()
+    }
+
+    /// Runs the named stages in the given order, followed by any stages that weren't named in the order they were already in.
+    pub fn set_order(&mut self, order: &[&'static str]) -> Result<(), ScheduleError> {
+        for name in order.iter() {
+            self.position(name)?;
+        }
+        let mut stages = std::mem::take(&mut self.stages);
+        for name in order.iter() {
+            if let Some(idx) = stages.iter().position(|stage| stage.name == *name) {
+                self.stages.push(stages.remove(idx));
+            }
+        }
+        self.stages.append(&mut stages);
+        Ok(())
+    }
+
+    /// moves the stage `name` to right before `other`
+    pub fn move_before(
+        &mut self,
+        name: &'static str,
+        other: &'static str,
+    ) -> Result<(), ScheduleError> {
+        self.position(other)?;
+        let stage = self.stages.remove(self.position(name)?);
+        let idx = self.position(other)?;
+        self.stages.insert(idx, stage);
+        Ok(())
+    }
+
+    /// moves the stage `name` to right after `other`
+    pub fn move_after(
+        &mut self,
+        name: &'static str,
+        other: &'static str,
+    ) -> Result<(), ScheduleError> {
+        self.position(other)?;
+        let stage = self.stages.remove(self.position(name)?);
+        let idx = self.position(other)?;
+        self.stages.insert(idx + 1, stage);
+        Ok(())
+    }
+
+    /// Checks that every enabled stage's inputs are written earlier in the frame, if they're written by a stage at all. Data no stage writes, like keyboard input, is assumed to come from outside the scheduler.
+    pub fn check(&self) -> Result<(), ScheduleError> {
+        let stages: Vec<&Stage<G>> = self.stages.iter().filter(|stage| stage.enabled).collect();
+        for (i, stage) in stages.iter().enumerate() {
+            for input in stage.inputs.iter() {
+                let written =
+                    |stages: &[&Stage<G>]| stages.iter().any(|stage| stage.outputs.contains(input));
+                if !written(&stages[..i]) && written(&stages[i + 1..]) {
+                    return Err(ScheduleError::StaleInput {
+                        stage: stage.name,
+                        input,
+                    });
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// runs every enabled stage once, in order
+    pub fn run(&mut self, game: &mut G) {
+        for stage in self.stages.iter_mut().filter(|stage| stage.enabled) {
+            (stage.run)(game);
+        }
+    }
+
+    fn position(&self, name: &'static str) -> Result<usize, ScheduleError> {
+        self.stages
+            .iter()
+            .position(|stage| stage.name == name)
+            .ok_or(ScheduleError::StageNotFound(name))
+    }
+}
+
+impl<G> Default for Scheduler<G> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
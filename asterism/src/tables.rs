@@ -4,9 +4,11 @@
 //!
 //! The outputs in asterism are the identities + their associated data and the events. Ex. for a collision logic the identities could be the positions/sizes/metadata of each collision body, while the events would be contacts.
 //!
-//! A condition table composes those individual queries together.
-use std::collections::HashMap;
+//! A condition table composes those individual queries together. Rows composed from other rows can be updated one at a time in whatever order the game wants, or given a [rule][ConditionTables::add_rule] so the whole table can be brought up to date with one call to [evaluate][ConditionTables::evaluate].
+use std::collections::{HashMap, HashSet};
+use std::fmt::Debug;
 use std::hash::Hash;
+use std::marker::PhantomData;
 
 /// Builds output tables based on output of logics
 pub trait OutputTable<ProcessOutput> {
@@ -15,25 +17,504 @@ pub trait OutputTable<ProcessOutput> {
 
 /// holds logics' output tables and outputs of [processing][Compose]. Each compose processes one or two previous queries' output, then outputs them into another table. Ex: where `composes.get(&query3) == Compose::Zip(query1, query2)`, `query_output.get(&query3)` would be `query1` and `query2`'s output zipped together and copied to a new table. You could then further filter on the output of query3: `Compose::Filter(query3)`.
 ///
-/// performance: does a lot of copying/reallocating every function call. works for now but might want to revisit in the future
+/// performance: does a lot of copying/reallocating every function call. [evaluate][Self::evaluate] at least skips rows whose inputs haven't changed since they were last computed.
 pub struct ConditionTables<QueryID: Hash + Eq + Copy> {
     query_output: AnyHashMap<QueryID>,
     composes: HashMap<QueryID, Option<Compose<QueryID>>>,
+    /// number of outputs in each row, so [Compose::Count] doesn't need to know the row's type
+    lengths: HashMap<QueryID, usize>,
+    /// the type of each row's outputs
+    row_types: HashMap<QueryID, &'static str>,
+    /// rows in the order they were added
+    ids: Vec<QueryID>,
+    /// rows in an order where each row comes after the rows it's composed from
+    order: Vec<QueryID>,
+    rules: HashMap<QueryID, Rule<QueryID>>,
+    /// when each row last changed, from `clock`
+    versions: HashMap<QueryID, u64>,
+    /// versions of each rule's inputs when it last ran
+    rule_inputs: HashMap<QueryID, Vec<u64>>,
+    clock: u64,
 }
 
-impl<QueryID: Hash + Eq + Copy + std::fmt::Debug> ConditionTables<QueryID> {
+type Rule<QueryID> = Box<dyn Fn(&mut ConditionTables<QueryID>)>;
+
+impl<QueryID: Hash + Eq + Copy + Debug> ConditionTables<QueryID> {
     pub fn new() -> Self {
         Self {
             query_output: AnyHashMap::new(),
             composes: HashMap::new(),
+            lengths: HashMap::new(),
+            row_types: HashMap::new(),
+            ids: Vec::new(),
+            order: Vec::new(),
+            rules: HashMap::new(),
+            versions: HashMap::new(),
+            rule_inputs: HashMap::new(),
+            clock: 0,
         }
     }
 
-    /// adds a query to the table, associating a new row of outputs with the given ID.
-    pub fn add_query<T: 'static>(&mut self, id: QueryID, compose: Option<Compose<QueryID>>) {
+    /// adds a query to the table, associating a new row of outputs with the given ID. Adding a query with an ID that's already in the table replaces it (and its rule), so any handles to the old row shouldn't be used anymore.
+    ///
+    /// Returns [TableError::Cycle] without changing the table if the compose would make a row depend on itself. The typed `add_` functions below panic instead, also without changing the table.
+    ///
+    /// Prefer [add_single][Self::add_single], [add_filter][Self::add_filter], [add_zip][Self::add_zip], etc., which check that the rows being composed hold the right types.
+    pub fn add_query<T: Debug + 'static>(
+        &mut self,
+        id: QueryID,
+        compose: Option<Compose<QueryID>>,
+    ) -> Result<QueryHandle<QueryID, T>, TableError<QueryID>> {
+        let order = self.order_with(Some((id, compose.as_ref())))?;
         let output: Vec<T> = Vec::new();
-        self.composes.insert(id, compose);
+        if self.composes.insert(id, compose).is_none() {
+            self.ids.push(id);
+        }
         self.query_output.insert(id, output);
+        self.row_types.insert(id, type_name::<T>());
+        self.lengths.insert(id, 0);
+        self.rules.remove(&id);
+        self.rule_inputs.remove(&id);
+        self.bump(id);
+        self.order = order;
+        Ok(QueryHandle::new(id))
+    }
+
+    /// [add_query][Self::add_query] for the typed `add_` functions, which panic on cycles instead
+    fn add_typed_query<T: Debug + 'static>(
+        &mut self,
+        id: QueryID,
+        compose: Option<Compose<QueryID>>,
+    ) -> QueryHandle<QueryID, T> {
+        self.add_query(id, compose)
+            .unwrap_or_else(|err| panic!("couldn't add query {:?}: {}", id, err))
+    }
+
+    /// Sets how to recompute a composed row, so [evaluate][Self::evaluate] can keep it up to date. The rule gets the table and the row's handle, and should update the row, ex. `tables.add_rule(pressed, |tables, pressed| { tables.filter(pressed, |ctrl: &CtrlEvent| ctrl.event_type == ControlEventType::KeyPressed); })`.
+    ///
+    /// A row counts as changed whenever it's written, even if its outputs come out the same, so rows composed from a row whose rule ran are always recomputed too.
+    pub fn add_rule<T: 'static, Src: 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, T, Src>,
+        rule: impl Fn(&mut Self, QueryHandle<QueryID, T, Src>) + 'static,
+    ) where
+        QueryID: 'static,
+    {
+        let id = handle.id;
+        self.rules
+            .insert(id, Box::new(move |tables: &mut Self| rule(tables, handle)));
+        self.rule_inputs.remove(&id);
+    }
+
+    /// Runs the rule of every row that has one, in dependency order, so each row is computed after the rows it's composed from. Rules whose input rows haven't changed since they last ran are skipped.
+    pub fn evaluate(&mut self) {
+        let rules = std::mem::take(&mut self.rules);
+        for id in self.order.clone() {
+            let rule = match rules.get(&id) {
+                Some(rule) => rule,
+                None => continue,
+            };
+            let inputs: Vec<u64> = self
+                .sources(id)
+                .iter()
+                .map(|source| self.versions.get(source).copied().unwrap_or(0))
+                .collect();
+            if self.rule_inputs.get(&id) == Some(&inputs) {
+                continue;
+            }
+            rule(self);
+            self.rule_inputs.insert(id, inputs);
+        }
+        self.rules = rules;
+    }
+
+    /// Every row, ordered so that rows come after the rows they're composed from. Rows that don't depend on each other stay in the order they were added.
+    ///
+    /// Returns [TableError::Cycle] if a row depends on itself.
+    pub fn dependency_order(&self) -> Result<Vec<QueryID>, TableError<QueryID>> {
+        self.order_with(None)
+    }
+
+    /// the dependency order if `added` (a row's ID and compose) were added to the table
+    fn order_with(
+        &self,
+        added: Option<(QueryID, Option<&Compose<QueryID>>)>,
+    ) -> Result<Vec<QueryID>, TableError<QueryID>> {
+        let mut visiting = HashSet::new();
+        let mut done = HashSet::new();
+        let mut order = Vec::new();
+        let new_id = added
+            .map(|(id, _)| id)
+            .filter(|id| !self.composes.contains_key(id));
+        for id in self.ids.iter().chain(new_id.iter()) {
+            self.visit(*id, added, &mut visiting, &mut done, &mut order)?;
+        }
+        Ok(order)
+    }
+
+    fn visit(
+        &self,
+        id: QueryID,
+        added: Option<(QueryID, Option<&Compose<QueryID>>)>,
+        visiting: &mut HashSet<QueryID>,
+        done: &mut HashSet<QueryID>,
+        order: &mut Vec<QueryID>,
+    ) -> Result<(), TableError<QueryID>> {
+        if done.contains(&id) {
+            return Ok(());
+        }
+        if !visiting.insert(id) {
+            return Err(TableError::Cycle(id));
+        }
+        let sources = match added {
+            Some((added_id, compose)) if added_id == id => {
+                compose.map(Compose::sources).unwrap_or_default()
+            }
+            _ => self.sources(id),
+        };
+        for source in sources {
+            // rows can be composed from rows that haven't been added yet
+            let exists = self.composes.contains_key(&source)
+                || added.map(|(added_id, _)| added_id) == Some(source);
+            if exists {
+                self.visit(source, added, visiting, done, order)?;
+            }
+        }
+        visiting.remove(&id);
+        done.insert(id);
+        order.push(id);
+        Ok(())
+    }
+
+    /// the rows a row is composed from
+    fn sources(&self, id: QueryID) -> Vec<QueryID> {
+        match self.composes.get(&id) {
+            Some(Some(compose)) => compose.sources(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn bump(&mut self, id: QueryID) {
+        self.clock += 1;
+        self.versions.insert(id, self.clock);
+    }
+
+    /// adds a row that's written to directly, usually with a logic's output.
+    pub fn add_single<T: Debug + 'static>(&mut self, id: QueryID) -> QueryHandle<QueryID, T> {
+        self.add_typed_query(id, None)
+    }
+
+    /// adds a row that holds the outputs of another row that pass a predicate.
+    pub fn add_filter<T: Debug + 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, T> {
+        self.add_typed_query(id, Some(Compose::Filter(source.id)))
+    }
+
+    /// adds a row that holds the outputs of two other rows zipped together.
+    pub fn add_zip<A: Debug + 'static, B: Debug + 'static, SA, SB>(
+        &mut self,
+        id: QueryID,
+        first: QueryHandle<QueryID, A, SA>,
+        second: QueryHandle<QueryID, B, SB>,
+    ) -> QueryHandle<QueryID, (A, B)> {
+        self.add_typed_query(id, Some(Compose::Zip(first.id, second.id)))
+    }
+
+    /// adds a row that holds the outputs of another row, transformed. The handle remembers the source row's type, so [map][Self::map] can only be given a function from it.
+    pub fn add_map<A: 'static, B: Debug + 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, A, S>,
+    ) -> QueryHandle<QueryID, B, A> {
+        self.add_typed_query(id, Some(Compose::Map(source.id)))
+            .with_source()
+    }
+
+    /// adds a row that pairs up the outputs of two rows by key.
+    pub fn add_join<A: Debug + 'static, B: Debug + 'static, SA, SB>(
+        &mut self,
+        id: QueryID,
+        first: QueryHandle<QueryID, A, SA>,
+        second: QueryHandle<QueryID, B, SB>,
+    ) -> QueryHandle<QueryID, (A, B)> {
+        self.add_typed_query(id, Some(Compose::Join(first.id, second.id)))
+    }
+
+    /// adds a row that pairs up the outputs of two rows by key, keeping outputs of the first row that have no match.
+    pub fn add_left_join<A: Debug + 'static, B: Debug + 'static, SA, SB>(
+        &mut self,
+        id: QueryID,
+        first: QueryHandle<QueryID, A, SA>,
+        second: QueryHandle<QueryID, B, SB>,
+    ) -> QueryHandle<QueryID, (A, Option<B>)> {
+        self.add_typed_query(id, Some(Compose::LeftJoin(first.id, second.id)))
+    }
+
+    /// adds a row that holds one aggregated value per key of another row. The handle remembers the source row's type, like [add_map][Self::add_map]'s.
+    pub fn add_group_by<T: 'static, K: Debug + 'static, V: Debug + 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, (K, V), T> {
+        self.add_typed_query(id, Some(Compose::GroupBy(source.id)))
+            .with_source()
+    }
+
+    /// adds a row that holds the number of outputs in another row.
+    pub fn add_count<T: 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, usize> {
+        self.add_typed_query(id, Some(Compose::Count(source.id)))
+    }
+
+    /// adds a row that holds the outputs of another row without duplicates.
+    pub fn add_distinct<T: Debug + 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, T> {
+        self.add_typed_query(id, Some(Compose::Distinct(source.id)))
+    }
+
+    /// adds a row that holds the outputs of another row, sorted.
+    pub fn add_sort<T: Debug + 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, T> {
+        self.add_typed_query(id, Some(Compose::Sort(source.id)))
+    }
+
+    /// adds a row that holds if any output of another row passes a predicate. The handle remembers the source row's type, like [add_map][Self::add_map]'s.
+    pub fn add_any<T: 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, bool, T> {
+        self.add_typed_query(id, Some(Compose::Any(source.id)))
+            .with_source()
+    }
+
+    /// adds a row that holds if every output of another row passes a predicate. The handle remembers the source row's type, like [add_map][Self::add_map]'s.
+    pub fn add_all<T: 'static, S>(
+        &mut self,
+        id: QueryID,
+        source: QueryHandle<QueryID, T, S>,
+    ) -> QueryHandle<QueryID, bool, T> {
+        self.add_typed_query(id, Some(Compose::All(source.id)))
+            .with_source()
+    }
+
+    /// gets a handle to a row that's already in the table, checking that it holds `T`s. Rows added with [add_map][Self::add_map], [add_group_by][Self::add_group_by], [add_any][Self::add_any], or [add_all][Self::add_all] can be read with it, but need a handle from [get_composed_handle][Self::get_composed_handle] to be updated.
+    pub fn get_handle<T: 'static>(
+        &self,
+        id: QueryID,
+    ) -> Result<QueryHandle<QueryID, T>, TableError<QueryID>> {
+        self.query_output.get::<Vec<T>>(&id)?;
+        Ok(QueryHandle::new(id))
+    }
+
+    /// gets a handle to a row composed from a row of `Src`s, checking that it holds `T`s and that its source row holds `Src`s.
+    pub fn get_composed_handle<T: 'static, Src: 'static>(
+        &self,
+        id: QueryID,
+    ) -> Result<QueryHandle<QueryID, T, Src>, TableError<QueryID>> {
+        self.query_output.get::<Vec<T>>(&id)?;
+        match self.compose(id, "get a source for")? {
+            Compose::Map(source)
+            | Compose::GroupBy(source)
+            | Compose::Any(source)
+            | Compose::All(source) => {
+                self.query_output.get::<Vec<Src>>(&source)?;
+                Ok(QueryHandle::new(id))
+            }
+            _ => Err(self.mismatched(id, "get a source for")),
+        }
+    }
+
+    /// Describes every row in the table, in the order they were added.
+    pub fn queries(&self) -> Vec<QueryInfo<QueryID>> {
+        self.ids
+            .iter()
+            .map(|id| QueryInfo {
+                id: *id,
+                compose: self.composes[id].clone(),
+                type_name: self.row_types[id],
+                len: self.lengths[id],
+                has_rule: self.rules.contains_key(id),
+            })
+            .collect()
+    }
+
+    /// Writes out every row and its current outputs, one row per line, for debugging rules that don't fire.
+    pub fn dump(&self) -> String {
+        let mut dump = String::new();
+        for info in self.queries() {
+            let compose = match &info.compose {
+                Some(compose) => format!("{:?}", compose),
+                None => "Single".to_string(),
+            };
+            dump.push_str(&format!(
+                "{:?} ({}, {}): {}\n",
+                info.id,
+                compose,
+                info.type_name,
+                self.query_output.debug(&info.id).unwrap_or_default()
+            ));
+        }
+        dump
+    }
+
+    /// the outputs currently in a row.
+    ///
+    /// Panics if the handle is from a different table.
+    pub fn get<T: 'static, Src>(&self, handle: QueryHandle<QueryID, T, Src>) -> &[T] {
+        expect_row(self.query_output.get::<Vec<T>>(&handle.id)).as_slice()
+    }
+
+    /// writes a row added with [add_single][Self::add_single].
+    ///
+    /// Panics if the handle is from a different table or its row is composed from other rows.
+    pub fn set<T: 'static>(&mut self, handle: QueryHandle<QueryID, T>, output: Vec<T>) -> &[T] {
+        expect_row(self.update_single(handle.id, output))
+    }
+
+    /// updates a row added with [add_filter][Self::add_filter].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a filter.
+    pub fn filter<T: Clone + 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, T>,
+        predicate: impl Fn(&T) -> bool,
+    ) -> &[T] {
+        expect_row(self.update_filter(handle.id, predicate))
+    }
+
+    /// updates a row added with [add_zip][Self::add_zip].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a zip.
+    pub fn zip<A: Clone + 'static, B: Clone + 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, (A, B)>,
+    ) -> &[(A, B)] {
+        expect_row(self.update_zip(handle.id))
+    }
+
+    /// updates a row added with [add_map][Self::add_map].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a map.
+    pub fn map<A: 'static, B: 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, B, A>,
+        f: impl Fn(&A) -> B,
+    ) -> &[B] {
+        expect_row(self.update_map(handle.id, f))
+    }
+
+    /// updates a row added with [add_join][Self::add_join].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a join.
+    pub fn join<A, B, K>(
+        &mut self,
+        handle: QueryHandle<QueryID, (A, B)>,
+        key_a: impl Fn(&A) -> K,
+        key_b: impl Fn(&B) -> K,
+    ) -> &[(A, B)]
+    where
+        A: Clone + 'static,
+        B: Clone + 'static,
+        K: Eq + Hash,
+    {
+        expect_row(self.update_join(handle.id, key_a, key_b))
+    }
+
+    /// updates a row added with [add_left_join][Self::add_left_join].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a left join.
+    pub fn left_join<A, B, K>(
+        &mut self,
+        handle: QueryHandle<QueryID, (A, Option<B>)>,
+        key_a: impl Fn(&A) -> K,
+        key_b: impl Fn(&B) -> K,
+    ) -> &[(A, Option<B>)]
+    where
+        A: Clone + 'static,
+        B: Clone + 'static,
+        K: Eq + Hash,
+    {
+        expect_row(self.update_left_join(handle.id, key_a, key_b))
+    }
+
+    /// updates a row added with [add_group_by][Self::add_group_by].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a group by.
+    pub fn group_by<T, K, V>(
+        &mut self,
+        handle: QueryHandle<QueryID, (K, V), T>,
+        key: impl Fn(&T) -> K,
+        aggregate: impl Fn(&[T]) -> V,
+    ) -> &[(K, V)]
+    where
+        T: Clone + 'static,
+        K: Eq + Hash + Clone + 'static,
+        V: 'static,
+    {
+        expect_row(self.update_group_by(handle.id, key, aggregate))
+    }
+
+    /// updates a row added with [add_count][Self::add_count].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a count.
+    pub fn count(&mut self, handle: QueryHandle<QueryID, usize>) -> usize {
+        expect_row(self.update_count(handle.id))
+    }
+
+    /// updates a row added with [add_distinct][Self::add_distinct].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a distinct.
+    pub fn distinct<T: Clone + PartialEq + 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, T>,
+    ) -> &[T] {
+        expect_row(self.update_distinct(handle.id))
+    }
+
+    /// updates a row added with [add_sort][Self::add_sort].
+    ///
+    /// Panics if the handle is from a different table or its row isn't a sort.
+    pub fn sort<T: Clone + 'static, K: Ord>(
+        &mut self,
+        handle: QueryHandle<QueryID, T>,
+        key: impl Fn(&T) -> K,
+    ) -> &[T] {
+        expect_row(self.update_sort(handle.id, key))
+    }
+
+    /// updates a row added with [add_any][Self::add_any].
+    ///
+    /// Panics if the handle is from a different table or its row isn't an any.
+    pub fn any<T: 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, bool, T>,
+        predicate: impl Fn(&T) -> bool,
+    ) -> bool {
+        expect_row(self.update_any(handle.id, predicate))
+    }
+
+    /// updates a row added with [add_all][Self::add_all].
+    ///
+    /// Panics if the handle is from a different table or its row isn't an all.
+    pub fn all<T: 'static>(
+        &mut self,
+        handle: QueryHandle<QueryID, bool, T>,
+        predicate: impl Fn(&T) -> bool,
+    ) -> bool {
+        expect_row(self.update_all(handle.id, predicate))
     }
 
     /// updates a row of the table with the given output, doing no processing.
@@ -42,13 +523,14 @@ impl<QueryID: Hash + Eq + Copy + std::fmt::Debug> ConditionTables<QueryID> {
         id: QueryID,
         output: Vec<T>,
     ) -> Result<&[T], TableError<QueryID>> {
-        let query = self.composes.get(&id).ok_or(TableError::ComposeNotFound)?;
+        let query = self
+            .composes
+            .get(&id)
+            .ok_or(TableError::ComposeNotFound(id))?;
         if query.is_none() {
-            let query_output = self.query_output.get_mut(&id)?;
-            *query_output = output;
-            Ok(query_output.as_slice())
+            self.write(id, output)
         } else {
-            Err(TableError::MismatchedQueryAction)
+            Err(self.mismatched(id, "set"))
         }
     }
 
@@ -58,21 +540,17 @@ impl<QueryID: Hash + Eq + Copy + std::fmt::Debug> ConditionTables<QueryID> {
         id: QueryID,
         predicate: impl Fn(&T) -> bool,
     ) -> Result<&[T], TableError<QueryID>> {
-        let query = self.composes.get(&id).ok_or(TableError::ComposeNotFound)?;
-        let query = query.as_ref().ok_or(TableError::MismatchedQueryAction)?;
-        match query {
+        match self.compose(id, "filter")? {
             Compose::Filter(other_id) => {
-                let prev_output = self.query_output.get::<Vec<T>>(other_id)?;
+                let prev_output = self.query_output.get::<Vec<T>>(&other_id)?;
                 let output = prev_output
                     .iter()
                     .cloned()
                     .filter(predicate)
                     .collect::<Vec<T>>();
-                let query_output = self.query_output.get_mut(&id)?;
-                *query_output = output;
-                Ok(query_output.as_slice())
+                self.write(id, output)
             }
-            _ => Err(TableError::MismatchedQueryAction),
+            _ => Err(self.mismatched(id, "filter")),
         }
     }
 
@@ -81,12 +559,10 @@ impl<QueryID: Hash + Eq + Copy + std::fmt::Debug> ConditionTables<QueryID> {
         &mut self,
         id: QueryID,
     ) -> Result<&[(A, B)], TableError<QueryID>> {
-        let query = self.composes.get(&id).ok_or(TableError::ComposeNotFound)?;
-        let query = query.as_ref().ok_or(TableError::MismatchedQueryAction)?;
-        match query {
+        match self.compose(id, "zip")? {
             Compose::Zip(id_1, id_2) => {
-                let zip_1 = self.query_output.get::<Vec<A>>(id_1)?;
-                let zip_2 = self.query_output.get::<Vec<B>>(id_2)?;
+                let zip_1 = self.query_output.get::<Vec<A>>(&id_1)?;
+                let zip_2 = self.query_output.get::<Vec<B>>(&id_2)?;
 
                 let output = zip_1
                     .iter()
@@ -94,46 +570,461 @@ impl<QueryID: Hash + Eq + Copy + std::fmt::Debug> ConditionTables<QueryID> {
                     .zip(zip_2.iter().cloned())
                     .collect::<Vec<(A, B)>>();
 
-                let query_output = self.query_output.get_mut(&id)?;
-                *query_output = output;
-                Ok(query_output.as_slice())
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "zip")),
+        }
+    }
+
+    /// transforms each output of a row and writes them to another row.
+    pub fn update_map<A: 'static, B: 'static>(
+        &mut self,
+        id: QueryID,
+        f: impl Fn(&A) -> B,
+    ) -> Result<&[B], TableError<QueryID>> {
+        match self.compose(id, "map")? {
+            Compose::Map(other_id) => {
+                let output = self
+                    .query_output
+                    .get::<Vec<A>>(&other_id)?
+                    .iter()
+                    .map(f)
+                    .collect::<Vec<B>>();
+                self.write(id, output)
             }
-            _ => Err(TableError::MismatchedQueryAction),
+            _ => Err(self.mismatched(id, "map")),
         }
     }
+
+    /// pairs up outputs of two rows with equal keys, in the order of the first row. Unlike zip, rows don't have to line up.
+    pub fn update_join<A, B, K>(
+        &mut self,
+        id: QueryID,
+        key_a: impl Fn(&A) -> K,
+        key_b: impl Fn(&B) -> K,
+    ) -> Result<&[(A, B)], TableError<QueryID>>
+    where
+        A: Clone + 'static,
+        B: Clone + 'static,
+        K: Eq + Hash,
+    {
+        match self.compose(id, "join")? {
+            Compose::Join(id_1, id_2) => {
+                let output = self
+                    .join_rows(id_1, id_2, key_a, key_b)?
+                    .into_iter()
+                    .filter_map(|(a, b)| b.map(|b| (a, b)))
+                    .collect::<Vec<(A, B)>>();
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "join")),
+        }
+    }
+
+    /// like [update_join][Self::update_join], but outputs of the first row without a match are kept, paired with `None`.
+    pub fn update_left_join<A, B, K>(
+        &mut self,
+        id: QueryID,
+        key_a: impl Fn(&A) -> K,
+        key_b: impl Fn(&B) -> K,
+    ) -> Result<&[(A, Option<B>)], TableError<QueryID>>
+    where
+        A: Clone + 'static,
+        B: Clone + 'static,
+        K: Eq + Hash,
+    {
+        match self.compose(id, "left join")? {
+            Compose::LeftJoin(id_1, id_2) => {
+                let output = self.join_rows(id_1, id_2, key_a, key_b)?;
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "left join")),
+        }
+    }
+
+    /// groups the outputs of a row by key, in the order each key first appears, and aggregates each group into one value. Ex. `update_group_by(id, |(pool, _)| *pool, |group| group.len())` counts events per pool.
+    pub fn update_group_by<T, K, V>(
+        &mut self,
+        id: QueryID,
+        key: impl Fn(&T) -> K,
+        aggregate: impl Fn(&[T]) -> V,
+    ) -> Result<&[(K, V)], TableError<QueryID>>
+    where
+        T: Clone + 'static,
+        K: Eq + Hash + Clone + 'static,
+        V: 'static,
+    {
+        match self.compose(id, "group by")? {
+            Compose::GroupBy(other_id) => {
+                let mut groups: Vec<(K, Vec<T>)> = Vec::new();
+                let mut group_idx = HashMap::new();
+                for output in self.query_output.get::<Vec<T>>(&other_id)?.iter() {
+                    let key = key(output);
+                    let idx = *group_idx.entry(key.clone()).or_insert_with(|| {
+                        groups.push((key, Vec::new()));
+                        groups.len() - 1
+                    });
+                    groups[idx].1.push(output.clone());
+                }
+                let output = groups
+                    .into_iter()
+                    .map(|(key, group)| {
+                        let value = aggregate(&group);
+                        (key, value)
+                    })
+                    .collect::<Vec<(K, V)>>();
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "group by")),
+        }
+    }
+
+    /// counts the outputs of a row. The count is written as a row with one output.
+    pub fn update_count(&mut self, id: QueryID) -> Result<usize, TableError<QueryID>> {
+        match self.compose(id, "count")? {
+            Compose::Count(other_id) => {
+                let count = *self
+                    .lengths
+                    .get(&other_id)
+                    .ok_or(TableError::QueryNotFound(other_id))?;
+                self.write(id, vec![count])?;
+                Ok(count)
+            }
+            _ => Err(self.mismatched(id, "count")),
+        }
+    }
+
+    /// copies the outputs of a row, leaving out any that are equal to an earlier output.
+    pub fn update_distinct<T: Clone + PartialEq + 'static>(
+        &mut self,
+        id: QueryID,
+    ) -> Result<&[T], TableError<QueryID>> {
+        match self.compose(id, "distinct")? {
+            Compose::Distinct(other_id) => {
+                let mut output: Vec<T> = Vec::new();
+                for prev in self.query_output.get::<Vec<T>>(&other_id)?.iter() {
+                    if !output.contains(prev) {
+                        output.push(prev.clone());
+                    }
+                }
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "distinct")),
+        }
+    }
+
+    /// copies the outputs of a row, sorted by key. Outputs with equal keys stay in the same order.
+    pub fn update_sort<T: Clone + 'static, K: Ord>(
+        &mut self,
+        id: QueryID,
+        key: impl Fn(&T) -> K,
+    ) -> Result<&[T], TableError<QueryID>> {
+        match self.compose(id, "sort")? {
+            Compose::Sort(other_id) => {
+                let mut output = self.query_output.get::<Vec<T>>(&other_id)?.clone();
+                output.sort_by_key(key);
+                self.write(id, output)
+            }
+            _ => Err(self.mismatched(id, "sort")),
+        }
+    }
+
+    /// checks if any output of a row passes the predicate. The result is written as a row with one output.
+    pub fn update_any<T: 'static>(
+        &mut self,
+        id: QueryID,
+        predicate: impl Fn(&T) -> bool,
+    ) -> Result<bool, TableError<QueryID>> {
+        match self.compose(id, "any")? {
+            Compose::Any(other_id) => {
+                let any = self
+                    .query_output
+                    .get::<Vec<T>>(&other_id)?
+                    .iter()
+                    .any(predicate);
+                self.write(id, vec![any])?;
+                Ok(any)
+            }
+            _ => Err(self.mismatched(id, "any")),
+        }
+    }
+
+    /// checks if every output of a row passes the predicate (true for an empty row). The result is written as a row with one output.
+    pub fn update_all<T: 'static>(
+        &mut self,
+        id: QueryID,
+        predicate: impl Fn(&T) -> bool,
+    ) -> Result<bool, TableError<QueryID>> {
+        match self.compose(id, "all")? {
+            Compose::All(other_id) => {
+                let all = self
+                    .query_output
+                    .get::<Vec<T>>(&other_id)?
+                    .iter()
+                    .all(predicate);
+                self.write(id, vec![all])?;
+                Ok(all)
+            }
+            _ => Err(self.mismatched(id, "all")),
+        }
+    }
+
+    /// the compose of a row that's composed from other rows
+    fn compose(
+        &self,
+        id: QueryID,
+        action: &'static str,
+    ) -> Result<Compose<QueryID>, TableError<QueryID>> {
+        let query = self
+            .composes
+            .get(&id)
+            .ok_or(TableError::ComposeNotFound(id))?;
+        query.clone().ok_or_else(|| self.mismatched(id, action))
+    }
+
+    fn mismatched(&self, id: QueryID, action: &'static str) -> TableError<QueryID> {
+        TableError::MismatchedQueryAction {
+            query: id,
+            expected: action,
+            compose: self.composes.get(&id).cloned().flatten(),
+        }
+    }
+
+    fn write<T: 'static>(
+        &mut self,
+        id: QueryID,
+        output: Vec<T>,
+    ) -> Result<&[T], TableError<QueryID>> {
+        self.query_output.get::<Vec<T>>(&id)?;
+        self.lengths.insert(id, output.len());
+        self.bump(id);
+        let query_output = self.query_output.get_mut::<Vec<T>>(&id)?;
+        *query_output = output;
+        Ok(query_output.as_slice())
+    }
+
+    /// left joins two rows
+    fn join_rows<A, B, K>(
+        &self,
+        id_1: QueryID,
+        id_2: QueryID,
+        key_a: impl Fn(&A) -> K,
+        key_b: impl Fn(&B) -> K,
+    ) -> Result<Vec<(A, Option<B>)>, TableError<QueryID>>
+    where
+        A: Clone + 'static,
+        B: Clone + 'static,
+        K: Eq + Hash,
+    {
+        let row_1 = self.query_output.get::<Vec<A>>(&id_1)?;
+        let row_2 = self.query_output.get::<Vec<B>>(&id_2)?;
+
+        let mut by_key: HashMap<K, Vec<&B>> = HashMap::new();
+        for b in row_2.iter() {
+            by_key.entry(key_b(b)).or_default().push(b);
+        }
+
+        let mut output = Vec::new();
+        for a in row_1.iter() {
+            match by_key.get(&key_a(a)) {
+                Some(matches) => {
+                    for b in matches.iter() {
+                        output.push((a.clone(), Some((*b).clone())));
+                    }
+                }
+                None => output.push((a.clone(), None)),
+            }
+        }
+        Ok(output)
+    }
+}
+
+impl<QueryID: Hash + Eq + Copy + Debug> Debug for ConditionTables<QueryID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.dump())
+    }
+}
+
+/// A description of a row of a [ConditionTables], from [ConditionTables::queries].
+#[derive(Clone, Debug)]
+pub struct QueryInfo<QueryID: Copy> {
+    pub id: QueryID,
+    /// `None` for rows that are written directly
+    pub compose: Option<Compose<QueryID>>,
+    /// the type of the row's outputs
+    pub type_name: &'static str,
+    /// the number of outputs currently in the row
+    pub len: usize,
+    /// if the row is kept up to date by [evaluate][ConditionTables::evaluate]
+    pub has_rule: bool,
+}
+
+/// A row of a [ConditionTables] that knows the type of its outputs. Returned when adding a query, and used to update or read the row without restating its type.
+///
+/// Rows whose updates take a function of the source row's outputs (maps, group bys, anys, and alls) also know the type of their source row, `Src`, so the function is checked against it at compile time. Other rows leave it as `()`.
+///
+/// ```compile_fail
+/// # use asterism::tables::ConditionTables;
+/// let mut tables: ConditionTables<u8> = ConditionTables::new();
+/// let numbers = tables.add_single::<u32>(0);
+/// let doubled = tables.add_map::<_, u32, _>(1, numbers);
+/// // the source row holds `u32`s, not `char`s
+/// tables.map(doubled, |c: &char| *c as u32);
+/// ```
+pub struct QueryHandle<QueryID, T, Src = ()> {
+    pub id: QueryID,
+    row: PhantomData<fn() -> (T, Src)>,
+}
+
+impl<QueryID, T, Src> QueryHandle<QueryID, T, Src> {
+    fn new(id: QueryID) -> Self {
+        Self {
+            id,
+            row: PhantomData,
+        }
+    }
+
+    fn with_source<NewSrc>(self) -> QueryHandle<QueryID, T, NewSrc> {
+        QueryHandle::new(self.id)
+    }
+}
+
+// derives would require `T: Clone`
+impl<QueryID: Copy, T, Src> Clone for QueryHandle<QueryID, T, Src> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<QueryID: Copy, T, Src> Copy for QueryHandle<QueryID, T, Src> {}
+
+impl<QueryID: Debug, T, Src> Debug for QueryHandle<QueryID, T, Src> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("QueryHandle").field(&self.id).finish()
+    }
+}
+
+/// a handle only exists for rows that were added with its type, so an error here means it's being used with the wrong table or for the wrong kind of update
+fn expect_row<R, QueryID: Copy + Debug>(result: Result<R, TableError<QueryID>>) -> R {
+    result.unwrap_or_else(|err| panic!("query handle doesn't match its row: {}", err))
 }
 
 /// Possible ways to compose queries.
 #[non_exhaustive]
-#[derive(Clone)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Compose<QueryID: Copy> {
     Filter(QueryID),
     Zip(QueryID, QueryID),
+    Map(QueryID),
+    /// inner join on a key
+    Join(QueryID, QueryID),
+    LeftJoin(QueryID, QueryID),
+    GroupBy(QueryID),
+    /// outputs one value
+    Count(QueryID),
+    Distinct(QueryID),
+    /// stable sort on a key
+    Sort(QueryID),
+    /// outputs one value
+    Any(QueryID),
+    /// outputs one value
+    All(QueryID),
+}
+
+impl<QueryID: Copy> Compose<QueryID> {
+    /// the rows this compose reads from
+    pub fn sources(&self) -> Vec<QueryID> {
+        match self {
+            Compose::Zip(a, b) | Compose::Join(a, b) | Compose::LeftJoin(a, b) => vec![*a, *b],
+            Compose::Filter(id)
+            | Compose::Map(id)
+            | Compose::GroupBy(id)
+            | Compose::Count(id)
+            | Compose::Distinct(id)
+            | Compose::Sort(id)
+            | Compose::Any(id)
+            | Compose::All(id) => vec![*id],
+        }
+    }
 }
 
 #[derive(Debug)]
-// TODO: proper error handling here would be cool...
-pub enum TableError<QueryID: std::fmt::Debug> {
-    ComposeNotFound,
+pub enum TableError<QueryID: Copy + Debug> {
+    /// the query was never added to the table
+    ComposeNotFound(QueryID),
+    /// the query has no row of outputs
     QueryNotFound(QueryID),
-    MismatchedQueryAction,
-    MismatchedTypes(QueryID),
+    /// tried to update a row in a way that doesn't match how it's composed, ex. filtering a zipped row
+    MismatchedQueryAction {
+        query: QueryID,
+        /// the kind of update that was tried
+        expected: &'static str,
+        compose: Option<Compose<QueryID>>,
+    },
+    /// tried to read or write a row as the wrong type
+    MismatchedTypes {
+        query: QueryID,
+        expected: &'static str,
+        actual: &'static str,
+    },
+    /// the row depends on itself
+    Cycle(QueryID),
+}
+
+impl<QueryID: Copy + Debug> std::fmt::Display for TableError<QueryID> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TableError::ComposeNotFound(query) => write!(f, "query {:?} isn't in the table", query),
+            TableError::QueryNotFound(query) => write!(f, "query {:?} has no outputs", query),
+            TableError::MismatchedQueryAction {
+                query,
+                expected,
+                compose,
+            } => write!(
+                f,
+                "can't {} query {:?}, it's composed as {:?}",
+                expected, query, compose
+            ),
+            TableError::MismatchedTypes {
+                query,
+                expected,
+                actual,
+            } => write!(f, "query {:?} holds {}, not {}", query, actual, expected),
+            TableError::Cycle(query) => write!(f, "query {:?} depends on itself", query),
+        }
+    }
 }
 
-use std::any::TypeId;
+impl<QueryID: Copy + Debug> std::error::Error for TableError<QueryID> {}
+
+use std::any::{type_name, TypeId};
 
 /// Wrapper around `anycollections::AnyHashMap` that does typechecking at runtime so you don't have to worry about accidentally transmuting something you shouldn't have and causing undefined behavior. NOTE that `get()` and `get_mut()` in `anycollections::AnyHashMap` are very unsafe, but aren't marked as such.
 ///
 /// The double lookups aren't ideal but work for now. Something like `HashMap/BTreeMap<ID, (TypeId, Box<something something UnsafeAny>)>` (????) would be better performance-wise but I can't be bothered to write that at the moment. (📌 unsafe-any: https://docs.rs/unsafe-any/0.4.2/unsafe_any/)
 struct AnyHashMap<ID: Hash + Eq> {
     map: anycollections::AnyHashMap<ID>,
-    /// `types_map.get(id)` *must* always be the `TypeId` of the type of map.get(id)'s output, otherwise very unsafe things will happen.
-    types_map: HashMap<ID, TypeId>,
+    /// `types_map.get(id)` *must* always describe the type of map.get(id)'s output, otherwise very unsafe things will happen.
+    types_map: HashMap<ID, ValueType<ID>>,
+}
+
+struct ValueType<ID: Hash + Eq> {
+    id: TypeId,
+    name: &'static str,
+    /// formats the value with `Debug`
+    debug: fn(&anycollections::AnyHashMap<ID>, &ID) -> String,
+}
+
+fn debug_value<ID: Hash + Eq, T: Debug + 'static>(
+    map: &anycollections::AnyHashMap<ID>,
+    key: &ID,
+) -> String {
+    format!("{:?}", map.get::<T>(key).unwrap())
 }
 
 impl<ID> AnyHashMap<ID>
 where
-    ID: Hash + Eq + Copy + std::fmt::Debug,
+    ID: Hash + Eq + Copy + Debug,
 {
     fn new() -> Self {
         Self {
@@ -142,32 +1033,50 @@ where
         }
     }
 
-    fn get<T: 'static>(&self, key: &ID) -> Result<&T, TableError<ID>> {
-        if let Some(type_id) = self.types_map.get(key) {
-            if *type_id == TypeId::of::<T>() {
-                return self
-                    .map
-                    .get::<T>(key)
-                    .ok_or(TableError::QueryNotFound(*key));
-            }
+    fn check<T: 'static>(&self, key: &ID) -> Result<(), TableError<ID>> {
+        let value_type = self
+            .types_map
+            .get(key)
+            .ok_or(TableError::QueryNotFound(*key))?;
+        if value_type.id == TypeId::of::<T>() {
+            Ok(())
+        } else {
+            Err(TableError::MismatchedTypes {
+                query: *key,
+                expected: type_name::<T>(),
+                actual: value_type.name,
+            })
         }
-        Err(TableError::MismatchedTypes(*key))
+    }
+
+    fn get<T: 'static>(&self, key: &ID) -> Result<&T, TableError<ID>> {
+        self.check::<T>(key)?;
+        self.map
+            .get::<T>(key)
+            .ok_or(TableError::QueryNotFound(*key))
     }
 
     fn get_mut<T: 'static>(&mut self, key: &ID) -> Result<&mut T, TableError<ID>> {
-        if let Some(type_id) = self.types_map.get(key) {
-            if *type_id == TypeId::of::<T>() {
-                return self
-                    .map
-                    .get_mut::<T>(key)
-                    .ok_or(TableError::QueryNotFound(*key));
-            }
-        }
-        Err(TableError::MismatchedTypes(*key))
+        self.check::<T>(key)?;
+        self.map
+            .get_mut::<T>(key)
+            .ok_or(TableError::QueryNotFound(*key))
     }
 
-    fn insert<T: 'static>(&mut self, key: ID, value: T) {
-        self.types_map.insert(key, TypeId::of::<T>());
+    fn insert<T: Debug + 'static>(&mut self, key: ID, value: T) {
+        self.types_map.insert(
+            key,
+            ValueType {
+                id: TypeId::of::<T>(),
+                name: type_name::<T>(),
+                debug: debug_value::<ID, T>,
+            },
+        );
         self.map.insert(key, value);
     }
+
+    fn debug(&self, key: &ID) -> Option<String> {
+        let value_type = self.types_map.get(key)?;
+        Some((value_type.debug)(&self.map, key))
+    }
 }
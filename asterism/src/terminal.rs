@@ -0,0 +1,238 @@
+//! # Terminal rendering
+//!
+//! Collision logics communicate where things are and how big they are, and that channel can be mapped onto any output device, including a grid of characters. A [TerminalRenderer] draws [AabbCollision] bodies, tile maps, and [QueuedResources] values into a grid of [Glyph]s, which can be shown in a terminal with ANSI colors or turned into a plain string.
+//!
+//! Plain strings don't depend on the terminal or a window, so they also work as snapshots of a game's state in golden-file tests: run a game headless for a few frames, draw it, and compare the string against a file.
+
+use std::fmt::{self, Debug};
+use std::io::{self, Write};
+
+use glam::Vec2;
+
+use crate::collision::{AabbCollision, CollisionData};
+use crate::resources::{QueuedResources, ResourceValue};
+
+/// the eight standard ANSI colors
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Color {
+    /// the escape code setting the foreground to this color
+    fn ansi(&self) -> &'static str {
+        match self {
+            Color::Black => "\x1b[30m",
+            Color::Red => "\x1b[31m",
+            Color::Green => "\x1b[32m",
+            Color::Yellow => "\x1b[33m",
+            Color::Blue => "\x1b[34m",
+            Color::Magenta => "\x1b[35m",
+            Color::Cyan => "\x1b[36m",
+            Color::White => "\x1b[37m",
+        }
+    }
+}
+
+const RESET: &str = "\x1b[0m";
+
+/// A character and the color to draw it in. `None` uses the terminal's default color.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Glyph {
+    pub ch: char,
+    pub color: Option<Color>,
+}
+
+impl Glyph {
+    pub fn new(ch: char, color: Option<Color>) -> Self {
+        Self { ch, color }
+    }
+
+    /// an empty cell
+    pub const BLANK: Glyph = Glyph {
+        ch: ' ',
+        color: None,
+    };
+}
+
+/// A grid of characters that logics are drawn into. Row 0 is the top of the screen, like in the collision logic.
+pub struct TerminalRenderer {
+    /// width in characters
+    pub width: usize,
+    /// height in characters
+    pub height: usize,
+    /// how much of the game's space one character covers on each axis. Characters are usually about twice as tall as they are wide, so ex. `Vec2::new(8.0, 16.0)` keeps a game's proportions.
+    pub scale: Vec2,
+    /// the point in the game's space drawn at the top left of the grid
+    pub origin: Vec2,
+    cells: Vec<Glyph>,
+}
+
+impl TerminalRenderer {
+    /// A blank grid where one character covers one unit of the game's space.
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            scale: Vec2::ONE,
+            origin: Vec2::ZERO,
+            cells: vec![Glyph::BLANK; width * height],
+        }
+    }
+
+    /// blanks every cell
+    pub fn clear(&mut self) {
+        for cell in self.cells.iter_mut() {
+            *cell = Glyph::BLANK;
+        }
+    }
+
+    /// the glyph at a character position, or `None` if it's off the grid
+    pub fn get(&self, x: usize, y: usize) -> Option<Glyph> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Sets the glyph at a character position. Positions off the grid are ignored.
+    pub fn put(&mut self, x: i32, y: i32, glyph: Glyph) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        self.cells[y as usize * self.width + x as usize] = glyph;
+    }
+
+    /// Writes text left to right from a character position, cut off at the edge of the grid.
+    pub fn text(&mut self, x: i32, y: i32, text: &str, color: Option<Color>) {
+        for (i, ch) in text.chars().enumerate() {
+            self.put(x + i as i32, y, Glyph::new(ch, color));
+        }
+    }
+
+    /// the character position a point in the game's space is drawn at
+    pub fn to_cell(&self, pos: Vec2) -> (i32, i32) {
+        let cell = ((pos - self.origin) / self.scale).floor();
+        (cell.x as i32, cell.y as i32)
+    }
+
+    /// Fills the characters a rectangle in the game's space covers, with its edges rounded to the nearest character. Rectangles smaller than a character still get one.
+    pub fn fill_rect(&mut self, top_left: Vec2, size: Vec2, glyph: Glyph) {
+        let min = ((top_left - self.origin) / self.scale).round();
+        let max = ((top_left + size - self.origin) / self.scale).round();
+        let (x0, y0) = (min.x as i32, min.y as i32);
+        let x1 = (max.x as i32).max(x0 + 1);
+        let y1 = (max.y as i32).max(y0 + 1);
+        for y in y0.max(0)..y1.min(self.height as i32) {
+            for x in x0.max(0)..x1.min(self.width as i32) {
+                self.put(x, y, glyph);
+            }
+        }
+    }
+
+    /// Draws each body in a collision logic as a filled rectangle, in order, so later bodies are drawn over earlier ones. `style` picks the glyph for a body from its index and metadata, or `None` to skip it.
+    pub fn draw_aabbs<ID: Copy + Eq>(
+        &mut self,
+        collision: &AabbCollision<ID>,
+        mut style: impl FnMut(usize, &CollisionData<ID>) -> Option<Glyph>,
+    ) {
+        for (i, meta) in collision.metadata.iter().enumerate() {
+            if let Some(glyph) = style(i, meta) {
+                let half_size = collision.half_sizes[i];
+                self.fill_rect(collision.centers[i] - half_size, half_size * 2.0, glyph);
+            }
+        }
+    }
+
+    /// Draws a tile map, ex. a tile map collision logic's `map`, one character per tile with the first row at the top. `style` picks the glyph for a tile, or `None` to leave it blank. Tile maps are already on a grid, so they aren't scaled or offset by `origin`; `x` and `y` are the character position of the top left tile.
+    pub fn draw_tiles<Tile>(
+        &mut self,
+        x: i32,
+        y: i32,
+        map: &[Vec<Option<Tile>>],
+        mut style: impl FnMut(&Tile) -> Option<Glyph>,
+    ) {
+        for (row_idx, row) in map.iter().enumerate() {
+            for (col_idx, tile) in row.iter().enumerate() {
+                if let Some(glyph) = tile.as_ref().and_then(&mut style) {
+                    self.put(x + col_idx as i32, y + row_idx as i32, glyph);
+                }
+            }
+        }
+    }
+
+    /// Writes each pool in a resource logic on its own line starting at a character position, as `id: value`.
+    pub fn draw_resources<ID, Value>(
+        &mut self,
+        x: i32,
+        y: i32,
+        resources: &QueuedResources<ID, Value>,
+        color: Option<Color>,
+    ) where
+        ID: Copy + Ord + Debug,
+        Value: ResourceValue + Debug,
+    {
+        for (i, (id, (value, _, _))) in resources.items.iter().enumerate() {
+            self.text(x, y + i as i32, &format!("{:?}: {:?}", id, value), color);
+        }
+    }
+
+    /// The grid with ANSI color codes, one line per row.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::with_capacity(self.cells.len() * 2);
+        for (y, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if y > 0 {
+                out.push('\n');
+            }
+            let mut color = None;
+            for glyph in row {
+                if glyph.color != color {
+                    out.push_str(glyph.color.map_or(RESET, |color| color.ansi()));
+                    color = glyph.color;
+                }
+                out.push(glyph.ch);
+            }
+            if color.is_some() {
+                out.push_str(RESET);
+            }
+        }
+        out
+    }
+
+    /// Draws the grid over whatever was last presented, for showing a game in a terminal frame by frame. Call [TerminalRenderer::begin] once first and [TerminalRenderer::end] when done.
+    pub fn present(&self, out: &mut impl Write) -> io::Result<()> {
+        // back to the top left, so each frame overwrites the last one instead of scrolling
+        write!(out, "\x1b[H{}", self.to_ansi())?;
+        out.flush()
+    }
+
+    /// clears the terminal and hides the cursor before presenting frames
+    pub fn begin(out: &mut impl Write) -> io::Result<()> {
+        write!(out, "\x1b[2J\x1b[?25l")?;
+        out.flush()
+    }
+
+    /// shows the cursor again and moves it below the last frame
+    pub fn end(out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "{}\x1b[?25h", RESET)?;
+        out.flush()
+    }
+}
+
+/// The grid as plain text without colors, one line per row with trailing spaces trimmed, so snapshots don't change with the terminal or with invisible whitespace.
+impl fmt::Display for TerminalRenderer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.cells.chunks(self.width.max(1)).enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            let line: String = row.iter().map(|glyph| glyph.ch).collect();
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+//! Computer players have to hold exactly the actions from the parts of their policies that succeed, and nothing the keyboard did to their sets.
+use asterism::ai::{AiControl, BehaviorTree, Policy, UtilityPolicy};
+use asterism::control::{InputWrapper, KeyboardControl};
+
+/// keys held this frame are the ones in the input helper
+struct Keys;
+
+impl InputWrapper for Keys {
+    type KeyCode = char;
+    type InputHelper = Vec<char>;
+
+    fn new() -> Self {
+        Keys
+    }
+    fn clear(&mut self) {}
+    fn update_held(&mut self, key: &char, held: &Vec<char>) -> bool {
+        held.contains(key)
+    }
+    fn is_pressed(&self, key: &char, held: &Vec<char>) -> bool {
+        held.contains(key)
+    }
+    fn is_released(&self, key: &char, held: &Vec<char>) -> bool {
+        !held.contains(key)
+    }
+}
+
+type Tree = BehaviorTree<u8, bool>;
+
+fn condition() -> Tree {
+    BehaviorTree::Condition(Box::new(|condition: &bool| *condition))
+}
+
+#[test]
+fn failed_sequences_hold_nothing() {
+    let mut sequence = BehaviorTree::Sequence(vec![
+        BehaviorTree::Hold(vec![1]),
+        condition(),
+        BehaviorTree::Hold(vec![2]),
+    ]);
+    let mut held = vec![0];
+    assert!(!sequence.run(&false, &mut held));
+    assert_eq!(held, vec![0]);
+    assert!(sequence.run(&true, &mut held));
+    assert_eq!(held, vec![0, 1, 2]);
+
+    let mut selector = BehaviorTree::Selector(vec![sequence, BehaviorTree::Hold(vec![3])]);
+    assert_eq!(selector.decide(&false), vec![3]);
+    assert_eq!(selector.decide(&true), vec![1, 2]);
+}
+
+#[test]
+fn not_discards_its_childs_actions() {
+    let mut not = BehaviorTree::Not(Box::new(BehaviorTree::Sequence(vec![
+        BehaviorTree::Hold(vec![1]),
+        condition(),
+    ])));
+    let mut held = Vec::new();
+    assert!(not.run(&false, &mut held));
+    assert!(held.is_empty());
+    assert!(!not.run(&true, &mut held));
+    assert!(held.is_empty());
+
+    let mut tree: Tree = BehaviorTree::Sequence(vec![
+        BehaviorTree::Not(Box::new(BehaviorTree::Hold(vec![1]))),
+        BehaviorTree::Hold(vec![2]),
+    ]);
+    assert!(tree.decide(&true).is_empty());
+}
+
+#[test]
+fn nan_scores_never_win() {
+    let mut utility = UtilityPolicy::new();
+    utility
+        .add_option(
+            Box::new(|nan: &bool| if *nan { f32::NAN } else { 1.0 }),
+            |_: &bool| vec![1u8],
+        )
+        .add_option(Box::new(|_: &bool| -10.0), |_: &bool| vec![2u8])
+        .add_option(Box::new(|_: &bool| f32::NAN), |_: &bool| vec![3u8]);
+    assert_eq!(utility.decide(&false), vec![1]);
+    assert_eq!(utility.decide(&true), vec![2]);
+
+    let mut only_nan = UtilityPolicy::new();
+    only_nan.add_option(Box::new(|_: &bool| f32::NAN), |_: &bool| vec![1u8]);
+    assert!(only_nan.decide(&true).is_empty());
+}
+
+#[test]
+fn computer_sets_ignore_the_keyboard() {
+    let mut control: KeyboardControl<u8, Keys> = KeyboardControl::new();
+    for set in 0..2 {
+        control.add_key_map(set, 'u', 0, true);
+        control.add_key_map(set, 'd', 1, true);
+    }
+    let mut ai = AiControl::new();
+    ai.control_set(1, |down: &bool| if *down { vec![1u8] } else { vec![] });
+    // (value, changed_by) of each action in a set
+    let values = |control: &KeyboardControl<u8, Keys>, set: usize| -> Vec<(f32, f32)> {
+        control.values[set]
+            .iter()
+            .map(|values| (values.value, values.changed_by))
+            .collect()
+    };
+
+    control.update(&vec!['u']);
+    ai.update(&mut control, &true);
+    assert_eq!(values(&control, 0), vec![(1.0, 1.0), (0.0, 0.0)]);
+    assert_eq!(values(&control, 1), vec![(0.0, 0.0), (1.0, 1.0)]);
+
+    control.update(&vec!['u', 'd']);
+    ai.update(&mut control, &true);
+    assert_eq!(values(&control, 1), vec![(0.0, 0.0), (1.0, 0.0)]);
+
+    control.update(&vec!['d']);
+    ai.update(&mut control, &false);
+    assert_eq!(values(&control, 0), vec![(0.0, -1.0), (1.0, 1.0)]);
+    assert_eq!(values(&control, 1), vec![(0.0, 0.0), (0.0, -1.0)]);
+
+    assert!(ai.release_set(1));
+    assert!(!ai.controls_set(1));
+    control.update(&vec!['u']);
+    ai.update(&mut control, &true);
+    assert_eq!(values(&control, 1), vec![(1.0, 1.0), (0.0, 0.0)]);
+}
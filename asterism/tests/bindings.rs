@@ -0,0 +1,108 @@
+//! Bound bodies have to stay paired with the right entities as bodies are added and removed around them.
+use asterism::bindings::{Anchor, Bindings};
+use asterism::collision::AabbCollision;
+use asterism::physics::PointPhysics;
+use glam::Vec2;
+
+/// three physics bodies at (0, 0), (10, 0), and (20, 0), and three 2x2 collision boxes
+fn bodies() -> (PointPhysics, AabbCollision<usize>) {
+    let mut physics = PointPhysics::new();
+    let mut collision = AabbCollision::new();
+    for i in 0..3 {
+        let x = i as f32 * 10.0;
+        physics.add_physics_entity(Vec2::new(x, 0.0), Vec2::new(i as f32, 1.0), Vec2::ZERO);
+        collision.add_collision_entity(Vec2::ZERO, Vec2::ONE, Vec2::ZERO, true, false, i);
+    }
+    (physics, collision)
+}
+
+fn remove_collision_body(collision: &mut AabbCollision<usize>, idx: usize) {
+    collision.centers.remove(idx);
+    collision.half_sizes.remove(idx);
+    collision.velocities.remove(idx);
+    collision.metadata.remove(idx);
+}
+
+#[test]
+fn anchors() {
+    let (mut physics, mut collision) = bodies();
+    let mut bindings = Bindings::new(Anchor::TopLeft);
+    bindings.bind(1, 0);
+    bindings.physics_to_collision(&physics, &mut collision);
+    assert_eq!(collision.centers[0], Vec2::new(11.0, 1.0));
+    assert_eq!(collision.velocities[0], Vec2::new(1.0, 1.0));
+
+    collision.centers[0] = Vec2::new(5.0, 5.0);
+    collision.velocities[0] = Vec2::new(-1.0, 0.0);
+    bindings.collision_to_physics(&collision, &mut physics);
+    assert_eq!(physics.positions[1], Vec2::new(4.0, 4.0));
+    assert_eq!(physics.velocities[1], Vec2::new(-1.0, 0.0));
+
+    bindings.anchor = Anchor::Center;
+    bindings.sync_vel = false;
+    physics.velocities[1] = Vec2::ZERO;
+    bindings.physics_to_collision(&physics, &mut collision);
+    assert_eq!(collision.centers[0], Vec2::new(4.0, 4.0));
+    assert_eq!(collision.velocities[0], Vec2::new(-1.0, 0.0));
+    collision.centers[0] = Vec2::new(7.0, 7.0);
+    bindings.collision_to_physics(&collision, &mut physics);
+    assert_eq!(physics.positions[1], Vec2::new(7.0, 7.0));
+    assert_eq!(physics.velocities[1], Vec2::ZERO);
+}
+
+#[test]
+fn removing_a_body_keeps_the_others_bound() {
+    let (mut physics, mut collision) = bodies();
+    let mut bindings = Bindings::new(Anchor::Center);
+    // bound in reverse so a shift in either index is noticed
+    for i in 0..3 {
+        bindings.bind(i, 2 - i);
+    }
+
+    remove_collision_body(&mut collision, 1);
+    bindings.collision_removed(1);
+    assert_eq!(bindings.get_collision(1), None);
+    assert_eq!(bindings.get_collision(0), Some(1));
+    assert_eq!(bindings.get_collision(2), Some(0));
+
+    bindings.physics_to_collision(&physics, &mut collision);
+    assert_eq!(collision.metadata[1].id, 2);
+    assert_eq!(collision.centers[1], Vec2::new(0.0, 0.0));
+    assert_eq!(collision.metadata[0].id, 0);
+    assert_eq!(collision.centers[0], Vec2::new(20.0, 0.0));
+    assert_eq!(collision.velocities[0], Vec2::new(2.0, 1.0));
+
+    collision.centers[1] = Vec2::new(-5.0, 0.0);
+    collision.centers[0] = Vec2::new(25.0, 0.0);
+    bindings.collision_to_physics(&collision, &mut physics);
+    assert_eq!(physics.positions[0], Vec2::new(-5.0, 0.0));
+    // unbound, so it stays put
+    assert_eq!(physics.positions[1], Vec2::new(10.0, 0.0));
+    assert_eq!(physics.positions[2], Vec2::new(25.0, 0.0));
+}
+
+#[test]
+fn insertions_and_removals_shift_indices() {
+    let mut bindings = Bindings::new(Anchor::Center);
+    bindings.bind(0, 0);
+    bindings.bind(2, 1);
+
+    bindings.physics_inserted(1);
+    assert_eq!(bindings.get_physics(0), Some(0));
+    assert_eq!(bindings.get_physics(1), Some(3));
+    bindings.collision_inserted(0);
+    assert_eq!(bindings.get_collision(0), Some(1));
+    assert_eq!(bindings.get_collision(3), Some(2));
+
+    bindings.physics_removed(0);
+    assert_eq!(bindings.get_physics(1), None);
+    assert_eq!(bindings.get_collision(2), Some(2));
+    bindings.collision_removed(0);
+    assert_eq!(bindings.get_collision(2), Some(1));
+    assert_eq!(bindings.bindings.len(), 1);
+
+    // rebinding replaces the old pair on either side
+    bindings.bind(0, 1);
+    assert_eq!(bindings.get_collision(2), None);
+    assert_eq!(bindings.get_physics(1), Some(0));
+}
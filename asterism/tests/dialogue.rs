@@ -0,0 +1,90 @@
+//! Parsed dialogues have to apply the effects they're written with and show pool values the way they'd be written.
+use asterism::dialogue::Dialogue;
+use asterism::resources::{OrdFloat, QueuedResources};
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+struct Energy;
+
+fn pool(name: &str) -> Option<Energy> {
+    match name {
+        "energy" => Some(Energy),
+        _ => None,
+    }
+}
+
+#[test]
+fn subtracting_negative_values_adds() {
+    let mut dialogue = Dialogue::parse(
+        "title: start
+---
+energy: {energy}
+[[rest|start]] <<set energy -= -3>>
+[[run|start]] <<set $energy -= 1.5>>
+===",
+        pool,
+    )
+    .unwrap();
+    let mut resources = QueuedResources::new();
+    resources
+        .items
+        .insert(Energy, (OrdFloat(1.0f32), OrdFloat(0.0), OrdFloat(10.0)));
+    let mut output = Vec::new();
+    dialogue
+        .run_terminal(&mut resources, "1\n2\n".as_bytes(), &mut output)
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    let energies = output
+        .lines()
+        .filter(|line| line.starts_with("energy"))
+        .collect::<Vec<_>>();
+    assert_eq!(energies, vec!["energy: 1", "energy: 4", "energy: 2.5"]);
+}
+
+#[test]
+fn jumps_out_of_the_start_node_are_taken() {
+    let mut dialogue = Dialogue::parse(
+        "title: start
+---
+you shouldn't see this
+<<jump tired>> <<if energy <= 1>> <<set energy += 1>>
+[[stay|start]]
+===
+title: tired
+---
+tired, energy: {energy}
+===",
+        pool,
+    )
+    .unwrap();
+    let mut resources = QueuedResources::new();
+    resources
+        .items
+        .insert(Energy, (OrdFloat(1.0f32), OrdFloat(0.0), OrdFloat(10.0)));
+    let mut output = Vec::new();
+    dialogue
+        .run_terminal(&mut resources, "".as_bytes(), &mut output)
+        .unwrap();
+
+    assert_eq!(String::from_utf8(output).unwrap(), "tired, energy: 2\n");
+}
+
+#[test]
+fn values_that_cant_be_negated_are_errors() {
+    let dialogue = Dialogue::<Energy, u8>::parse(
+        "title: start
+---
+[[rest|start]] <<set energy -= 3>>
+===",
+        pool,
+    );
+    assert_eq!(dialogue.err().unwrap(), "line 3: can't subtract \"3\"");
+    let dialogue = Dialogue::<Energy, i8>::parse(
+        "title: start
+---
+[[rest|start]] <<set energy -= -128>>
+===",
+        pool,
+    );
+    assert_eq!(dialogue.err().unwrap(), "line 3: can't subtract \"-128\"");
+}
@@ -0,0 +1,131 @@
+//! Nested states: moving between them has to exit and enter the right states, and graphs that can't be nested properly have to be rejected when they're added.
+use asterism::entity_state::{EntityReaction, EntityStateError, FlatEntityState};
+use asterism::Logic;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+enum Jump {
+    Grounded,
+    Airborne,
+    Rising,
+    Falling,
+}
+
+fn jumper() -> FlatEntityState<Jump> {
+    let mut state = FlatEntityState::new();
+    state
+        .add_hierarchical_graph(
+            0,
+            [
+                (Jump::Grounded, None, &[Jump::Airborne]),
+                (Jump::Airborne, None, &[Jump::Grounded]),
+                (Jump::Rising, Some(Jump::Airborne), &[Jump::Falling]),
+                (Jump::Falling, Some(Jump::Airborne), &[]),
+            ],
+        )
+        .unwrap();
+    state
+}
+
+#[test]
+fn moves_through_nested_states() {
+    let mut state = jumper();
+    assert_eq!(state.get_id_for_entity(0), Jump::Grounded);
+
+    // entering Airborne moves to its initial substate
+    state.handle_predicate(&EntityReaction::Activate(0, 1));
+    state.update();
+    assert_eq!(state.get_state_path(0), vec![Jump::Airborne, Jump::Rising]);
+    assert_eq!(state.exited, vec![(0, 0)]);
+    assert_eq!(state.entered, vec![(0, 1), (0, 2)]);
+
+    state.handle_predicate(&EntityReaction::Activate(0, 3));
+    state.update();
+    assert_eq!(state.get_id_for_entity(0), Jump::Falling);
+    assert_eq!(state.exited, vec![(0, 2)]);
+    assert_eq!(state.entered, vec![(0, 3)]);
+
+    // Falling has no edges of its own, so Airborne's edge to Grounded applies
+    state.handle_predicate(&EntityReaction::Activate(0, 0));
+    state.update();
+    assert_eq!(state.get_id_for_entity(0), Jump::Grounded);
+    assert_eq!(state.exited, vec![(0, 3), (0, 1)]);
+    assert_eq!(state.entered, vec![(0, 0)]);
+}
+
+#[test]
+fn rejects_unknown_states() {
+    let mut state = FlatEntityState::new();
+    assert_eq!(
+        state.add_hierarchical_graph(
+            0,
+            [
+                (Jump::Grounded, None, &[Jump::Airborne]),
+                (Jump::Rising, Some(Jump::Airborne), &[]),
+            ],
+        ),
+        Err(EntityStateError::UnknownState(0))
+    );
+    assert_eq!(
+        state.add_graph(2, [(Jump::Grounded, &[])]),
+        Err(EntityStateError::StartNotFound(2))
+    );
+    assert!(state.graphs.is_empty());
+}
+
+#[test]
+fn rejects_parent_cycles() {
+    let mut state = FlatEntityState::new();
+    assert_eq!(
+        state.add_hierarchical_graph(
+            0,
+            [
+                (Jump::Grounded, None, &[]),
+                (Jump::Rising, Some(Jump::Falling), &[]),
+                (Jump::Falling, Some(Jump::Rising), &[]),
+            ],
+        ),
+        Err(EntityStateError::ParentCycle(1))
+    );
+    assert!(state.graphs.is_empty());
+}
+
+#[test]
+fn timeouts_keep_existing_edges() {
+    let mut state = FlatEntityState::new();
+    state
+        .add_graph(0, [(0u8, &[1]), (1, &[0]), (2, &[])])
+        .unwrap();
+    // 1 -> 0 is already traversable when 0 is activated
+    state.add_timeout(0, 1, 3, 0).unwrap();
+    state.add_timeout(0, 1, 5, 2).unwrap();
+    assert_eq!(
+        state.add_timeout(0, 1, 5, 3),
+        Err(EntityStateError::TimeoutStateNotFound)
+    );
+    assert_eq!(
+        state.add_timeout(0, 4, 5, 0),
+        Err(EntityStateError::TimeoutStateNotFound)
+    );
+    assert_eq!(state.graphs[0].graph.get_edges(1), vec![0, 2]);
+
+    state.handle_predicate(&EntityReaction::Traverse(0, 1));
+    state.handle_predicate(&EntityReaction::Activate(0, 0));
+    state.update();
+    assert_eq!(state.get_id_for_entity(0), 0);
+
+    state.handle_predicate(&EntityReaction::Traverse(0, 1));
+    for _ in 0..3 {
+        state.update();
+    }
+    assert_eq!(state.get_id_for_entity(0), 0);
+}
+
+#[test]
+fn setting_the_state_enters_and_exits() {
+    let mut state = jumper();
+    state.update_ident_data(0, Jump::Airborne);
+    assert_eq!(state.get_state_path(0), vec![Jump::Airborne, Jump::Rising]);
+    assert_eq!(state.exited, vec![(0, 0)]);
+    assert_eq!(state.entered, vec![(0, 1), (0, 2)]);
+    assert_eq!(state.ticks_in(0, Jump::Airborne), Some(0));
+}
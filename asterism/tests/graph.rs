@@ -0,0 +1,272 @@
+//! Graph algorithms have to give the same answers with either kind of edge storage.
+use std::rc::Rc;
+
+use asterism::graph::{
+    EdgeData, Graph, GraphStorage, Guard, GuardData, StateMachine, StateMachineDataError,
+    Transition,
+};
+
+/// 0 -> 1 (weight 4), 0 -> 2, 2 -> 1, 1 <-> 3, and 4 on its own
+fn graph(storage: GraphStorage) -> Graph<char> {
+    let mut graph = Graph::with_storage(storage);
+    graph.add_nodes(&['a', 'b', 'c', 'd', 'e']);
+    graph.add_edge_with_data(
+        0,
+        1,
+        EdgeData {
+            weight: 4.0,
+            label: None,
+        },
+    );
+    graph.add_edge(0, 2);
+    graph.add_edge(2, 1);
+    graph.add_edge(1, 3);
+    graph.add_edge(3, 1);
+    graph
+}
+
+const STORAGES: [GraphStorage; 2] = [GraphStorage::AdjacencyMatrix, GraphStorage::AdjacencyList];
+
+#[test]
+fn traversals() {
+    for storage in STORAGES {
+        let graph = graph(storage);
+        assert_eq!(graph.bfs(0), vec![0, 1, 2, 3]);
+        assert_eq!(graph.dfs(0), vec![0, 1, 3, 2]);
+        assert!(graph.is_reachable(2, 3));
+        assert!(!graph.is_reachable(0, 4));
+        assert!(!graph.is_reachable(1, 0));
+    }
+}
+
+#[test]
+fn shortest_paths() {
+    for storage in STORAGES {
+        let graph = graph(storage);
+        assert_eq!(graph.shortest_path(0, 3), Some((3.0, vec![0, 2, 1, 3])));
+        assert_eq!(graph.shortest_path(0, 0), Some((0.0, vec![0])));
+        assert_eq!(graph.shortest_path(0, 4), None);
+    }
+}
+
+#[test]
+fn strongly_connected_components() {
+    for storage in STORAGES {
+        let mut components = graph(storage).strongly_connected_components();
+        for component in components.iter_mut() {
+            component.sort_unstable();
+        }
+        assert_eq!(components, vec![vec![1, 3], vec![2], vec![0], vec![4]]);
+    }
+}
+
+#[test]
+fn long_chains_dont_overflow_the_stack() {
+    const LEN: usize = 200_000;
+    let mut graph = Graph::with_storage(GraphStorage::AdjacencyList);
+    graph.add_nodes(&(0..LEN).collect::<Vec<_>>());
+    for node in 0..LEN - 1 {
+        graph.add_edge(node, node + 1);
+    }
+    graph.add_edge(LEN - 1, 0);
+    let components = graph.strongly_connected_components();
+    assert_eq!(components.len(), 1);
+    assert_eq!(components[0].len(), LEN);
+}
+
+#[test]
+fn node_indices_follow_removals() {
+    for storage in STORAGES {
+        let mut graph = graph(storage);
+        assert_eq!(graph.node_idx(&'d'), Some(3));
+        graph.remove_node(1);
+        assert_eq!(graph.nodes(), &['a', 'c', 'd', 'e']);
+        assert_eq!(graph.node_idx(&'b'), None);
+        assert_eq!(graph.node_idx(&'d'), Some(2));
+        assert_eq!(graph.get_edges(0), vec![1]);
+
+        graph.set_node(2, 'z');
+        assert_eq!(graph.node_idx(&'d'), None);
+        assert_eq!(graph.node_idx(&'z'), Some(2));
+        assert_eq!(graph.add_node('f'), 4);
+        assert_eq!(graph.node_idx(&'f'), Some(4));
+    }
+}
+
+#[test]
+fn state_machines_keep_their_last_node() {
+    let mut machine = StateMachine::new();
+    machine.add_nodes(&['a', 'b']);
+    machine.graph.add_edge(0, 1);
+    machine.current_node = 1;
+    assert!(!machine.remove_node(2));
+    assert!(machine.remove_node(0));
+    assert_eq!(machine.current_node, 0);
+    assert_eq!(machine.get_current_node(), 'b');
+    assert!(!machine.remove_node(0));
+    assert_eq!(machine.get_current_node(), 'b');
+}
+
+/// 'a' with an edge to each of 'b', 'c', and 'd'
+fn fork() -> StateMachine<char> {
+    let mut machine = StateMachine::new();
+    machine.add_nodes(&['a', 'b', 'c', 'd']);
+    for to in 1..4 {
+        machine.add_transition(0, to, Transition::new());
+    }
+    machine
+}
+
+#[test]
+fn transitions_prefer_high_priorities_then_low_indices() {
+    let mut machine = fork();
+    machine.conditions.fill(true);
+    assert_eq!(machine.next_node_from(0), Some(1));
+
+    machine.transitions.get_mut(&(0, 3)).unwrap().priority = 2;
+    machine.transitions.get_mut(&(0, 2)).unwrap().priority = 2;
+    assert_eq!(machine.next_node_from(0), Some(2));
+
+    // a higher priority doesn't matter if the guard doesn't pass
+    machine.transitions.get_mut(&(0, 1)).unwrap().priority = 5;
+    machine.conditions[1] = false;
+    assert_eq!(machine.next_node_from(0), Some(2));
+    machine.conditions.fill(false);
+    assert_eq!(machine.next_node_from(0), None);
+}
+
+#[test]
+fn guards() {
+    let mut machine = fork();
+    let set_guard = |machine: &mut StateMachine<char>, guard| {
+        machine.transitions.get_mut(&(0, 1)).unwrap().guard = guard;
+    };
+
+    set_guard(&mut machine, Guard::NodeActivated(3));
+    machine.conditions[1] = true;
+    assert!(!machine.transitions[&(0, 1)].guard.check(&machine, 0, 1));
+    machine.conditions[3] = true;
+    assert!(machine.transitions[&(0, 1)].guard.check(&machine, 0, 1));
+    machine.conditions.fill(false);
+
+    set_guard(&mut machine, Guard::After(2));
+    machine.tick();
+    assert_eq!(machine.next_node_from(0), None);
+    machine.tick();
+    assert_eq!(machine.next_node_from(0), Some(1));
+    machine.enter(0);
+    assert_eq!(machine.next_node_from(0), None);
+
+    set_guard(
+        &mut machine,
+        Guard::Any(vec![Guard::Never, Guard::NodeActivated(2)]),
+    );
+    assert_eq!(machine.next_node_from(0), None);
+    machine.conditions[2] = true;
+    // 0 -> 2 passes too, but 0 -> 1 has the lower index
+    assert_eq!(machine.next_node_from(0), Some(1));
+    machine.conditions.fill(false);
+
+    set_guard(
+        &mut machine,
+        Guard::All(vec![Guard::Always, Guard::Activated]),
+    );
+    assert_eq!(machine.next_node_from(0), None);
+    machine.conditions[1] = true;
+    assert_eq!(machine.next_node_from(0), Some(1));
+    set_guard(
+        &mut machine,
+        Guard::All(vec![Guard::Activated, Guard::Not(Box::new(Guard::Always))]),
+    );
+    assert_eq!(machine.next_node_from(0), None);
+    machine.conditions.fill(false);
+
+    set_guard(
+        &mut machine,
+        Guard::Predicate(Rc::new(|machine: &StateMachine<char>, to| {
+            machine.ticks == 4 && to == 1
+        })),
+    );
+    assert_eq!(machine.next_node_from(0), None);
+    machine.tick();
+    machine.tick();
+    assert_eq!(machine.next_node_from(0), Some(1));
+
+    set_guard(&mut machine, Guard::Never);
+    machine.conditions[1] = true;
+    assert_eq!(machine.next_node_from(0), None);
+    set_guard(&mut machine, Guard::Always);
+    machine.conditions[1] = false;
+    assert_eq!(machine.next_node_from(0), Some(1));
+}
+
+#[test]
+fn removals_shift_guards() {
+    let mut machine = StateMachine::new();
+    machine.add_nodes(&['a', 'b', 'c', 'd']);
+    machine.add_transition(
+        0,
+        3,
+        Transition {
+            guard: Guard::Any(vec![
+                Guard::NodeActivated(1),
+                Guard::All(vec![Guard::NodeActivated(2)]),
+            ]),
+            ..Transition::new()
+        },
+    );
+    assert!(machine.remove_node(1));
+
+    // 'c' is now node 1, and nothing can activate the removed 'b'
+    machine.conditions[1] = true;
+    assert_eq!(machine.next_node_from(0), Some(2));
+    machine.conditions[1] = false;
+    assert_eq!(machine.next_node_from(0), None);
+    match &machine.transitions[&(0, 2)].guard {
+        Guard::Any(guards) => {
+            assert!(matches!(guards[0], Guard::Never));
+            assert!(
+                matches!(&guards[1], Guard::All(all) if matches!(all[0], Guard::NodeActivated(1)))
+            );
+        }
+        _ => panic!("the guard should still be an Any"),
+    }
+}
+
+#[test]
+fn imports_reject_missing_nodes() {
+    let machine = StateMachine::<char>::from_json(
+        r#"{"nodes": ["a", "b"], "edges": [{"from": 0, "to": 1}], "current_node": 1, "activated": [0]}"#,
+    )
+    .unwrap();
+    assert_eq!(machine.get_current_node(), 'b');
+    assert!(machine.conditions[0]);
+
+    let mut data = machine.to_data();
+    data.edges[0].to = 2;
+    assert_eq!(
+        StateMachine::from_data(&data).err(),
+        Some(StateMachineDataError::Edge(0))
+    );
+    data.edges[0].to = 1;
+    data.edges[0].guard = Some(GuardData::Not(Box::new(GuardData::NodeActivated(5))));
+    assert_eq!(
+        StateMachine::from_data(&data).err(),
+        Some(StateMachineDataError::Guard(0))
+    );
+    data.edges[0].guard = None;
+    data.last_activated = vec![7];
+    assert_eq!(
+        StateMachine::from_data(&data).err(),
+        Some(StateMachineDataError::LastActivated(7))
+    );
+
+    assert!(
+        StateMachine::<char>::from_json(r#"{"nodes": ["a"], "edges": [], "current_node": 3}"#)
+            .is_err()
+    );
+    assert!(
+        StateMachine::<char>::from_json(r#"{"nodes": ["a"], "edges": [], "activated": [1]}"#)
+            .is_err()
+    );
+}
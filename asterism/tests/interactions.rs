@@ -0,0 +1,78 @@
+//! Interaction tables are meant to be edited as data, so they have to survive a round trip through each format and dispatch in the order they're written.
+use asterism::interactions::InteractionTable;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+enum Event {
+    Hit(u8),
+    Scored,
+}
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+enum Reaction {
+    Bounce,
+    Add(String, i32),
+}
+
+fn table() -> InteractionTable<Event, Reaction> {
+    let mut table = InteractionTable::new();
+    table.add_interaction(Event::Hit(0), Reaction::Bounce);
+    table.add_interaction(Event::Scored, Reaction::Add("score".to_string(), 1));
+    table.add_interaction(Event::Hit(0), Reaction::Add("hits".to_string(), 1));
+    table
+}
+
+#[test]
+fn dispatch() {
+    let table = table();
+    assert_eq!(table.interactions.len(), 2);
+    assert_eq!(
+        table.dispatch(vec![Event::Hit(1), Event::Scored, Event::Hit(0)]),
+        vec![
+            (Event::Scored, &Reaction::Add("score".to_string(), 1)),
+            (Event::Hit(0), &Reaction::Bounce),
+            (Event::Hit(0), &Reaction::Add("hits".to_string(), 1)),
+        ]
+    );
+
+    let mut table = table;
+    table.remove_interactions(&Event::Hit(0));
+    assert!(table.get_reactions(&Event::Hit(0)).is_empty());
+    assert_eq!(table.dispatch(vec![Event::Scored]).len(), 1);
+}
+
+#[test]
+fn json_round_trip() {
+    let table = table();
+    assert_eq!(
+        InteractionTable::from_json(&table.to_json()).unwrap(),
+        table
+    );
+    let written = InteractionTable::<Event, Reaction>::from_json(
+        r#"[{ "when": { "Hit": 0 }, "then": ["Bounce", { "Add": ["hits", 1] }] }]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        written.get_reactions(&Event::Hit(0)),
+        vec![&Reaction::Bounce, &Reaction::Add("hits".to_string(), 1)]
+    );
+    assert!(InteractionTable::<Event, Reaction>::from_json(
+        r#"[{ "when": "Missed", "then": [] }]"#
+    )
+    .is_err());
+}
+
+#[cfg(feature = "ron")]
+#[test]
+fn ron_round_trip() {
+    let table = table();
+    assert_eq!(InteractionTable::from_ron(&table.to_ron()).unwrap(), table);
+    let written = InteractionTable::<Event, Reaction>::from_ron(
+        r#"[(when: Hit(0), then: [Bounce, Add("hits", 1)])]"#,
+    )
+    .unwrap();
+    assert_eq!(
+        written.get_reactions(&Event::Hit(0)),
+        vec![&Reaction::Bounce, &Reaction::Add("hits".to_string(), 1)]
+    );
+}
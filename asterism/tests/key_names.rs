@@ -0,0 +1,17 @@
+//! Saved controls name their keys, so every key has to come back from its name.
+#![cfg(feature = "macroquad-render")]
+use asterism::control::{KeyName, MacroquadInputWrapper};
+use macroquad::prelude::KeyCode;
+
+#[test]
+fn every_key_round_trips() {
+    let keys = MacroquadInputWrapper::KEYCODES;
+    // `Unknown` is the last keycode
+    assert_eq!(keys.len(), KeyCode::Unknown as usize + 1);
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(*key as usize, i, "{:?} is out of order", key);
+        assert_eq!(KeyCode::from_key_name(&key.key_name()), Some(*key));
+    }
+    assert_eq!(KeyCode::LeftShift.key_name(), "LeftShift");
+    assert_eq!(KeyCode::from_key_name("Shift"), None);
+}
@@ -0,0 +1,123 @@
+//! Both peers have to run every frame with the same input, even when packets are dropped, arrive late, or can't be decoded.
+use std::io;
+
+use asterism::control::{InputWrapper, KeyboardControl};
+use asterism::network::{LoopbackTransport, NetworkControl, Transport};
+
+/// keys held this frame are the ones in the input helper
+struct Keys;
+
+impl InputWrapper for Keys {
+    type KeyCode = char;
+    type InputHelper = Vec<char>;
+
+    fn new() -> Self {
+        Keys
+    }
+    fn clear(&mut self) {}
+    fn update_held(&mut self, key: &char, held: &Vec<char>) -> bool {
+        held.contains(key)
+    }
+    fn is_pressed(&self, key: &char, held: &Vec<char>) -> bool {
+        held.contains(key)
+    }
+    fn is_released(&self, key: &char, held: &Vec<char>) -> bool {
+        !held.contains(key)
+    }
+}
+
+/// a loopback connection that drops some packets, delivers some late and out of order, and sends garbage
+struct Unreliable {
+    loopback: LoopbackTransport,
+    sent: u32,
+    late: Vec<Vec<u8>>,
+}
+
+impl Transport for Unreliable {
+    fn send(&mut self, packet: &[u8]) -> io::Result<()> {
+        self.sent += 1;
+        match self.sent % 5 {
+            1 => Ok(()),
+            2 => {
+                self.late.push(packet.to_vec());
+                Ok(())
+            }
+            3 => {
+                self.loopback.send(b"not an input packet")?;
+                self.loopback.send(&packet[..packet.len() / 2])?;
+                self.loopback.send(packet)
+            }
+            _ => {
+                self.loopback.send(packet)?;
+                for packet in self.late.drain(..) {
+                    self.loopback.send(&packet)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    fn recv(&mut self) -> io::Result<Option<Vec<u8>>> {
+        self.loopback.recv()
+    }
+}
+
+/// set 0 is the first player's and set 1 is the second's, both jumping with `x`
+fn control() -> KeyboardControl<u8, Keys> {
+    let mut control = KeyboardControl::new();
+    control.add_key_map(0, 'x', 0, true);
+    control.add_key_map(1, 'x', 0, true);
+    control
+}
+
+/// both sets' values, for comparing what each peer saw
+fn frame_input(control: &KeyboardControl<u8, Keys>) -> Vec<(f32, f32)> {
+    control
+        .values
+        .iter()
+        .map(|set| (set[0].value, set[0].changed_by))
+        .collect()
+}
+
+#[test]
+fn peers_see_the_same_input() {
+    let (first, second) = LoopbackTransport::pair();
+    let unreliable = |loopback| Unreliable {
+        loopback,
+        sent: 0,
+        late: Vec::new(),
+    };
+    let mut first = (NetworkControl::new(unreliable(first), 0, 1, 3), control());
+    let mut second = (NetworkControl::new(unreliable(second), 1, 0, 3), control());
+    let (mut first_frames, mut second_frames) = (Vec::new(), Vec::new());
+    let mut stalls = 0;
+
+    for tick in 0..300 {
+        let held = if tick % 7 < 3 { vec!['x'] } else { vec![] };
+        if first.0.update(&mut first.1, &held).unwrap() {
+            first_frames.push(frame_input(&first.1));
+        } else {
+            stalls += 1;
+        }
+
+        // the second peer falls behind every few ticks
+        if tick % 4 == 3 {
+            continue;
+        }
+        let held = if tick % 5 < 2 { vec!['x'] } else { vec![] };
+        if second.0.update(&mut second.1, &held).unwrap() {
+            second_frames.push(frame_input(&second.1));
+        } else {
+            stalls += 1;
+        }
+    }
+
+    let frames = first_frames.len().min(second_frames.len());
+    assert!(frames > 150, "only {} frames ran", frames);
+    assert!(stalls > 0);
+    assert_eq!(first_frames[..frames], second_frames[..frames]);
+    // both players pressed their keys at some point
+    for set in 0..2 {
+        assert!(first_frames.iter().any(|input| input[set].0 == 1.0));
+    }
+}
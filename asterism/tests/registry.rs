@@ -0,0 +1,92 @@
+//! Logics in a registry are only known by name, so every wrong type passed in has to come back as an error instead of a panic or a silently empty result.
+use asterism::collision::{AabbCollision, CollisionEvent};
+use asterism::physics::{PhysicsEvent, PhysicsEventType, PhysicsReaction, PointPhysics};
+use asterism::registry::{DynLogicError, LogicRegistry};
+use asterism::Event;
+use glam::Vec2;
+
+/// a moving physics body, and two overlapping boxes that haven't been checked for collisions yet
+fn registry() -> LogicRegistry {
+    let mut registry = LogicRegistry::new();
+    let mut physics = PointPhysics::new();
+    physics.add_physics_entity(Vec2::ZERO, Vec2::ONE, Vec2::ZERO);
+    registry.register("physics", physics);
+    let mut collision = AabbCollision::<u8>::new();
+    collision.add_collision_entity(Vec2::ZERO, Vec2::ONE, Vec2::ZERO, true, false, 0);
+    collision.add_collision_entity(Vec2::ONE, Vec2::ONE, Vec2::ZERO, true, true, 1);
+    registry.register("collision", collision);
+    registry
+}
+
+#[test]
+fn lookups_by_name() {
+    let mut registry = registry();
+    assert_eq!(registry.names(), vec!["physics", "collision"]);
+    assert!(registry
+        .get("collision")
+        .unwrap()
+        .logic_type()
+        .contains("AabbCollision"));
+    assert_eq!(
+        registry.get("input").err(),
+        Some(DynLogicError::LogicNotFound("input".to_string()))
+    );
+
+    registry
+        .handle_reaction("physics", &PhysicsReaction::SetPos(0, Vec2::new(5.0, 5.0)))
+        .unwrap();
+    assert_eq!(
+        registry
+            .get_as::<PointPhysics>("physics")
+            .unwrap()
+            .positions[0],
+        Vec2::new(5.0, 5.0)
+    );
+    assert!(registry.get_as::<AabbCollision<u8>>("physics").is_none());
+    assert!(registry.get_as::<AabbCollision<u16>>("collision").is_none());
+
+    assert!(registry.remove("physics").is_ok());
+    assert_eq!(registry.names(), vec!["collision"]);
+}
+
+#[test]
+fn events_downcast() {
+    let mut registry = registry();
+    registry.update_all().unwrap();
+
+    let moved: Vec<PhysicsEvent> = registry.events("physics").unwrap();
+    assert_eq!(moved.len(), 1);
+    assert_eq!(moved[0].get_type(), &PhysicsEventType::PosChange);
+    let contacts: Vec<CollisionEvent> = registry.events("collision").unwrap();
+    assert_eq!(contacts, vec![(0, 1)]);
+    assert_eq!(
+        registry.get("collision").unwrap().debug_events(),
+        vec!["(0, 1)"]
+    );
+}
+
+#[test]
+fn mismatched_types() {
+    let mut registry = registry();
+
+    // the boxes haven't collided yet, but the events still aren't physics events
+    assert!(registry.get("collision").unwrap().events().is_empty());
+    assert!(matches!(
+        registry.events::<PhysicsEvent>("collision"),
+        Err(DynLogicError::MismatchedEvents { .. })
+    ));
+    assert!(matches!(
+        registry.events::<CollisionEvent>("physics"),
+        Err(DynLogicError::MismatchedEvents { .. })
+    ));
+
+    assert!(matches!(
+        registry.handle_reaction("physics", &3u8),
+        Err(DynLogicError::MismatchedReaction { .. })
+    ));
+    assert!(matches!(
+        registry.update("collision", &3u8),
+        Err(DynLogicError::MismatchedContext { .. })
+    ));
+    assert!(registry.update("collision", &()).is_ok());
+}
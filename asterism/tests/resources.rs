@@ -0,0 +1,58 @@
+//! Threshold IDs have to keep pointing at the same threshold when others are removed, including between an update and reading its events.
+use asterism::resources::{
+    QueuedResources, ResourceEvent, ResourceEventType, ThresholdKind, Transaction,
+};
+use asterism::OutputTable;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Pool {
+    Score,
+}
+
+fn threshold_events(resources: &QueuedResources<Pool, i32>) -> Vec<ResourceEventType> {
+    OutputTable::<ResourceEvent<Pool>>::get_table(resources)
+        .into_iter()
+        .map(|event| event.event_type)
+        .filter(|event_type| *event_type != ResourceEventType::PoolUpdated)
+        .collect()
+}
+
+#[test]
+fn removing_a_threshold_keeps_other_ids() {
+    let mut resources = QueuedResources::new();
+    resources.items.insert(Pool::Score, (0, 0, 100));
+    let five = resources.add_threshold(Pool::Score, 5, ThresholdKind::CrossedUp);
+    let ten = resources.add_threshold(Pool::Score, 10, ThresholdKind::CrossedUp);
+
+    resources
+        .transactions
+        .push((Pool::Score, Transaction::Change(10)));
+    resources.update();
+    assert_eq!(
+        threshold_events(&resources),
+        vec![
+            ResourceEventType::ThresholdCrossedUp(five),
+            ResourceEventType::ThresholdCrossedUp(ten)
+        ]
+    );
+
+    // removed after the update, so its crossing goes away with it
+    assert!(resources.remove_threshold(five));
+    assert!(!resources.remove_threshold(five));
+    assert_eq!(
+        threshold_events(&resources),
+        vec![ResourceEventType::ThresholdCrossedUp(ten)]
+    );
+
+    resources
+        .transactions
+        .push((Pool::Score, Transaction::Set(0)));
+    resources
+        .transactions
+        .push((Pool::Score, Transaction::Change(10)));
+    resources.update();
+    assert_eq!(
+        threshold_events(&resources),
+        vec![ResourceEventType::ThresholdCrossedUp(ten)]
+    );
+}
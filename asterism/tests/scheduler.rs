@@ -0,0 +1,96 @@
+//! Reordering stages must keep the order the docs promise, and `check` has to catch stages that would read last frame's data.
+use asterism::scheduler::{ScheduleError, Scheduler};
+
+/// input -> physics -> collision -> resources, each recording its name when run
+fn frame() -> Scheduler<Vec<&'static str>> {
+    let mut schedule = Scheduler::new();
+    schedule
+        .add_logic("input", |ran: &mut Vec<_>| ran.push("input"))
+        .reads(&["keyboard"])
+        .writes(&["input"]);
+    schedule
+        .add_logic("physics", |ran: &mut Vec<_>| ran.push("physics"))
+        .reads(&["input"])
+        .writes(&["positions"]);
+    schedule
+        .add_logic("collision", |ran: &mut Vec<_>| ran.push("collision"))
+        .reads(&["positions"])
+        .writes(&["contacts"]);
+    schedule.add_synthesis(
+        "resources",
+        &["contacts"],
+        &["score"],
+        |ran: &mut Vec<_>| ran.push("resources"),
+    );
+    schedule
+}
+
+#[test]
+fn writes_before_reads_pass() {
+    let mut schedule = frame();
+    assert_eq!(schedule.check(), Ok(()));
+    let mut ran = Vec::new();
+    schedule.run(&mut ran);
+    assert_eq!(ran, schedule.order());
+}
+
+#[test]
+fn reads_before_writes_fail() {
+    let mut schedule = frame();
+    schedule.move_before("collision", "physics").unwrap();
+    assert_eq!(
+        schedule.check(),
+        Err(ScheduleError::StaleInput {
+            stage: "collision",
+            input: "positions",
+        })
+    );
+
+    // disabled stages don't write anything
+    schedule.move_after("collision", "physics").unwrap();
+    schedule.set_enabled("physics", false).unwrap();
+    assert_eq!(schedule.check(), Ok(()));
+    let mut ran = Vec::new();
+    schedule.run(&mut ran);
+    assert_eq!(ran, vec!["input", "collision", "resources"]);
+}
+
+#[test]
+fn reordering() {
+    let mut schedule = frame();
+    schedule.move_before("resources", "input").unwrap();
+    assert_eq!(
+        schedule.order(),
+        vec!["resources", "input", "physics", "collision"]
+    );
+    schedule.move_after("resources", "physics").unwrap();
+    assert_eq!(
+        schedule.order(),
+        vec!["input", "physics", "resources", "collision"]
+    );
+    schedule.move_after("input", "collision").unwrap();
+    assert_eq!(
+        schedule.order(),
+        vec!["physics", "resources", "collision", "input"]
+    );
+
+    // unnamed stages keep their order after the named ones
+    schedule.set_order(&["collision", "input"]).unwrap();
+    assert_eq!(
+        schedule.order(),
+        vec!["collision", "input", "physics", "resources"]
+    );
+
+    assert_eq!(
+        schedule.move_before("input", "render"),
+        Err(ScheduleError::StageNotFound("render"))
+    );
+    assert_eq!(
+        schedule.set_order(&["input", "render"]),
+        Err(ScheduleError::StageNotFound("render"))
+    );
+    assert_eq!(
+        schedule.order(),
+        vec!["collision", "input", "physics", "resources"]
+    );
+}
@@ -0,0 +1,107 @@
+//! Composed rows have to be recomputed from their sources with the functions their handles allow.
+use std::cell::Cell;
+use std::rc::Rc;
+
+use asterism::tables::{Compose, ConditionTables, TableError};
+
+#[test]
+fn composed_handles_know_their_source() {
+    let mut tables: ConditionTables<u8> = ConditionTables::new();
+    let scores = tables.add_single::<(char, u32)>(0);
+    let doubled = tables.add_map(1, scores);
+    let totals = tables.add_group_by(2, scores);
+    let any_high = tables.add_any(3, scores);
+    let all_high = tables.add_all(4, scores);
+    tables.set(scores, vec![('a', 3), ('b', 1), ('a', 2)]);
+
+    assert_eq!(tables.map(doubled, |(_, score)| score * 2), &[6, 2, 4]);
+    assert_eq!(
+        tables.group_by(
+            totals,
+            |(player, _)| *player,
+            |group| group.iter().map(|(_, score)| score).sum::<u32>()
+        ),
+        &[('a', 5), ('b', 1)]
+    );
+    assert!(tables.any(any_high, |(_, score)| *score > 2));
+    assert!(!tables.all(all_high, |(_, score)| *score > 2));
+
+    // maps can be composed further like any other row
+    let big = tables.add_filter(5, doubled);
+    assert_eq!(tables.filter(big, |score| *score > 2), &[6, 4]);
+}
+
+#[test]
+fn composed_handles_can_be_looked_up() {
+    let mut tables: ConditionTables<u8> = ConditionTables::new();
+    let scores = tables.add_single::<u32>(0);
+    tables.add_map::<_, String, _>(1, scores);
+    tables.set(scores, vec![1, 20]);
+
+    let strings = tables.get_composed_handle::<String, u32>(1).unwrap();
+    assert_eq!(tables.map(strings, |score| score.to_string()), &["1", "20"]);
+    assert!(matches!(
+        tables.get_composed_handle::<String, char>(1),
+        Err(TableError::MismatchedTypes { query: 0, .. })
+    ));
+    assert!(matches!(
+        tables.get_composed_handle::<u32, ()>(0),
+        Err(TableError::MismatchedQueryAction { compose: None, .. })
+    ));
+    assert_eq!(tables.queries()[1].compose, Some(Compose::Map(0)));
+}
+
+#[test]
+fn rules_run_in_dependency_order_when_their_inputs_change() {
+    let mut tables: ConditionTables<u8> = ConditionTables::new();
+    let numbers = tables.add_single::<u32>(0);
+    // added out of order: the count depends on the filter
+    let big = tables.add_filter(2, numbers);
+    let count = tables.add_count(3, big);
+    let runs = Rc::new(Cell::new(0));
+    let count_runs = runs.clone();
+    tables.add_rule(count, move |tables, count| {
+        count_runs.set(count_runs.get() + 1);
+        tables.count(count);
+    });
+    tables.add_rule(big, |tables, big| {
+        tables.filter(big, |n| *n > 1);
+    });
+    assert_eq!(tables.dependency_order().unwrap(), vec![0, 2, 3]);
+
+    tables.set(numbers, vec![1, 2, 3]);
+    tables.evaluate();
+    assert_eq!(tables.get(count), &[2]);
+    assert_eq!(runs.get(), 1);
+    tables.evaluate();
+    assert_eq!(runs.get(), 1);
+
+    tables.set(numbers, vec![5]);
+    tables.evaluate();
+    assert_eq!(tables.get(count), &[1]);
+    assert_eq!(runs.get(), 2);
+}
+
+#[test]
+fn cycles_are_rejected_without_changing_the_table() {
+    let mut tables: ConditionTables<u8> = ConditionTables::new();
+    tables
+        .add_query::<u32>(0, Some(Compose::Filter(1)))
+        .unwrap();
+    let first = tables.add_query::<u32>(1, Some(Compose::Filter(0)));
+    assert!(matches!(first, Err(TableError::Cycle(_))));
+    assert!(matches!(
+        tables.add_query::<u32>(0, Some(Compose::Filter(0))),
+        Err(TableError::Cycle(0))
+    ));
+
+    let queries = tables.queries();
+    assert_eq!(queries.len(), 1);
+    assert_eq!(queries[0].compose, Some(Compose::Filter(1)));
+    assert_eq!(tables.dependency_order().unwrap(), vec![0]);
+
+    // replacing the row without the cycle is fine
+    let single = tables.add_query::<u32>(1, None).unwrap();
+    tables.set(single, vec![4]);
+    assert_eq!(tables.dependency_order().unwrap(), vec![1, 0]);
+}
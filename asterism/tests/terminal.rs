@@ -0,0 +1,80 @@
+//! The terminal renderer's plain text output is what golden-file tests compare against, so it has to stay the same for the same game state.
+use asterism::collision::AabbCollision;
+use asterism::resources::QueuedResources;
+use asterism::terminal::{Color, Glyph, TerminalRenderer};
+use glam::Vec2;
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+enum Id {
+    Wall,
+    Ball,
+    Lives,
+}
+
+/// a wall along the top, a ball, a small tile map below them, and a pool of lives
+fn draw_scene(terminal: &mut TerminalRenderer) {
+    let mut collision = AabbCollision::new();
+    collision.add_entity_as_xywh(
+        Vec2::ZERO,
+        Vec2::new(24.0, 2.0),
+        Vec2::ZERO,
+        true,
+        true,
+        Id::Wall,
+    );
+    collision.add_entity_as_xywh(
+        Vec2::new(6.0, 2.0),
+        Vec2::new(2.0, 2.0),
+        Vec2::ZERO,
+        true,
+        false,
+        Id::Ball,
+    );
+    terminal.draw_aabbs(&collision, |_, meta| match meta.id {
+        Id::Ball => Some(Glyph::new('o', Some(Color::Yellow))),
+        _ => Some(Glyph::new('=', Some(Color::Blue))),
+    });
+
+    let map = vec![
+        vec![Some(1), None, Some(2), Some(2)],
+        vec![None, Some(1), None, Some(3)],
+    ];
+    terminal.draw_tiles(8, 3, &map, |tile| match tile {
+        1 => Some(Glyph::new('#', None)),
+        2 => Some(Glyph::new('~', Some(Color::Cyan))),
+        _ => None,
+    });
+
+    let mut resources = QueuedResources::new();
+    resources.items.insert(Id::Lives, (3, 0, 3));
+    terminal.draw_resources(0, 4, &resources, Some(Color::Red));
+}
+
+#[test]
+fn snapshot_matches_golden_file() {
+    let mut terminal = TerminalRenderer::new(12, 5);
+    terminal.scale = Vec2::new(2.0, 1.0);
+    draw_scene(&mut terminal);
+    assert_eq!(
+        terminal.to_string(),
+        include_str!("golden/terminal.txt").trim_end()
+    );
+}
+
+#[test]
+fn clearing_leaves_a_blank_snapshot() {
+    let mut terminal = TerminalRenderer::new(12, 5);
+    draw_scene(&mut terminal);
+    terminal.clear();
+    assert_eq!(terminal.to_string(), "\n\n\n\n");
+}
+
+#[test]
+fn ansi_output_colors_runs_of_glyphs() {
+    let mut terminal = TerminalRenderer::new(4, 1);
+    terminal.text(0, 0, "ab", Some(Color::Red));
+    terminal.put(3, 0, Glyph::new('c', None));
+    assert_eq!(terminal.to_ansi(), "\x1b[31mab\x1b[0m c");
+    assert_eq!(terminal.get(0, 0), Some(Glyph::new('a', Some(Color::Red))));
+    assert_eq!(terminal.get(4, 0), None);
+}
@@ -80,6 +80,28 @@ r#"
         ),
     );
 
+    // the character runs away from the player whenever they move
+    for action in [
+        ActionID::Up,
+        ActionID::Down,
+        ActionID::Left,
+        ActionID::Right,
+    ]
+    .iter()
+    {
+        game.add_ctrl_predicate(
+            *action,
+            ControlEventType::KeyPressed,
+            Box::new(|state: &mut State, logics: &mut Logics, _: &CtrlEvent| {
+                let link = logics.linking.graphs[0].get_current_node();
+                if state.links[&link].0 == 0 {
+                    let player = logics.collision.positions[0];
+                    logics.step_away(state.get_col_idx(0, CollisionEnt::Character), player);
+                }
+            }),
+        );
+    }
+
     game.add_link((0, IVec2::new(3, 5)), (1, IVec2::new(1, 1)));
 
     game.add_rsrc_predicate(
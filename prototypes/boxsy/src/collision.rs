@@ -1,9 +1,12 @@
 use std::collections::BTreeMap;
 use std::fmt::Debug;
 
+use asterism::terminal::{Glyph, TerminalRenderer};
 use asterism::{Event, Logic, OutputTable, Reaction};
 use macroquad::math::IVec2;
+use serde::{Deserialize, Serialize};
 
+#[derive(Serialize, Deserialize)]
 pub struct CollisionData<ID> {
     pub solid: bool,
     pub fixed: bool,
@@ -16,6 +19,11 @@ impl<ID> CollisionData<ID> {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "TileID: Serialize, EntID: Serialize",
+    deserialize = "TileID: Deserialize<'de> + Ord, EntID: Deserialize<'de>"
+))]
 pub struct TileMapCollision<TileID: Debug, EntID> {
     pub map: Vec<Vec<Option<TileID>>>,
     pub tile_solid: BTreeMap<TileID, bool>,
@@ -25,7 +33,7 @@ pub struct TileMapCollision<TileID: Debug, EntID> {
     pub contacts: Vec<Contact>,
 }
 
-#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum Contact {
     Ent(usize, usize),
     Tile(usize, IVec2),
@@ -78,6 +86,11 @@ impl<TileID: Copy + Eq + Ord + Debug, EntID: Copy> Logic for TileMapCollision<Ti
     type Reaction = CollisionReaction<TileID, EntID>;
     type Ident = ColIdent;
     type IdentData = TileMapColData<TileID, EntID>;
+    type Context = ();
+
+    fn update(&mut self, _: &()) {
+        TileMapCollision::update(self);
+    }
 
     fn handle_predicate(&mut self, reaction: &Self::Reaction) {
         match reaction {
@@ -200,6 +213,21 @@ impl<TileID: Eq + Ord + Copy + Debug, EntID> TileMapCollision<TileID, EntID> {
         self.tile_solid.clear();
     }
 
+    /// draws the map one character per tile, then the entities over it
+    pub fn draw_terminal(
+        &self,
+        terminal: &mut TerminalRenderer,
+        tile_style: impl FnMut(&TileID) -> Option<Glyph>,
+        mut ent_style: impl FnMut(usize, &EntID) -> Option<Glyph>,
+    ) {
+        terminal.draw_tiles(0, 0, &self.map, tile_style);
+        for (idx, (pos, meta)) in self.positions.iter().zip(self.metadata.iter()).enumerate() {
+            if let Some(glyph) = ent_style(idx, &meta.id) {
+                terminal.put(pos.x, pos.y, glyph);
+            }
+        }
+    }
+
     pub fn update(&mut self) {
         self.contacts.clear();
 
@@ -309,6 +337,17 @@ impl<TileID: Eq + Ord + Copy + Debug, EntID> TileMapCollision<TileID, EntID> {
         }
     }
 
+    /// if an entity could stand on this tile: it's in bounds and either empty or not solid
+    pub fn walkable(&self, pos: IVec2) -> bool {
+        if !self.in_bounds(pos) {
+            return false;
+        }
+        match self.tile_at_pos(&pos) {
+            Some(tile_id) => !self.tile_solid(tile_id),
+            None => true,
+        }
+    }
+
     fn tile_at_pos(&self, pos: &IVec2) -> &Option<TileID> {
         &self.map[pos.y as usize][pos.x as usize]
     }
@@ -325,10 +364,9 @@ impl<TileID: Eq + Ord + Copy + Debug, EntID> TileMapCollision<TileID, EntID> {
     }
 
     fn in_bounds(&self, pos: IVec2) -> bool {
-        pos.x < self.map[0].len() as i32
-            && pos.y < self.map.len() as i32
-            && pos.x >= 0
+        pos.x >= 0
             && pos.y >= 0
+            && matches!(self.map.get(pos.y as usize), Some(row) if (pos.x as usize) < row.len())
     }
 }
 
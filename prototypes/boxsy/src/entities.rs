@@ -81,24 +81,34 @@ impl Game {
         let link_from = find(from);
         let link_to = find(to);
 
-        self.logics.linking.graphs[0]
-            .graph
-            .add_edge(link_from.idx(), link_to.idx());
+        let graph = &mut self.logics.linking.graphs[0].graph;
+        let (from_idx, to_idx) = (
+            graph.node_idx(&link_from).unwrap(),
+            graph.node_idx(&link_to).unwrap(),
+        );
+        graph.add_edge(from_idx, to_idx);
 
         self.add_collision_predicate(
             Contact::Tile(0, from.1),
             from.0,
             Box::new(
                 move |_: &mut State, logics: &mut Logics, _: &(ColEvent, usize)| {
-                    let idx = logics.linking.graphs[0].graph.node_idx(&link_from).unwrap();
+                    // either end of the link may have been removed since
+                    let graph = &logics.linking.graphs[0].graph;
+                    let (from_idx, to_idx) =
+                        match (graph.node_idx(&link_from), graph.node_idx(&link_to)) {
+                            (Some(from_idx), Some(to_idx)) => (from_idx, to_idx),
+                            _ => return,
+                        };
+                    if !graph.edge_exists(from_idx, to_idx) {
+                        return;
+                    }
                     logics
                         .linking
-                        .handle_predicate(&LinkingReaction::Traverse(0, idx));
-
-                    let idx = logics.linking.graphs[0].graph.node_idx(&link_to).unwrap();
+                        .handle_predicate(&LinkingReaction::Traverse(0, from_idx));
                     logics
                         .linking
-                        .handle_predicate(&LinkingReaction::Activate(0, idx));
+                        .handle_predicate(&LinkingReaction::Activate(0, to_idx));
                 },
             ),
         );
@@ -289,6 +299,58 @@ impl Game {
         self.state.rooms[room].chars.remove(ent_idx);
     }
 
+    /// Removes a link location along with every link going to or from it. Returns false if it's the only link location, which can't be removed.
+    pub fn remove_link(&mut self, link: LinkID) -> bool {
+        let graph = &self.logics.linking.graphs[0].graph;
+        let idx = graph
+            .node_idx(&link)
+            .unwrap_or_else(|| panic!("link with id {:?} not found", link));
+        if graph.nodes().len() == 1 {
+            return false;
+        }
+        self.logics
+            .linking
+            .handle_predicate(&LinkingReaction::RemoveNode(0, idx));
+        self.state.links.remove(&link);
+
+        // link predicates refer to nodes by index, so drop the ones about the removed node and shift the rest
+        let shift = |node: &mut usize| {
+            if *node > idx {
+                *node -= 1;
+            }
+        };
+        let mut remove = Vec::new();
+        for (i, (_, event, _)) in self.events.linking.iter_mut().enumerate() {
+            let from = match &mut event.event_type {
+                LinkingEventType::Traversed(from)
+                | LinkingEventType::EdgeAdded(from)
+                | LinkingEventType::EdgeRemoved(from) => Some(from),
+                _ => None,
+            };
+            if event.node == idx || from.as_deref() == Some(&idx) {
+                remove.push(i);
+            }
+            if let Some(from) = from {
+                shift(from);
+            }
+            shift(&mut event.node);
+        }
+        for i in remove.iter().rev() {
+            let _ = self.events.linking.remove(*i);
+        }
+        true
+    }
+
+    /// Removes the link from one location to another, keeping both locations.
+    pub fn remove_link_between(&mut self, from: LinkID, to: LinkID) {
+        let graph = &self.logics.linking.graphs[0].graph;
+        if let (Some(from_idx), Some(to_idx)) = (graph.node_idx(&from), graph.node_idx(&to)) {
+            self.logics
+                .linking
+                .handle_predicate(&LinkingReaction::RemoveEdge(0, (from_idx, to_idx)));
+        }
+    }
+
     // unsure if this is needed atm
     pub fn remove_tile_at_pos(&mut self, room: usize, pos: IVec2) {
         self.state.rooms[room].map[pos.y as usize][pos.x as usize] = None;
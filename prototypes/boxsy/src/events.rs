@@ -2,11 +2,12 @@ use crate::types::*;
 use crate::{Game, Logics, State};
 use asterism::{
     control::ControlEventType, linking::LinkingEvent, linking::LinkingEventType,
-    resources::ResourceEventType, tables::Compose,
+    resources::ResourceEventType,
 };
 
-type PredicateFn<Event> = (
-    UserQueryID,
+/// the query the predicate filters, the event it's filtering for, and what to do when it happens
+type PredicateFn<Event, Row = Event> = (
+    Query<Row>,
     Event,
     Box<dyn Fn(&mut State, &mut Logics, &Event)>,
 );
@@ -50,18 +51,18 @@ impl Game {
         on_key_event: Box<dyn Fn(&mut State, &mut Logics, &CtrlEvent)>,
     ) {
         let query_id = self.events.add_query();
-        self.tables.add_query::<CtrlEvent>(
-            QueryType::User(query_id),
-            Some(Compose::Filter(QueryType::ControlEvent)),
-        );
+        let query = self
+            .tables
+            .add_filter(QueryType::User(query_id), self.queries.control_event);
         let key_event = CtrlEvent {
             event_type: key_event,
             action_id: action,
             set: 0,
         };
-        self.events
-            .control
-            .push((query_id, key_event, on_key_event));
+        self.tables.add_rule(query, move |tables, query| {
+            tables.filter(query, |event: &CtrlEvent| *event == key_event);
+        });
+        self.events.control.push((query, key_event, on_key_event));
     }
 
     pub fn add_link_predicate(
@@ -71,10 +72,9 @@ impl Game {
         when_traversed: Box<dyn Fn(&mut State, &mut Logics, &LinkingEvent)>,
     ) {
         let query_id = self.events.add_query();
-        self.tables.add_query::<LinkingEvent>(
-            QueryType::User(query_id),
-            Some(Compose::Filter(QueryType::LinkingEvent)),
-        );
+        let query = self
+            .tables
+            .add_filter(QueryType::User(query_id), self.queries.linking_event);
         let to = self.logics.linking.graphs[0].graph.node_idx(&to).unwrap();
         let from = self.logics.linking.graphs[0].graph.node_idx(&from).unwrap();
         let event = LinkingEvent {
@@ -83,7 +83,7 @@ impl Game {
             event_type: LinkingEventType::Traversed(from),
         };
 
-        self.events.linking.push((query_id, event, when_traversed));
+        self.events.linking.push((query, event, when_traversed));
     }
 
     #[allow(clippy::type_complexity)]
@@ -94,13 +94,12 @@ impl Game {
         on_collide: Box<dyn Fn(&mut State, &mut Logics, &(ColEvent, usize))>,
     ) {
         let query_id = self.events.add_query();
-        self.tables.add_query::<(ColEvent, (usize, LinkID))>(
-            QueryType::User(query_id),
-            Some(Compose::Filter(QueryType::ContactRoom)),
-        );
+        let query = self
+            .tables
+            .add_filter(QueryType::User(query_id), self.queries.contact_room);
         self.events
             .collision
-            .push((query_id, (col_event, room), on_collide));
+            .push((query, (col_event, room), on_collide));
     }
 
     pub fn add_rsrc_predicate(
@@ -110,16 +109,15 @@ impl Game {
         on_rsrc_event: Box<dyn Fn(&mut State, &mut Logics, &RsrcEvent)>,
     ) {
         let query_id = self.events.add_query();
-        self.tables.add_query::<RsrcEvent>(
-            QueryType::User(query_id),
-            Some(Compose::Filter(QueryType::ResourceEvent)),
-        );
+        let query = self
+            .tables
+            .add_filter(QueryType::User(query_id), self.queries.resource_event);
         let rsrc_event = RsrcEvent {
             pool,
             event_type: rsrc_event,
         };
         self.events
             .resource_event
-            .push((query_id, rsrc_event, on_rsrc_event));
+            .push((query, rsrc_event, on_rsrc_event));
     }
 }
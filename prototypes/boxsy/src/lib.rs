@@ -17,6 +17,7 @@
 //! - [x] Adding/removing entities
 //! - [x] Add linking logics
 //!     - [x] graph/state machine struct
+//! - [x] pathfinding for characters
 //! - [x] composing multiple queries
 
 #![allow(clippy::upper_case_acronyms)]
@@ -24,6 +25,8 @@
 
 use std::collections::BTreeMap;
 
+use asterism::graph::GraphStorage;
+use asterism::scheduler::Scheduler;
 use asterism::tables::*;
 use asterism::{
     control::{KeyboardControl, MacroquadInputWrapper},
@@ -31,6 +34,7 @@ use asterism::{
     resources::QueuedResources,
 };
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 // reexports
 pub use asterism::control::{Action, ControlEventType, ControlReaction, Values};
@@ -39,6 +43,7 @@ pub use asterism::resources::{ResourceEventType, ResourceReaction, Transaction};
 pub use asterism::{Logic, OutputTable};
 pub use collision::*;
 pub use entities::set_current_room;
+pub use pathfinding::FlowField;
 pub use types::*;
 
 const TILE_SIZE: usize = 32;
@@ -48,6 +53,8 @@ pub const GAME_SIZE: usize = TILE_SIZE * WORLD_SIZE;
 mod collision;
 mod entities;
 mod events;
+mod pathfinding;
+mod save;
 mod types;
 use events::*;
 
@@ -67,6 +74,9 @@ pub struct Game {
     events: Events,
     pub colors: Colors,
     tables: ConditionTables<QueryType>,
+    queries: Queries,
+    /// the stages of a frame, see [Game::default_schedule]
+    pub schedule: Scheduler<Game>,
 }
 
 impl Game {
@@ -74,32 +84,38 @@ impl Game {
         let mut tables = ConditionTables::new();
 
         // contacts
-        tables.add_query::<ColEvent>(QueryType::ContactOnly, None);
+        let contact_only = tables.add_single(QueryType::ContactOnly);
 
         // rsrcs
-        tables.add_query::<RsrcEvent>(QueryType::ResourceEvent, None);
-        tables.add_query::<(RsrcID, (u16, u16, u16))>(QueryType::ResourceIdent, None);
+        let resource_event = tables.add_single(QueryType::ResourceEvent);
+        let resource_ident = tables.add_single(QueryType::ResourceIdent);
 
         // ctrl
-        tables.add_query::<CtrlEvent>(QueryType::ControlEvent, None);
+        let control_event = tables.add_single(QueryType::ControlEvent);
+        let control_filter = tables.add_filter(QueryType::ControlFilter, control_event);
+        tables.add_rule(control_filter, |tables, control_filter| {
+            tables.filter(control_filter, |event: &CtrlEvent| {
+                event.event_type == ControlEventType::KeyPressed
+            });
+        });
 
         // linking
-        tables.add_query::<LinkingEvent>(QueryType::LinkingEvent, None);
-        tables.add_query::<LinkingEvent>(
-            QueryType::TraverseRoom,
-            Some(Compose::Filter(QueryType::LinkingEvent)),
-        );
+        let linking_event = tables.add_single(QueryType::LinkingEvent);
+        tables.add_filter(QueryType::TraverseRoom, linking_event);
 
-        tables.add_query::<(usize, LinkID)>(QueryType::LinkingIdent, None);
+        let linking_ident = tables.add_single(QueryType::LinkingIdent);
 
         // col + link
-        tables.add_query::<(ColEvent, (usize, LinkID))>(
-            QueryType::ContactRoom,
-            Some(Compose::Zip(
-                QueryType::ContactOnly,
-                QueryType::LinkingIdent,
-            )),
-        );
+        // contacts all happen in the room graph
+        let contact_link = tables.add_join(QueryType::ContactLink, contact_only, linking_ident);
+        tables.add_rule(contact_link, |tables, contact_link| {
+            tables.join(
+                contact_link,
+                |_: &ColEvent| 0,
+                |(graph, _): &(usize, LinkID)| *graph,
+            );
+        });
+        let contact_room = tables.add_map(QueryType::ContactRoom, contact_link);
 
         Self {
             state: State::new(),
@@ -110,9 +126,54 @@ impl Game {
                 colors: BTreeMap::new(),
             },
             tables,
+            queries: Queries {
+                contact_only,
+                contact_room,
+                linking_event,
+                linking_ident,
+                control_event,
+                control_filter,
+                resource_event,
+                resource_ident,
+            },
+            schedule: Self::default_schedule(),
         }
     }
 
+    /// adding entities, control, collision, resources, linking, then removing entities. Walking onto a link traverses it during collision, and the room it leads to is loaded into collision during linking.
+    pub fn default_schedule() -> Scheduler<Game> {
+        let mut schedule = Scheduler::new();
+        schedule
+            .add_logic("add entities", add_entities)
+            .writes(&["collision", "resources"]);
+        schedule
+            .add_logic("control", control)
+            .writes(&["control", "collision"]);
+        schedule
+            .add_logic("collision", collision)
+            .reads(&["control"])
+            .writes(&["collision", "linking"]);
+        schedule
+            .add_logic("resources", resources)
+            .writes(&["resources"]);
+        schedule
+            .add_logic("linking", linking)
+            .reads(&["linking"])
+            .writes(&["linking", "collision"]);
+        schedule
+            .add_logic("remove entities", remove_entities)
+            .writes(&["collision", "resources"]);
+        debug_assert_eq!(schedule.check(), Ok(()));
+        schedule
+    }
+
+    /// runs one frame of the game
+    pub fn update(&mut self) {
+        let mut schedule = std::mem::take(&mut self.schedule);
+        schedule.run(self);
+        self.schedule = schedule;
+    }
+
     pub fn get_current_room(&self) -> usize {
         let node = self.logics.linking.graphs[0].get_current_node();
         self.state.links.get(&node).unwrap().0
@@ -124,12 +185,14 @@ pub struct Colors {
     pub colors: BTreeMap<EntID, Color>,
 }
 
-#[derive(Default)]
+#[derive(Default, Serialize, Deserialize)]
 pub struct Room {
     pub chars: Vec<(CharacterID, IVec2)>,
     pub map: [[Option<TileID>; WORLD_SIZE]; WORLD_SIZE],
 }
 
+/// Entities queued to be added or removed aren't saved, since the queues are emptied every frame.
+#[derive(Serialize, Deserialize)]
 pub struct State {
     pub rooms: Vec<Room>,
     pub player: bool,
@@ -139,7 +202,9 @@ pub struct State {
     pub links: BTreeMap<LinkID, (usize, IVec2)>,
     link_id_max: usize,
     tile_type_count: usize,
+    #[serde(skip)]
     add_queue: Vec<Ent>,
+    #[serde(skip)]
     remove_queue: Vec<EntID>,
 }
 
@@ -174,6 +239,7 @@ impl State {
     }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct Logics {
     pub control: KeyboardControl<ActionID, MacroquadInputWrapper>,
     pub collision: TileMapCollision<TileID, CollisionEnt>,
@@ -190,6 +256,10 @@ impl Logics {
             linking: {
                 let mut linking = GraphedLinking::new();
                 linking.add_graph(0, []);
+                // one node per link location, with only a few edges each
+                linking.graphs[0]
+                    .graph
+                    .set_storage(GraphStorage::AdjacencyList);
                 linking
             },
         }
@@ -199,89 +269,85 @@ impl Logics {
 pub async fn run(mut game: Game) {
     setup(&mut game);
 
+    let mut quicksave = None;
     loop {
+        if is_key_pressed(KeyCode::F5) {
+            quicksave = Some(game.save());
+        }
+        if is_key_pressed(KeyCode::F9) {
+            if let Some(save) = &quicksave {
+                game.load(save).expect("error loading quicksave");
+            }
+        }
         draw(&game);
+        game.update();
 
-        let add_queue = std::mem::take(&mut game.state.add_queue);
-        for ent in add_queue {
-            match ent {
-                Ent::TileID(tile, pos, room) => {
-                    game.add_tile_at_pos(tile, room, pos);
-                }
-                Ent::Character(character, room) => {
-                    game.add_character(character, room);
-                }
-            }
+        if is_key_down(KeyCode::Escape) {
+            return;
         }
+        next_frame().await;
+    }
+}
 
-        control(&mut game);
-        collision(&mut game);
-        resources(&mut game);
-        linking(&mut game);
+fn add_entities(game: &mut Game) {
+    let add_queue = std::mem::take(&mut game.state.add_queue);
+    for ent in add_queue {
+        match ent {
+            Ent::TileID(tile, pos, room) => {
+                game.add_tile_at_pos(tile, room, pos);
+            }
+            Ent::Character(character, room) => {
+                game.add_character(character, room);
+            }
+        }
+    }
+}
 
-        let remove_queue = std::mem::take(&mut game.state.remove_queue);
-        for ent in remove_queue {
-            match ent {
-                EntID::Player => {
-                    game.remove_player();
-                }
-                EntID::Tile(id) => {
-                    let mut remove = Vec::new();
-                    for (room_idx, room) in game.state.rooms.iter().enumerate() {
-                        for (y, row) in room.map.iter().enumerate() {
-                            for (x, tile) in row.iter().enumerate() {
-                                if let Some(tile) = tile {
-                                    if *tile == id {
-                                        remove.push((room_idx, IVec2::new(x as i32, y as i32)));
-                                    }
+fn remove_entities(game: &mut Game) {
+    let remove_queue = std::mem::take(&mut game.state.remove_queue);
+    for ent in remove_queue {
+        match ent {
+            EntID::Player => {
+                game.remove_player();
+            }
+            EntID::Tile(id) => {
+                let mut remove = Vec::new();
+                for (room_idx, room) in game.state.rooms.iter().enumerate() {
+                    for (y, row) in room.map.iter().enumerate() {
+                        for (x, tile) in row.iter().enumerate() {
+                            if let Some(tile) = tile {
+                                if *tile == id {
+                                    remove.push((room_idx, IVec2::new(x as i32, y as i32)));
                                 }
                             }
                         }
                     }
-                    for (i, pos) in remove {
-                        game.remove_tile_at_pos(i, pos);
-                    }
                 }
-                EntID::Character(id) => {
-                    game.remove_character(id);
+                for (i, pos) in remove {
+                    game.remove_tile_at_pos(i, pos);
                 }
             }
+            EntID::Character(id) => {
+                game.remove_character(id);
+            }
         }
-
-        if is_key_down(KeyCode::Escape) {
-            return;
-        }
-        next_frame().await;
     }
 }
 
 fn control(game: &mut Game) {
     game.logics.control.update(&());
     game.tables
-        .update_single::<CtrlEvent>(QueryType::ControlEvent, game.logics.control.get_table())
-        .unwrap();
+        .set(game.queries.control_event, game.logics.control.get_table());
+    game.tables.evaluate();
 
-    for (id, ctrl_event, reaction) in game.events.control.iter() {
-        let ans = game
-            .tables
-            .update_filter(QueryType::User(*id), |event: &CtrlEvent| {
-                event == ctrl_event
-            })
-            .unwrap();
-        for event in ans.iter() {
+    for (query, _, reaction) in game.events.control.iter() {
+        for event in game.tables.get(*query).iter() {
             reaction(&mut game.state, &mut game.logics, event);
         }
     }
 
-    let ans = game
-        .tables
-        .update_filter(QueryType::ControlFilter, |event: &CtrlEvent| {
-            event.event_type == ControlEventType::KeyPressed
-        })
-        .unwrap();
-
     // if all four direction keys are not being pressed, set vel = 0
-    if ans.is_empty() {
+    if game.tables.get(game.queries.control_filter).is_empty() {
         game.logics
             .collision
             .handle_predicate(&CollisionReaction::SetEntVel(0, IVec2::ZERO));
@@ -291,26 +357,22 @@ fn control(game: &mut Game) {
 fn collision(game: &mut Game) {
     game.logics.collision.update();
     game.tables
-        .update_single::<ColEvent>(QueryType::ContactOnly, game.logics.collision.get_table())
-        .unwrap();
-
-    // zip collision event + linking info
-    game.tables
-        .update_zip::<ColEvent, (usize, LinkID)>(QueryType::ContactRoom)
-        .unwrap();
+        .set(game.queries.contact_only, game.logics.collision.get_table());
+
+    // pair contacts with the current node of the room graph, then look up its room
+    game.tables.evaluate();
+    let links = &game.state.links;
+    game.tables.map(
+        game.queries.contact_room,
+        |(contact, (_, link)): &(ColEvent, (usize, LinkID))| (*contact, links[link].0),
+    );
 
-    for (id, (col_event, room_num), reaction) in game.events.collision.iter() {
+    for (query, event, reaction) in game.events.collision.iter() {
         let ans = game
             .tables
-            .update_filter(
-                QueryType::User(*id),
-                |(col, (room, _)): &(ColEvent, (usize, LinkID))| {
-                    col == col_event && room == room_num
-                },
-            )
-            .unwrap();
-        for (col_event, (room, _)) in ans.iter() {
-            reaction(&mut game.state, &mut game.logics, &(*col_event, *room));
+            .filter(*query, |contact: &(ColEvent, usize)| contact == event);
+        for contact in ans.iter() {
+            reaction(&mut game.state, &mut game.logics, contact);
         }
     }
 }
@@ -318,23 +380,19 @@ fn collision(game: &mut Game) {
 fn resources(game: &mut Game) {
     game.logics.resources.update();
 
-    game.tables
-        .update_single::<(RsrcID, (u16, u16, u16))>(
-            QueryType::ResourceIdent,
-            game.logics.resources.get_table(),
-        )
-        .unwrap();
-    game.tables
-        .update_single::<RsrcEvent>(QueryType::ResourceEvent, game.logics.resources.get_table())
-        .unwrap();
+    game.tables.set(
+        game.queries.resource_ident,
+        game.logics.resources.get_table(),
+    );
+    game.tables.set(
+        game.queries.resource_event,
+        game.logics.resources.get_table(),
+    );
 
-    for (id, event, reaction) in game.events.resource_event.iter() {
-        let ans = game
-            .tables
-            .update_filter(QueryType::User(*id), |rsrc: &RsrcEvent| rsrc == event)
-            .unwrap();
+    for (query, event, reaction) in game.events.resource_event.iter() {
+        let ans = game.tables.filter(*query, |rsrc: &RsrcEvent| rsrc == event);
         for event in ans.iter() {
-            reaction(&mut game.state, &mut game.logics, &event);
+            reaction(&mut game.state, &mut game.logics, event);
         }
     }
 }
@@ -343,21 +401,18 @@ fn linking(game: &mut Game) {
     game.logics.linking.update();
 
     game.tables
-        .update_single::<(usize, LinkID)>(QueryType::LinkingIdent, game.logics.linking.get_table())
-        .unwrap();
+        .set(game.queries.linking_ident, game.logics.linking.get_table());
 
     game.tables
-        .update_single::<LinkingEvent>(QueryType::LinkingEvent, game.logics.linking.get_table())
-        .unwrap();
+        .set(game.queries.linking_event, game.logics.linking.get_table());
 
     // only linking events
-    for (id, event, reaction) in game.events.linking.iter() {
+    for (query, event, reaction) in game.events.linking.iter() {
         let ans = game
             .tables
-            .update_filter(QueryType::User(*id), |link: &LinkingEvent| link == event)
-            .unwrap();
+            .filter(*query, |link: &LinkingEvent| link == event);
         for event in ans.iter() {
-            reaction(&mut game.state, &mut game.logics, &event);
+            reaction(&mut game.state, &mut game.logics, event);
         }
     }
 }
@@ -511,9 +566,4 @@ fn setup(game: &mut Game) {
             }),
         );
     }
-
-    game.tables.add_query::<CtrlEvent>(
-        QueryType::ControlFilter,
-        Some(Compose::Filter(QueryType::ControlEvent)),
-    );
 }
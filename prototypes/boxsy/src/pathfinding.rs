@@ -0,0 +1,202 @@
+//! Pathfinding over the tile map, for moving characters around.
+//!
+//! A tile is walkable if it's in bounds and either empty or not solid. Paths move one tile at a time in the four cardinal directions.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::fmt::Debug;
+
+use asterism::Logic;
+use macroquad::math::IVec2;
+
+use crate::{CollisionReaction, Logics, TileMapCollision};
+
+const DIRECTIONS: [IVec2; 4] = [
+    IVec2::new(0, -1),
+    IVec2::new(1, 0),
+    IVec2::new(0, 1),
+    IVec2::new(-1, 0),
+];
+
+/// distance to a goal from every walkable tile on the map, computed all at once so many characters can share it
+pub struct FlowField {
+    pub goal: IVec2,
+    /// number of steps to the goal, or `None` if the goal can't be reached from that tile
+    pub distances: Vec<Vec<Option<u32>>>,
+}
+
+impl FlowField {
+    pub fn distance(&self, pos: IVec2) -> Option<u32> {
+        if pos.x < 0 || pos.y < 0 {
+            return None;
+        }
+        *self.distances.get(pos.y as usize)?.get(pos.x as usize)?
+    }
+
+    /// the neighboring tile that gets closest to the goal, or `None` if there's nowhere closer to go
+    pub fn step_toward(&self, pos: IVec2) -> Option<IVec2> {
+        let here = self.distance(pos)?;
+        DIRECTIONS
+            .iter()
+            .map(|dir| pos + *dir)
+            .filter_map(|next| self.distance(next).map(|dist| (dist, next)))
+            .filter(|(dist, _)| *dist < here)
+            .min_by_key(|(dist, _)| *dist)
+            .map(|(_, next)| next)
+    }
+
+    /// the neighboring tile that gets furthest from the goal, or `None` if there's nowhere further to go
+    pub fn step_away(&self, pos: IVec2) -> Option<IVec2> {
+        let here = self.distance(pos)?;
+        DIRECTIONS
+            .iter()
+            .map(|dir| pos + *dir)
+            .filter_map(|next| self.distance(next).map(|dist| (dist, next)))
+            .filter(|(dist, _)| *dist > here)
+            // first direction wins ties
+            .fold(
+                None,
+                |best: Option<(u32, IVec2)>, (dist, next)| match best {
+                    Some((best_dist, _)) if best_dist >= dist => best,
+                    _ => Some((dist, next)),
+                },
+            )
+            .map(|(_, next)| next)
+    }
+}
+
+impl<TileID: Eq + Ord + Copy + Debug, EntID> TileMapCollision<TileID, EntID> {
+    /// Finds the shortest path between two tiles with A*. The path includes `to` but not `from`, so it's empty if they're the same tile. Returns `None` if there's no path.
+    pub fn find_path(&self, from: IVec2, to: IVec2) -> Option<Vec<IVec2>> {
+        if !self.walkable(from) || !self.walkable(to) {
+            return None;
+        }
+        if from == to {
+            return Some(Vec::new());
+        }
+        let width = self.map.iter().map(Vec::len).max().unwrap_or(0);
+        let idx = |pos: IVec2| pos.y as usize * width + pos.x as usize;
+        let heuristic = |pos: IVec2| ((pos.x - to.x).abs() + (pos.y - to.y).abs()) as u32;
+
+        let mut cost = vec![u32::MAX; width * self.map.len()];
+        let mut came_from: Vec<Option<IVec2>> = vec![None; width * self.map.len()];
+        let mut open = BinaryHeap::new();
+        cost[idx(from)] = 0;
+        open.push(Reverse((heuristic(from), 0, (from.y, from.x))));
+
+        while let Some(Reverse((_, steps, (y, x)))) = open.pop() {
+            let pos = IVec2::new(x, y);
+            if pos == to {
+                let mut path = vec![to];
+                let mut pos = to;
+                while let Some(prev) = came_from[idx(pos)] {
+                    if prev == from {
+                        break;
+                    }
+                    path.push(prev);
+                    pos = prev;
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if steps > cost[idx(pos)] {
+                continue;
+            }
+            for dir in DIRECTIONS.iter() {
+                let next = pos + *dir;
+                if !self.walkable(next) || steps + 1 >= cost[idx(next)] {
+                    continue;
+                }
+                cost[idx(next)] = steps + 1;
+                came_from[idx(next)] = Some(pos);
+                open.push(Reverse((
+                    steps + 1 + heuristic(next),
+                    steps + 1,
+                    (next.y, next.x),
+                )));
+            }
+        }
+        None
+    }
+
+    /// Computes the distance from every tile to `goal` with a breadth-first search. An empty map gives an empty field.
+    pub fn flow_field(&self, goal: IVec2) -> FlowField {
+        let mut distances: Vec<Vec<Option<u32>>> =
+            self.map.iter().map(|row| vec![None; row.len()]).collect();
+        let mut queue = VecDeque::new();
+        if self.walkable(goal) {
+            distances[goal.y as usize][goal.x as usize] = Some(0);
+            queue.push_back(goal);
+        }
+
+        while let Some(pos) = queue.pop_front() {
+            let dist = distances[pos.y as usize][pos.x as usize].unwrap();
+            for dir in DIRECTIONS.iter() {
+                let next = pos + *dir;
+                if self.walkable(next) && distances[next.y as usize][next.x as usize].is_none() {
+                    distances[next.y as usize][next.x as usize] = Some(dist + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        FlowField { goal, distances }
+    }
+
+    /// if a solid entity other than `ent` is standing on this tile
+    fn occupied(&self, ent: usize, pos: IVec2) -> bool {
+        self.positions
+            .iter()
+            .zip(self.metadata.iter())
+            .enumerate()
+            .any(|(i, (ent_pos, meta))| i != ent && meta.solid && *ent_pos == pos)
+    }
+}
+
+impl Logics {
+    /// Moves a collision entity one tile along the shortest path to `goal`. Entities stop next to other solid entities instead of walking onto them, so a character following the player stops once it's beside them.
+    ///
+    /// Returns if the entity moved.
+    pub fn step_toward(&mut self, ent: usize, goal: IVec2) -> bool {
+        let pos = self.collision.positions[ent];
+        let next = match self.collision.find_path(pos, goal) {
+            Some(path) if !path.is_empty() => path[0],
+            _ => return false,
+        };
+        self.step_to(ent, next)
+    }
+
+    /// Moves a collision entity one tile further away from `threat`, if it can.
+    ///
+    /// Returns if the entity moved.
+    pub fn step_away(&mut self, ent: usize, threat: IVec2) -> bool {
+        let pos = self.collision.positions[ent];
+        match self.collision.flow_field(threat).step_away(pos) {
+            Some(next) => self.step_to(ent, next),
+            None => false,
+        }
+    }
+
+    /// Moves a collision entity one tile along a flow field.
+    ///
+    /// Returns if the entity moved.
+    pub fn step_along(&mut self, ent: usize, field: &FlowField) -> bool {
+        let pos = self.collision.positions[ent];
+        match field.step_toward(pos) {
+            Some(next) => self.step_to(ent, next),
+            None => false,
+        }
+    }
+
+    fn step_to(&mut self, ent: usize, next: IVec2) -> bool {
+        if self.collision.occupied(ent, next) {
+            return false;
+        }
+        let pos = self.collision.positions[ent];
+        self.collision
+            .handle_predicate(&CollisionReaction::SetEntVel(ent, next - pos));
+        self.collision
+            .handle_predicate(&CollisionReaction::SetEntPos(ent, next));
+        true
+    }
+}
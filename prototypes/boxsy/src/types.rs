@@ -1,11 +1,12 @@
 use macroquad::{color::*, input::KeyCode, math::IVec2};
+use serde::{Deserialize, Serialize};
 
 /// generates identifier structs (i got tired of typing all of them out)
 macro_rules! id_impl_new {
     ($([$($derive:meta)*] $id_type:ident),*) => {
         $(
             $(#[$derive])*
-            #[derive(Clone, Copy, PartialEq, Eq, Debug)]
+            #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
             pub struct $id_type(usize);
 
             impl $id_type {
@@ -21,11 +22,12 @@ macro_rules! id_impl_new {
     };
 }
 
-id_impl_new!([derive(Hash, Ord, PartialOrd)] TileID, [derive(Hash, Ord, PartialOrd)] CharacterID, [derive(Hash, Ord, PartialOrd)] RsrcID, [derive(Ord, PartialOrd)] LinkID, [derive(Hash)] UserQueryID);
+id_impl_new!([derive(Hash, Ord, PartialOrd)] TileID, [derive(Hash, Ord, PartialOrd)] CharacterID, [derive(Hash, Ord, PartialOrd)] RsrcID, [derive(Hash, Ord, PartialOrd)] LinkID, [derive(Hash)] UserQueryID);
 
 #[derive(Hash, PartialEq, Eq, Clone, Copy, Debug)]
 pub(crate) enum QueryType {
     ContactOnly,
+    ContactLink,
     ContactRoom,
     LinkingEvent,
     LinkingIdent,
@@ -37,6 +39,21 @@ pub(crate) enum QueryType {
     User(UserQueryID),
 }
 
+pub(crate) type Query<T, Src = ()> = asterism::tables::QueryHandle<QueryType, T, Src>;
+
+/// handles to the rows of the condition table that every game has
+pub(crate) struct Queries {
+    pub contact_only: Query<ColEvent>,
+    /// contacts and the room they happened in
+    pub contact_room: Query<(ColEvent, usize), (ColEvent, (usize, LinkID))>,
+    pub linking_event: Query<LinkingEvent>,
+    pub linking_ident: Query<(usize, LinkID)>,
+    pub control_event: Query<CtrlEvent>,
+    pub control_filter: Query<CtrlEvent>,
+    pub resource_event: Query<RsrcEvent>,
+    pub resource_ident: Query<(RsrcID, (u16, u16, u16))>,
+}
+
 pub enum Ent {
     /// tile id of tile to add, position, room
     TileID(TileID, IVec2, usize),
@@ -44,14 +61,14 @@ pub enum Ent {
 }
 
 // the stonks meme but it says derive
-#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug)]
+#[derive(PartialOrd, Ord, PartialEq, Eq, Hash, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum EntID {
     Player,
     Tile(TileID),
     Character(CharacterID),
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum ActionID {
     Left,
     Right,
@@ -157,9 +174,10 @@ impl Resource {
 
 use crate::collision::Contact;
 use asterism::control::ControlEvent;
+use asterism::linking::LinkingEvent;
 use asterism::resources::ResourceEvent;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum CollisionEnt {
     Player,
     Character,
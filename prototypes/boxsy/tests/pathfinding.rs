@@ -0,0 +1,39 @@
+//! Paths go around solid tiles, and maps with no tiles at all just have nowhere to go.
+use bitsy::TileMapCollision;
+use macroquad::math::IVec2;
+
+#[test]
+fn paths_go_around_walls() {
+    let mut collision: TileMapCollision<u8, ()> = TileMapCollision::new(5, 5);
+    collision.tile_solid.insert(1, true);
+    for row in collision.map.iter_mut().take(4) {
+        row[2] = Some(1);
+    }
+    let path = collision
+        .find_path(IVec2::new(0, 0), IVec2::new(4, 0))
+        .unwrap();
+    assert_eq!(path.len(), 12);
+    assert_eq!(path.last(), Some(&IVec2::new(4, 0)));
+    assert_eq!(
+        collision.find_path(IVec2::new(0, 0), IVec2::new(2, 0)),
+        None
+    );
+
+    let field = collision.flow_field(IVec2::new(4, 0));
+    assert_eq!(field.distance(IVec2::new(0, 0)), Some(12));
+    assert_eq!(field.step_toward(IVec2::new(0, 0)), Some(IVec2::new(1, 0)));
+    assert_eq!(field.distance(IVec2::new(2, 0)), None);
+}
+
+#[test]
+fn empty_maps_have_no_paths() {
+    let collision: TileMapCollision<u8, ()> = TileMapCollision::new(0, 0);
+    assert!(collision.map.is_empty());
+    assert_eq!(
+        collision.find_path(IVec2::new(0, 0), IVec2::new(0, 0)),
+        None
+    );
+    let field = collision.flow_field(IVec2::new(0, 0));
+    assert!(field.distances.is_empty());
+    assert_eq!(field.step_toward(IVec2::new(0, 0)), None);
+}
@@ -0,0 +1,97 @@
+//! Loading a save has to give back the same rooms, links, current room, and inventory, so saving it again gives the same save.
+use bitsy::*;
+use macroquad::math::IVec2;
+
+const ROOM: &str = "00000000
+0      0
+0      0
+0      0
+0      0
+0      0
+0      0
+00000000";
+
+/// two walled rooms linked both ways, a character in the second room, and a player carrying some rocks
+fn new_game() -> (Game, RsrcID) {
+    let mut game = Game::new();
+    let mut wall = Tile::new();
+    wall.solid = true;
+    game.log_tile_info(wall);
+    game.add_room_from_str(ROOM).unwrap();
+    game.add_room_from_str(ROOM).unwrap();
+
+    let rocks = game.log_rsrc();
+    let mut player = Player::new();
+    player.pos = IVec2::new(3, 3);
+    let mut resource = Resource::new();
+    resource.val = 4;
+    player.add_inventory_item(rocks, resource);
+    game.set_player(player);
+
+    let mut character = Character::new();
+    character.pos = IVec2::new(5, 5);
+    game.add_character(character, 1);
+
+    game.add_link((0, IVec2::new(1, 1)), (1, IVec2::new(2, 2)));
+    game.add_link((1, IVec2::new(6, 6)), (0, IVec2::new(3, 3)));
+    (game, rocks)
+}
+
+/// moves from the first room to the second the way walking onto the link does
+fn traverse_link(game: &mut Game) {
+    let graph = &game.logics.linking.graphs[0].graph;
+    let from = graph.node_idx(&LinkID::new(0)).unwrap();
+    let to = graph.node_idx(&LinkID::new(1)).unwrap();
+    game.logics
+        .linking
+        .handle_predicate(&LinkingReaction::Traverse(0, from));
+    game.logics
+        .linking
+        .handle_predicate(&LinkingReaction::Activate(0, to));
+    game.logics.linking.update();
+    set_current_room(&mut game.state, &mut game.logics, 0, 1);
+}
+
+#[test]
+fn saves_round_trip() {
+    let (mut game, rocks) = new_game();
+    traverse_link(&mut game);
+    game.logics
+        .resources
+        .handle_predicate(&(rocks, Transaction::Change(3)));
+    game.logics.resources.update();
+    assert_eq!(game.get_current_room(), 1);
+    let save = game.save();
+
+    // colors and rules are set up in code, so saves are loaded into a game that's already been set up
+    let (mut loaded, _) = new_game();
+    assert_ne!(loaded.save(), save);
+    loaded.load(&save).unwrap();
+    assert_eq!(loaded.save(), save);
+
+    assert_eq!(loaded.get_current_room(), 1);
+    assert_eq!(loaded.state.rooms.len(), 2);
+    assert_eq!(loaded.state.rooms[1].chars, game.state.rooms[1].chars);
+    assert_eq!(loaded.state.links, game.state.links);
+    assert_eq!(
+        loaded.logics.linking.graphs[0].graph.nodes(),
+        game.logics.linking.graphs[0].graph.nodes()
+    );
+    assert_eq!(
+        loaded.logics.resources.get_value_by_itemtype(&rocks),
+        Some(7)
+    );
+    assert_eq!(
+        loaded.logics.collision.positions,
+        game.logics.collision.positions
+    );
+}
+
+#[test]
+fn rejects_malformed_saves() {
+    let (mut game, _) = new_game();
+    let save = game.save();
+    assert!(game.load("{}").is_err());
+    assert!(game.load(&save[..save.len() / 2]).is_err());
+    assert_eq!(game.save(), save);
+}
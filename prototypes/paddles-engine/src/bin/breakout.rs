@@ -26,7 +26,7 @@ async fn main() {
     run(game).await;
 }
 
-fn init(game: &mut Game) {
+pub fn init(game: &mut Game) {
     // ball
     let mut ball = Ball::new();
     ball.set_pos(Vec2::new(
@@ -82,6 +82,10 @@ fn init(game: &mut Game) {
     game.add_paddle(paddle);
 
     let score = game.add_score(Score::new());
+    let all_blocks_broken =
+        game.logics
+            .resources
+            .add_threshold(RsrcPool::Score(score), 40, ThresholdKind::CrossedUp);
 
     let reset_game = move |state: &mut State, logics: &mut Logics| {
         let blocks = state.walls()[4..].to_vec();
@@ -155,7 +159,7 @@ fn init(game: &mut Game) {
         control: [
             {
                 filter move_paddle,
-                QueryType::CtrlEvent => CtrlEvent,
+                game.queries.ctrl_event => CtrlEvent,
                 |ctrl, _, _| {
                     ctrl.event_type == ControlEventType::KeyHeld && ctrl.action_id != action_serve
                 },
@@ -173,7 +177,7 @@ fn init(game: &mut Game) {
             },
             {
                 filter serve,
-                QueryType::CtrlEvent => CtrlEvent,
+                game.queries.ctrl_event => CtrlEvent,
                 |ctrl, _, _| {
                     ctrl.event_type == ControlEventType::KeyPressed && ctrl.action_id == action_serve
                 },
@@ -193,7 +197,7 @@ fn init(game: &mut Game) {
         collision: [
             {
                 filter bounce,
-                QueryType::ColEvent => ColEvent,
+                game.queries.col_event => ColEvent,
                 |(i, j), _, logics| {
                     let i_id = logics.collision.metadata[*i].id;
                     let j_id = logics.collision.metadata[*j].id;
@@ -206,7 +210,7 @@ fn init(game: &mut Game) {
             },
             {
                 filter reset_lose,
-                QueryType::ColEvent => ColEvent,
+                game.queries.col_event => ColEvent,
                 |(i, j), state, logics| {
                     let i_id = logics.collision.metadata[*i].id;
                     i_id == CollisionEnt::Ball &&
@@ -221,9 +225,9 @@ fn init(game: &mut Game) {
         resources: [
             {
                 filter reset_win,
-                QueryType::RsrcIdent => RsrcIdent,
-                |(_, (val, ..)), _, _| {
-                    *val >= 40
+                game.queries.rsrc_event => RsrcEvent,
+                |event, _, _| {
+                    event.event_type == ResourceEventType::ThresholdCrossedUp(all_blocks_broken)
                 },
                 foreach |_, state, logics| {
                     reset_game(state, logics);
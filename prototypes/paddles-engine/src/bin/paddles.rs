@@ -41,12 +41,12 @@ fn init(game: &mut Game) {
     let mut wall = Wall::new();
     wall.set_pos(Vec2::new(-1.0, 0.0));
     wall.set_size(Vec2::new(1.0, HEIGHT as f32));
-    let left_wall = game.add_wall(wall);
+    game.add_wall(wall);
     // right
     let mut wall = Wall::new();
     wall.set_pos(Vec2::new(WIDTH as f32, 0.0));
     wall.set_size(Vec2::new(1.0, HEIGHT as f32));
-    let right_wall = game.add_wall(wall);
+    game.add_wall(wall);
     // top
     let mut wall = Wall::new();
     wall.set_pos(Vec2::new(0.0, -1.0));
@@ -58,7 +58,7 @@ fn init(game: &mut Game) {
     wall.set_size(Vec2::new(WIDTH as f32, 1.0));
     game.add_wall(wall);
 
-    // paddle 1
+    // paddle 1. each paddle's third action serves, which interactions/paddles.json refers to as `"action": 2`, so the order of the control maps matters
     let mut paddle1 = Paddle::new();
     let action_q = paddle1.add_control_map(KeyCode::Q, true);
     let action_a = paddle1.add_control_map(KeyCode::A, true);
@@ -83,8 +83,53 @@ fn init(game: &mut Game) {
 
     game.add_paddle(paddle2);
 
-    let score1 = game.add_score(Score::new());
-    let score2 = game.add_score(Score::new());
+    // with `--cpu`, the computer plays paddle 2: it serves when it can and follows the ball otherwise
+    if std::env::args().any(|arg| arg == "--cpu") {
+        let paddles = game.queries.col_ident;
+        let balls = game.queries.phys_ident;
+        let controls = game.queries.ctrl_ident;
+        let mut follow_ball = FollowTarget::new(
+            Box::new(move |tables: &ConditionTables<QueryType>| {
+                tables.get(paddles).get(1).map(|(_, paddle)| paddle.center)
+            }),
+            Box::new(move |tables: &ConditionTables<QueryType>| {
+                tables
+                    .get(balls)
+                    .first()
+                    .map(|(_, ball)| ball.pos + Vec2::splat(BALL_SIZE as f32 / 2.0))
+            }),
+        );
+        follow_ball.up = Some(action_o);
+        follow_ball.down = Some(action_l);
+        follow_ball.dead_zone = PADDLE_HEIGHT as f32 / 4.0;
+        let can_serve = move |tables: &ConditionTables<QueryType>| {
+            tables
+                .get(controls)
+                .get(1)
+                .into_iter()
+                .flat_map(|(_, actions)| actions)
+                .any(|action| action.id == action_i && action.is_valid)
+        };
+        game.ai.control_set(
+            1,
+            BehaviorTree::Selector(vec![
+                BehaviorTree::Sequence(vec![
+                    BehaviorTree::Condition(Box::new(can_serve)),
+                    BehaviorTree::Hold(vec![action_i]),
+                ]),
+                BehaviorTree::Policy(Box::new(follow_ball)),
+            ]),
+        );
+    }
+
+    game.add_score(Score::new());
+    game.add_score(Score::new());
+
+    // scoring is data-driven: a ball hitting the left or right wall gives the other player a point and lets them serve
+    debug_assert_eq!((action_w, action_i), (ActionID::new(2), ActionID::new(2)));
+    game.interactions =
+        InteractionTable::from_json(include_str!("../../interactions/paddles.json"))
+            .expect("couldn't parse interactions");
 
     // expands to a match statement mapping the values 0, 1 to the two inputs given
     //
@@ -135,20 +180,6 @@ fn init(game: &mut Game) {
             .handle_predicate(&ControlReaction::SetKeyInvalid(set, action_id));
     };
 
-    // increase score on collision with side wall
-    let inc_score = move |logics: &mut Logics, set: usize| {
-        logics
-            .control
-            .handle_predicate(&ControlReaction::SetKeyValid(
-                set,
-                match_set!(set, action_w, action_i),
-            ));
-        logics.resources.handle_predicate(&(
-            RsrcPool::Score(match_set!(set, score1, score2)),
-            Transaction::Change(1),
-        ));
-    };
-
     let bounce_ball = |(i, j): &ColEvent, state: &mut State, logics: &mut Logics| {
         let id = state.get_id(*i);
         if let EntID::Ball(ball_id) = id {
@@ -167,14 +198,13 @@ fn init(game: &mut Game) {
     let move_paddle = QueryType::User(game.add_query());
     let serve = QueryType::User(game.add_query());
     let bounce = QueryType::User(game.add_query());
-    let score = QueryType::User(game.add_query());
     let score_increased = QueryType::User(game.add_query());
 
     paddles_engine::rules!(game =>
         control: [
             {
                 filter move_paddle,
-                QueryType::CtrlEvent => CtrlEvent,
+                game.queries.ctrl_event => CtrlEvent,
                 |ctrl, _, _| {
                     ctrl.event_type == ControlEventType::KeyHeld
                 },
@@ -188,7 +218,7 @@ fn init(game: &mut Game) {
             },
             {
                 filter serve,
-                QueryType::CtrlEvent => CtrlEvent,
+                game.queries.ctrl_event => CtrlEvent,
                 |ctrl, _, _| {
                     ctrl.event_type == ControlEventType::KeyPressed && (ctrl.action_id == action_w || ctrl.action_id == action_i)
                 },
@@ -203,7 +233,7 @@ fn init(game: &mut Game) {
         collision: [
             {
                 filter bounce,
-                QueryType::ColEvent => ColEvent,
+                game.queries.col_event => ColEvent,
                 |(i, j), _, logics| {
                     let i_id = logics.collision.metadata[*i].id;
                     let j_id = logics.collision.metadata[*j].id;
@@ -213,31 +243,13 @@ fn init(game: &mut Game) {
                 foreach |col, state, logics| {
                     bounce_ball(col, state, logics);
                 }
-            },
-            {
-                filter score,
-                QueryType::ColEvent => ColEvent,
-                |(i, j), state, logics| {
-                    let i_id = logics.collision.metadata[*i].id;
-                    i_id == CollisionEnt::Ball &&
-                        (*j == state.get_col_idx(left_wall.idx(), CollisionEnt::Wall) || *j == state.get_col_idx(right_wall.idx(), CollisionEnt::Wall))
-                },
-                foreach |(_, j), state, logics| {
-                    if *j == state.get_col_idx(left_wall.idx(), CollisionEnt::Wall) {
-                        inc_score(logics, 1);
-                    } else if *j == state.get_col_idx(right_wall.idx(), CollisionEnt::Wall) {
-                        inc_score(logics, 0);
-                    } else {
-                        unreachable!();
-                    }
-                }
             }
         ]
 
         resources: [
             {
                 filter score_increased,
-                QueryType::RsrcEvent => RsrcEvent,
+                game.queries.rsrc_event => RsrcEvent,
                 |pool, _, _| {
                     pool.event_type == ResourceEventType::PoolUpdated
                 },
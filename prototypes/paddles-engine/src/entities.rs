@@ -61,11 +61,11 @@ impl Game {
             .position(|pid| *pid == paddle)
             .unwrap();
         self.logics.control.mapping.remove(ent_i);
+        let col_idx = self.state.get_col_idx(ent_i, CollisionEnt::Paddle);
         self.logics
             .collision
-            .handle_predicate(&CollisionReaction::RemoveBody(
-                self.state.get_col_idx(ent_i, CollisionEnt::Paddle),
-            ));
+            .handle_predicate(&CollisionReaction::RemoveBody(col_idx));
+        self.logics.bindings.collision_removed(col_idx);
 
         self.state.paddles.remove(ent_i);
     }
@@ -77,11 +77,11 @@ impl Game {
             .iter()
             .position(|wid| *wid == wall)
             .unwrap();
+        let col_idx = self.state.get_col_idx(ent_i, CollisionEnt::Wall);
         self.logics
             .collision
-            .handle_predicate(&CollisionReaction::RemoveBody(
-                self.state.get_col_idx(ent_i, CollisionEnt::Wall),
-            ));
+            .handle_predicate(&CollisionReaction::RemoveBody(col_idx));
+        self.logics.bindings.collision_removed(col_idx);
 
         self.state.walls.remove(ent_i);
     }
@@ -93,14 +93,15 @@ impl Game {
             .iter()
             .position(|bid| *bid == ball)
             .unwrap();
+        let col_idx = self.state.get_col_idx(ent_i, CollisionEnt::Ball);
         self.logics
             .physics
             .handle_predicate(&PhysicsReaction::RemoveBody(ent_i));
         self.logics
             .collision
-            .handle_predicate(&CollisionReaction::RemoveBody(
-                self.state.get_col_idx(ent_i, CollisionEnt::Ball),
-            ));
+            .handle_predicate(&CollisionReaction::RemoveBody(col_idx));
+        self.logics.bindings.physics_removed(ent_i);
+        self.logics.bindings.collision_removed(col_idx);
 
         self.state.balls.remove(ent_i);
     }
@@ -126,6 +127,7 @@ impl Logics {
         self.collision.centers.insert(col_idx, center);
         self.collision.half_sizes.insert(col_idx, hs);
         self.collision.velocities.insert(col_idx, Vec2::ZERO);
+        self.bindings.collision_inserted(col_idx);
 
         use asterism::collision::CollisionData;
         self.collision.metadata.insert(
@@ -148,6 +150,7 @@ impl Logics {
         self.collision.centers.insert(col_idx, center);
         self.collision.half_sizes.insert(col_idx, hs);
         self.collision.velocities.insert(col_idx, Vec2::ZERO);
+        self.bindings.collision_inserted(col_idx);
 
         use asterism::collision::CollisionData;
         self.collision.metadata.insert(
@@ -161,6 +164,7 @@ impl Logics {
     }
 
     pub fn consume_ball(&mut self, col_idx: usize, ball: Ball) {
+        let phys_idx = self.physics.positions.len();
         self.physics
             .add_physics_entity(ball.pos, ball.vel, Vec2::ZERO);
         let hs = ball.size / 2.0;
@@ -178,6 +182,8 @@ impl Logics {
                 id: CollisionEnt::Ball,
             },
         );
+        self.bindings.collision_inserted(col_idx);
+        self.bindings.bind(phys_idx, col_idx);
     }
 
     pub fn consume_score(&mut self, id: ScoreID, score: Score) {
@@ -31,65 +31,44 @@ impl Events {
 ///
 /// Note that it's impossible to add events while the game is running. This is a restriction of how the macro works (with closures rather than from a data structure or JSON file or something).
 ///
-/// @setup rules are used during initialization to add a row of query outputs to the table. @run rules are used in the game loop to update those table rows. @then rules execute a piece of code according to the table output (as described above).
+/// The rows being filtered or zipped are given as [QueryHandle][asterism::tables::QueryHandle]s (ex. `game.queries.ctrl_event`), so the type written after `=>` is checked against the row at compile time.
+///
+/// @setup rules are used during initialization to add a row of query outputs to the table, returning a handle to it. @run rules take that handle and make the closure that updates the row in the game loop. @then rules execute a piece of code according to the table output (as described above).
 macro_rules! rules {
-    (@setup filter $id:expr, $filter:expr => $filter_type:ty, |$_filter_pat:pat, $logic:pat, $state:pat| $_predicate:block $(, $($_then:tt)*)?) => {
-        |game: &mut $crate::Game| {
-            game.tables.add_query::<$filter_type>(
-                $id,
-                Some($crate::Compose::Filter($filter)),
-            );
-        }
+    (@setup [$game:ident] filter $id:expr, $filter:expr => $filter_type:ty, |$_filter_pat:pat, $logic:pat, $state:pat| $_predicate:block $(, $($_then:tt)*)?) => {
+        $game.tables.add_filter::<$filter_type, _>($id, $filter)
     };
-    (@setup zip $id:expr, ($zip1:expr => $zip_ty1:ty, $zip2:expr => $zip_ty2:ty) $(, $($_then:tt)*)?) => {
-        |game: &mut $crate::Game| {
-            game.tables.add_query::<($zip_ty1, $zip_ty2)>(
-                $id,
-                Some($crate::Compose::Zip(
-                    $zip1,
-                    $zip2,
-                )),
-            );
-        }
+    (@setup [$game:ident] zip $id:expr, ($zip1:expr => $zip_ty1:ty, $zip2:expr => $zip_ty2:ty) $(, $($_then:tt)*)?) => {
+        $game.tables.add_zip::<$zip_ty1, $zip_ty2, _, _>($id, $zip1, $zip2)
     };
 
-    (@run filter $id:expr, $filter:expr => $filter_type:ty, |$filter_pat:pat, $state:pat, $logics:pat| $predicate:block) => {
-        |game: &mut Game| {
+    (@run [$query:ident] filter $id:expr, $filter:expr => $filter_type:ty, |$filter_pat:pat, $state:pat, $logics:pat| $predicate:block) => {
+        move |game: &mut $crate::Game| {
             let $logics = &game.logics;
             let $state = &game.state;
-            game
-                .tables
-                .update_filter($id, |$filter_pat: &$filter_type| $predicate)
-                .unwrap();
-            }
+            game.tables.filter($query, |$filter_pat: &$filter_type| $predicate);
+        }
     };
-    (@run filter $id:expr, $filter:expr => $filter_type:ty, |$filter_pat:pat, $state:pat, $logics:pat| $predicate:block, $($then:tt)*) => {
-        |game: &mut Game| {
+    (@run [$query:ident] filter $id:expr, $filter:expr => $filter_type:ty, |$filter_pat:pat, $state:pat, $logics:pat| $predicate:block, $($then:tt)*) => {
+        move |game: &mut $crate::Game| {
             let ans = {
                 let $state = &game.state;
                 let $logics = &game.logics;
-                game
-                .tables
-                .update_filter($id, |$filter_pat: &$filter_type| $predicate)
-                .unwrap()
+                game.tables.filter($query, |$filter_pat: &$filter_type| $predicate)
             };
             $crate::rules!(@then [ans] [game] [&$filter_type], $($then)*);
         }
     };
 
-    (@run zip $id:expr, ($zip1:expr => $zip_ty1:ty, $zip2:expr => $zip_ty2:ty)) => {
-        |game: &mut Game| {
-        game.tables
-            .update_zip::<$zip_ty1, $zip_ty2>($id)
-            .unwrap();
+    (@run [$query:ident] zip $id:expr, ($zip1:expr => $zip_ty1:ty, $zip2:expr => $zip_ty2:ty)) => {
+        move |game: &mut $crate::Game| {
+            game.tables.zip($query);
         }
     };
 
-    (@run zip $id:expr, ($_zip1:expr => $zip_ty1:ty, $_zip2:expr => $zip_ty2:ty), $($then:tt)*) => {
-        |game: &mut Game| {
-            let ans = game.tables
-                .update_zip::<$zip_ty1, $zip_ty2>($id)
-                .unwrap();
+    (@run [$query:ident] zip $id:expr, ($_zip1:expr => $zip_ty1:ty, $_zip2:expr => $zip_ty2:ty), $($then:tt)*) => {
+        move |game: &mut $crate::Game| {
+            let ans = game.tables.zip($query);
             $crate::rules!(@then [ans] [game] [&($zip_ty1, $zip_ty2)], $($then)*);
         }
     };
@@ -124,56 +103,38 @@ macro_rules! rules {
     ) => {
         {
             use std::rc::Rc;
-            trait PaddlesUserEvents: $crate::PaddlesGame {
-                fn setup(&mut self, setup: Rc<dyn Fn(&mut Self)>);
-                fn control(&mut self, control: Rc<dyn Fn(&mut Self)>);
-                fn collision(&mut self, collision: Rc<dyn Fn(&mut Self)>);
-                fn resources(&mut self, resources: Rc<dyn Fn(&mut Self)>);
-                fn physics(&mut self, physics: Rc<dyn Fn(&mut Self)>);
-            }
-            impl PaddlesUserEvents for $crate::Game {
-                fn setup(&mut self, setup: Rc<dyn Fn(&mut $crate::Game)>) {
-                    setup(self);
-                }
-                fn control(&mut self, control: Rc<dyn Fn(&mut Self)>) {
-                    self.events.control = Some(control);
-                }
-                fn collision(&mut self, collision: Rc<dyn Fn(&mut Self)>) {
-                    self.events.collision = Some(collision);
-                }
-                fn resources(&mut self, resources: Rc<dyn Fn(&mut Self)>) {
-                    self.events.resources = Some(resources);
-            }
-                fn physics(&mut self, physics: Rc<dyn Fn(&mut Self)>) {
-                    self.events.physics = Some(physics);
-                }
-            }
-
-            let setup = Rc::new(move |game: &mut $crate::Game| {
-                $($crate::rules!(@setup $($ctrl_rule)+)(game);)*
-                $($crate::rules!(@setup $($phys_rule)+)(game);)*
-                $($crate::rules!(@setup $($col_rule)+)(game);)*
-                $($crate::rules!(@setup $($rsrc_rule)+)(game);)*
-            });
-            $game.setup(setup);
+            type Rule = Box<dyn Fn(&mut $crate::Game)>;
 
-            let control = Rc::new(move |game: &mut $crate::Game| {
-                $($crate::rules!(@run $($ctrl_rule)+)(game);)*
-            });
-            let physics = Rc::new(move |game: &mut $crate::Game| {
-                $($crate::rules!(@run $($phys_rule)+)(game);)*
-            });
-            let collision = Rc::new(move |game: &mut $crate::Game| {
-                $($crate::rules!(@run $($col_rule)+)(game);)*
-            });
-            let resources = Rc::new(move |game: &mut $crate::Game| {
-                $($crate::rules!(@run $($rsrc_rule)+)(game);)*
-            });
+            // rows are added in the order the rules are written
+            let control: Vec<Rule> = vec![$({
+                let query = $crate::rules!(@setup [$game] $($ctrl_rule)+);
+                Box::new($crate::rules!(@run [query] $($ctrl_rule)+))
+            }),*];
+            let physics: Vec<Rule> = vec![$({
+                let query = $crate::rules!(@setup [$game] $($phys_rule)+);
+                Box::new($crate::rules!(@run [query] $($phys_rule)+))
+            }),*];
+            let collision: Vec<Rule> = vec![$({
+                let query = $crate::rules!(@setup [$game] $($col_rule)+);
+                Box::new($crate::rules!(@run [query] $($col_rule)+))
+            }),*];
+            let resources: Vec<Rule> = vec![$({
+                let query = $crate::rules!(@setup [$game] $($rsrc_rule)+);
+                Box::new($crate::rules!(@run [query] $($rsrc_rule)+))
+            }),*];
 
-            $game.control(control);
-            $game.physics(physics);
-            $game.collision(collision);
-            $game.resources(resources);
+            $game.events.control = Some(Rc::new(move |game: &mut $crate::Game| {
+                control.iter().for_each(|rule| rule(game));
+            }));
+            $game.events.physics = Some(Rc::new(move |game: &mut $crate::Game| {
+                physics.iter().for_each(|rule| rule(game));
+            }));
+            $game.events.collision = Some(Rc::new(move |game: &mut $crate::Game| {
+                collision.iter().for_each(|rule| rule(game));
+            }));
+            $game.events.resources = Some(Rc::new(move |game: &mut $crate::Game| {
+                resources.iter().for_each(|rule| rule(game));
+            }));
         }
     };
 }
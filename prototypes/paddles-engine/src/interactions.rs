@@ -0,0 +1,83 @@
+//! data-driven rules, for games that want to change what happens without recompiling. see [asterism::interactions].
+use asterism::control::ControlReaction;
+use asterism::physics::PhysicsReaction;
+use asterism::resources::Transaction;
+use asterism::Logic;
+use macroquad::math::Vec2;
+use serde::{Deserialize, Serialize};
+
+use crate::types::*;
+use crate::{EntID, Game};
+
+pub use asterism::interactions::{Interaction, InteractionTable};
+
+pub type PaddlesInteractions = InteractionTable<PaddlesEvent, PaddlesReaction>;
+
+/// events that can trigger an interaction
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum PaddlesEvent {
+    Control(CtrlEvent),
+    /// two entities touched, the first one moving into the second
+    Collision(EntID, EntID),
+    Resource(RsrcEvent),
+}
+
+/// reactions an interaction can perform
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum PaddlesReaction {
+    SetKeyValid { set: usize, action: ActionID },
+    SetKeyInvalid { set: usize, action: ActionID },
+    Resource(RsrcPool, Transaction<u16>),
+    SetBallVel { ball: BallID, vel: (f32, f32) },
+    SetBallPos { ball: BallID, pos: (f32, f32) },
+    Remove(EntID),
+}
+
+impl Game {
+    /// performs the reactions of every interaction matching the given events
+    pub(crate) fn interact(&mut self, events: Vec<PaddlesEvent>) {
+        let reactions: Vec<PaddlesReaction> = self
+            .interactions
+            .dispatch(events)
+            .into_iter()
+            .map(|(_, reaction)| *reaction)
+            .collect();
+        for reaction in reactions {
+            self.react(reaction);
+        }
+    }
+
+    fn react(&mut self, reaction: PaddlesReaction) {
+        let logics = &mut self.logics;
+        match reaction {
+            PaddlesReaction::SetKeyValid { set, action } => {
+                logics
+                    .control
+                    .handle_predicate(&ControlReaction::SetKeyValid(set, action));
+            }
+            PaddlesReaction::SetKeyInvalid { set, action } => {
+                logics
+                    .control
+                    .handle_predicate(&ControlReaction::SetKeyInvalid(set, action));
+            }
+            PaddlesReaction::Resource(pool, transaction) => {
+                logics.resources.handle_predicate(&(pool, transaction));
+            }
+            PaddlesReaction::SetBallVel { ball, vel } => {
+                if let Some(idx) = self.state.balls.iter().position(|id| *id == ball) {
+                    logics
+                        .physics
+                        .handle_predicate(&PhysicsReaction::SetVel(idx, Vec2::new(vel.0, vel.1)));
+                }
+            }
+            PaddlesReaction::SetBallPos { ball, pos } => {
+                if let Some(idx) = self.state.balls.iter().position(|id| *id == ball) {
+                    logics
+                        .physics
+                        .handle_predicate(&PhysicsReaction::SetPos(idx, Vec2::new(pos.0, pos.1)));
+                }
+            }
+            PaddlesReaction::Remove(ent) => self.state.queue_remove(ent),
+        }
+    }
+}
@@ -2,32 +2,45 @@
 #![allow(clippy::upper_case_acronyms)]
 
 use asterism::{
+    bindings::{Anchor, Bindings},
     control::{KeyboardControl, MacroquadInputWrapper},
     physics::PointPhysics,
     resources::QueuedResources,
 };
 use macroquad::prelude::*;
+use serde::{Deserialize, Serialize};
 
 mod entities;
 pub mod events;
+mod interactions;
+mod rollback;
+mod save;
 mod types;
 use events::*;
 
 // reexports
+pub use asterism::ai::{AiControl, BehaviorTree, FollowTarget, Policy, UtilityPolicy};
 pub use asterism::collision::{AabbColData, AabbCollision, CollisionReaction};
-pub use asterism::control::{Action, ControlEventType, ControlReaction, Values};
+pub use asterism::control::{Action, ControlEventType, ControlReaction, InputFrame, Values};
 pub use asterism::physics::{PhysicsEvent, PhysicsReaction, PointPhysData};
-pub use asterism::resources::{ResourceEventType, ResourceReaction, Transaction};
+pub use asterism::resources::{ResourceEventType, ResourceReaction, ThresholdKind, Transaction};
+pub use asterism::rollback::{Resimulate, Rollback, RollbackError, Snapshot};
+pub use asterism::scheduler::{ScheduleError, Scheduler, Stage, StageKind};
 pub use asterism::tables::*;
-pub use asterism::{Logic, OutputTable};
+pub use asterism::{DynLogic, Logic, OutputTable};
 // pub use events::PaddlesUserEvents;
+pub use interactions::*;
+pub use rollback::GameSnapshot;
 pub use types::*;
 
+#[derive(Serialize, Deserialize)]
 pub struct Logics {
     pub collision: AabbCollision<CollisionEnt>,
     pub physics: PointPhysics,
     pub resources: QueuedResources<RsrcPool, u16>,
     pub control: KeyboardControl<ActionID, MacroquadInputWrapper>,
+    /// ball physics bodies bound to their collision bodies
+    pub bindings: Bindings,
 }
 
 impl Logics {
@@ -37,11 +50,31 @@ impl Logics {
             physics: PointPhysics::new(),
             resources: QueuedResources::new(),
             control: KeyboardControl::new(),
+            bindings: Bindings::new(Anchor::TopLeft),
         }
     }
+
+    /// every logic by name, for going through them without knowing their types
+    pub fn dyn_logics(&self) -> Vec<(&'static str, &dyn DynLogic)> {
+        vec![
+            ("control", &self.control),
+            ("physics", &self.physics),
+            ("collision", &self.collision),
+            ("resources", &self.resources),
+        ]
+    }
+
+    pub fn dyn_logics_mut(&mut self) -> Vec<(&'static str, &mut dyn DynLogic)> {
+        vec![
+            ("control", &mut self.control),
+            ("physics", &mut self.physics),
+            ("collision", &mut self.collision),
+            ("resources", &mut self.resources),
+        ]
+    }
 }
 
-#[derive(PartialEq, Eq)]
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum EntID {
     Wall(WallID),
     Ball(BallID),
@@ -49,6 +82,7 @@ pub enum EntID {
     Score(ScoreID),
 }
 
+#[derive(Clone)]
 pub enum Ent {
     Wall(Wall),
     Ball(Ball),
@@ -56,9 +90,12 @@ pub enum Ent {
     Score(Score),
 }
 
-#[derive(Default)]
+/// Entities queued to be added or removed aren't saved, since the queues are emptied at the end of every frame.
+#[derive(Default, Clone, Serialize, Deserialize)]
 pub struct State {
+    #[serde(skip)]
     remove_queue: Vec<EntID>,
+    #[serde(skip)]
     add_queue: Vec<Ent>,
     paddles: Vec<PaddleID>,
     walls: Vec<WallID>,
@@ -127,6 +164,14 @@ pub struct Game {
     pub logics: Logics,
     pub events: Events,
     pub tables: ConditionTables<QueryType>,
+    pub queries: Queries,
+    pub interactions: PaddlesInteractions,
+    /// computer players, which play sets of control mappings based on last frame's tables
+    pub ai: AiControl<ActionID, ConditionTables<QueryType>>,
+    /// the stages of a frame, see [Game::default_schedule]
+    pub schedule: Scheduler<Game>,
+    /// recorded input for the control logic to use this frame instead of the keyboard, see [Game::step]
+    replay: Option<InputFrame>,
 }
 
 impl Game {
@@ -134,36 +179,92 @@ impl Game {
         let mut tables = ConditionTables::new();
 
         // collision
-        tables.add_query::<ColEvent>(QueryType::ColEvent, None);
-        tables.add_query::<ColIdent>(QueryType::ColIdent, None);
+        let col_event = tables.add_single(QueryType::ColEvent);
+        let col_ident = tables.add_single(QueryType::ColIdent);
 
         // phys
-        tables.add_query::<PhysIdent>(QueryType::PhysIdent, None);
-        tables.add_query::<PhysEvent>(QueryType::PhysEvent, None);
+        let phys_ident = tables.add_single(QueryType::PhysIdent);
+        let phys_event = tables.add_single(QueryType::PhysEvent);
 
         // rsrc
-        tables.add_query::<RsrcEvent>(QueryType::RsrcEvent, None);
-        tables.add_query::<RsrcIdent>(QueryType::RsrcIdent, None);
+        let rsrc_event = tables.add_single(QueryType::RsrcEvent);
+        let rsrc_ident = tables.add_single(QueryType::RsrcIdent);
 
         // ctrl
-        tables.add_query::<CtrlEvent>(QueryType::CtrlEvent, None);
-        tables.add_query::<CtrlIdent>(QueryType::CtrlIdent, None);
-
-        // ball collision idents
-        // ball physics idents are just physics idents
-        tables.add_query::<ColIdent>(
-            QueryType::BallCol,
-            Some(Compose::Filter(QueryType::ColIdent)),
-        );
+        let ctrl_event = tables.add_single(QueryType::CtrlEvent);
+        let ctrl_ident = tables.add_single(QueryType::CtrlIdent);
 
         Self {
             state: State::default(),
             logics: Logics::new(),
             events: Events::new(),
             tables,
+            queries: Queries {
+                ctrl_event,
+                ctrl_ident,
+                col_event,
+                col_ident,
+                phys_event,
+                phys_ident,
+                rsrc_event,
+                rsrc_ident,
+            },
+            interactions: InteractionTable::new(),
+            ai: AiControl::new(),
+            schedule: Self::default_schedule(),
+            replay: None,
         }
     }
 
+    /// control, physics, collision, and resources, with positions copied between physics and collision, followed by adding and removing entities. Interactions run after control, collision, and resources, and can change control, physics, and resources.
+    pub fn default_schedule() -> Scheduler<Game> {
+        let mut schedule = Scheduler::new();
+        schedule
+            .add_logic("control", control)
+            .writes(&["control", "physics", "resources"]);
+        schedule
+            .add_logic("physics", physics)
+            .reads(&["control"])
+            .writes(&["physics"]);
+        schedule.add_synthesis(
+            "physics to collision",
+            &["physics"],
+            &["collision"],
+            physics_to_collision,
+        );
+        schedule
+            .add_logic("collision", collision)
+            .reads(&["collision"])
+            .writes(&["collision"]);
+        schedule.add_synthesis(
+            "collision to physics",
+            &["collision"],
+            &["physics"],
+            collision_to_physics,
+        );
+        schedule
+            .add_logic("collision reactions", collision_reactions)
+            .reads(&["collision"])
+            .writes(&["control", "physics", "resources"]);
+        schedule
+            .add_logic("resources", resources)
+            .writes(&["control", "physics", "resources"]);
+        schedule.add_logic("entities", update_entities).writes(&[
+            "collision",
+            "physics",
+            "resources",
+        ]);
+        debug_assert_eq!(schedule.check(), Ok(()));
+        schedule
+    }
+
+    /// runs one frame of the game
+    pub fn update(&mut self) {
+        let mut schedule = std::mem::take(&mut self.schedule);
+        schedule.run(self);
+        self.schedule = schedule;
+    }
+
     pub fn add_query(&mut self) -> UserQueryID {
         let id = UserQueryID::new(self.events.queries_max_id);
         self.events.queries_max_id += 1;
@@ -230,136 +331,188 @@ macro_rules! match_ent_id {
 }
 
 pub async fn run(mut game: Game) {
+    let mut quicksave = None;
     loop {
         if is_key_down(KeyCode::Escape) {
             break;
         }
-        draw(&game);
-
-        control(&mut game);
-        physics(&mut game);
-        collision(&mut game);
-        resources(&mut game);
-
-        // remove
-        game.state.remove_queue.sort_by(|a, b| {
-            let a = match_ent_id!(a, only ent: { ent.idx() } );
-            let b = match_ent_id!(b, only ent: { ent.idx() });
-            a.cmp(&b)
-        });
-        let remove_queue = std::mem::take(&mut game.state.remove_queue);
-        for ent in remove_queue {
-            match_ent_id!(
-                ent,
-                wall: { game.remove_wall(wall); },
-                ball: { game.remove_ball(ball); },
-                paddle: { game.remove_paddle(paddle); },
-                score: { game.remove_score(score); }
-            );
+        // print what's in the tables, for figuring out why a rule isn't firing
+        if is_key_pressed(KeyCode::F1) {
+            println!("{}", game.tables.dump());
+            for (name, logic) in game.logics.dyn_logics() {
+                println!("{} events: {:?}", name, logic.debug_events());
+            }
         }
-
-        // add
-        let add_queue = std::mem::take(&mut game.state.add_queue);
-        for ent in add_queue {
-            match_ent!(
-                ent,
-                wall: { game.add_wall(wall); },
-                ball: { game.add_ball(ball); },
-                paddle: { game.add_paddle(paddle); },
-                score: { game.add_score(score); }
-            );
+        if is_key_pressed(KeyCode::F5) {
+            quicksave = Some(game.save());
         }
-
+        if is_key_pressed(KeyCode::F9) {
+            if let Some(save) = &quicksave {
+                game.load(save).expect("error loading quicksave");
+            }
+        }
+        draw(&game);
+        game.update();
         next_frame().await;
     }
 }
 
-fn control(game: &mut Game) {
-    game.logics.control.update(&());
-
-    game.tables
-        .update_single::<CtrlEvent>(QueryType::CtrlEvent, game.logics.control.get_table())
-        .unwrap();
-    game.tables
-        .update_single::<CtrlIdent>(QueryType::CtrlIdent, game.logics.control.get_table())
-        .unwrap();
+fn update_entities(game: &mut Game) {
+    // remove
+    game.state.remove_queue.sort_by(|a, b| {
+        let a = match_ent_id!(a, only ent: { ent.idx() } );
+        let b = match_ent_id!(b, only ent: { ent.idx() });
+        a.cmp(&b)
+    });
+    let remove_queue = std::mem::take(&mut game.state.remove_queue);
+    for ent in remove_queue {
+        match_ent_id!(
+            ent,
+            wall: { game.remove_wall(wall); },
+            ball: { game.remove_ball(ball); },
+            paddle: { game.remove_paddle(paddle); },
+            score: { game.remove_score(score); }
+        );
+    }
 
-    if let Some(control) = game.events.control.clone() {
-        control(game);
+    // add
+    let add_queue = std::mem::take(&mut game.state.add_queue);
+    for ent in add_queue {
+        match_ent!(
+            ent,
+            wall: { game.add_wall(wall); },
+            ball: { game.add_ball(ball); },
+            paddle: { game.add_paddle(paddle); },
+            score: { game.add_score(score); }
+        );
     }
 }
 
-fn physics(game: &mut Game) {
-    game.logics.physics.update();
-
-    game.tables
-        .update_single::<PhysEvent>(QueryType::PhysEvent, game.logics.physics.get_table())
-        .unwrap();
+/// updates a logic, then writes its events and idents to the tables. Returns the events.
+fn update_logic<L>(
+    logic: &mut L,
+    context: &L::Context,
+    tables: &mut ConditionTables<QueryType>,
+    events: Query<L::Event>,
+    idents: Query<(L::Ident, L::IdentData)>,
+) -> Vec<L::Event>
+where
+    L: Logic,
+    L::Event: 'static,
+    L::Ident: 'static,
+    L::IdentData: 'static,
+{
+    logic.update(context);
+    write_logic(logic, tables, events, idents)
+}
 
-    let ans = game
-        .tables
-        .update_single::<PhysIdent>(QueryType::PhysIdent, game.logics.physics.get_table())
-        .unwrap();
+/// writes a logic's events and idents to the tables. Returns the events.
+fn write_logic<L>(
+    logic: &L,
+    tables: &mut ConditionTables<QueryType>,
+    events: Query<L::Event>,
+    idents: Query<(L::Ident, L::IdentData)>,
+) -> Vec<L::Event>
+where
+    L: Logic,
+    L::Event: 'static,
+    L::Ident: 'static,
+    L::IdentData: 'static,
+{
+    tables.set(idents, logic.idents());
+    tables.set(events, logic.events()).to_vec()
+}
 
-    // update physics positions to collision
-    for (idx, data) in ans.iter() {
-        let idx = game.state.get_col_idx(*idx, CollisionEnt::Ball);
+fn control(game: &mut Game) {
+    match game.replay.take() {
+        Some(input) => game.logics.control.replay(&input),
+        None => {
+            game.logics.control.update(&());
+            game.ai.update(&mut game.logics.control, &game.tables);
+        }
+    }
+    let events = write_logic(
+        &game.logics.control,
+        &mut game.tables,
+        game.queries.ctrl_event,
+        game.queries.ctrl_ident,
+    );
 
-        game.logics
-            .collision
-            .handle_predicate(&CollisionReaction::SetPos(idx, data.pos));
+    if let Some(control) = game.events.control.clone() {
+        control(game);
     }
+    game.interact(events.into_iter().map(PaddlesEvent::Control).collect());
+}
+
+fn physics(game: &mut Game) {
+    update_logic(
+        &mut game.logics.physics,
+        &(),
+        &mut game.tables,
+        game.queries.phys_event,
+        game.queries.phys_ident,
+    );
 
     if let Some(physics) = game.events.physics.clone() {
         physics(game);
     }
 }
 
+/// copies ball positions from physics to collision
+fn physics_to_collision(game: &mut Game) {
+    let logics = &mut game.logics;
+    logics
+        .bindings
+        .physics_to_collision(&logics.physics, &mut logics.collision);
+}
+
 fn collision(game: &mut Game) {
-    game.logics.collision.update();
-
-    game.tables
-        .update_single::<ColEvent>(QueryType::ColEvent, game.logics.collision.get_table())
-        .unwrap();
-    game.tables
-        .update_single::<ColIdent>(QueryType::ColIdent, game.logics.collision.get_table())
-        .unwrap();
-
-    // update collision positions to physics
-    let paddles_len = game.state.paddles.len();
-    let walls_len = game.state.walls.len();
-    let ans = game
-        .tables
-        .update_filter(
-            QueryType::BallCol,
-            Box::new(|(idx, _): &ColIdent| *idx > paddles_len + walls_len),
-        )
-        .unwrap();
-    for (idx, data) in ans.iter() {
-        let idx = idx - paddles_len - walls_len;
-        game.logics
-            .physics
-            .handle_predicate(&PhysicsReaction::SetPos(idx, data.center - data.half_size));
-    }
+    update_logic(
+        &mut game.logics.collision,
+        &(),
+        &mut game.tables,
+        game.queries.col_event,
+        game.queries.col_ident,
+    );
+}
 
+/// runs the collision rules. separate from [collision] so balls are moved out of walls before rules move them somewhere else
+fn collision_reactions(game: &mut Game) {
     if let Some(collision) = game.events.collision.clone() {
         collision(game);
     }
+
+    let state = &game.state;
+    let events = game
+        .tables
+        .get(game.queries.col_event)
+        .iter()
+        .map(|(i, j)| PaddlesEvent::Collision(state.get_id(*i), state.get_id(*j)))
+        .collect();
+    game.interact(events);
 }
 
-fn resources(game: &mut Game) {
-    game.logics.resources.update();
+/// copies ball positions from collision back to physics, after they've been moved out of whatever they hit
+fn collision_to_physics(game: &mut Game) {
+    let logics = &mut game.logics;
+    logics
+        .bindings
+        .collision_to_physics(&logics.collision, &mut logics.physics);
+}
 
-    game.tables
-        .update_single::<RsrcEvent>(QueryType::RsrcEvent, game.logics.resources.get_table())
-        .unwrap();
-    game.tables
-        .update_single::<RsrcIdent>(QueryType::RsrcIdent, game.logics.resources.get_table())
-        .unwrap();
+fn resources(game: &mut Game) {
+    let events = update_logic(
+        &mut game.logics.resources,
+        &(),
+        &mut game.tables,
+        game.queries.rsrc_event,
+        game.queries.rsrc_ident,
+    );
 
     if let Some(resources) = game.events.resources.clone() {
         resources(game);
     }
+    game.interact(events.into_iter().map(PaddlesEvent::Resource).collect());
 }
 
 pub fn draw(game: &Game) {
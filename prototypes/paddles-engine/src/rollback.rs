@@ -0,0 +1,60 @@
+//! rolling games back and resimulating them
+use asterism::bindings::Bindings;
+use asterism::control::{KeyboardControl, MacroquadInputWrapper};
+use asterism::physics::PointPhysics;
+use asterism::resources::QueuedResources;
+use asterism::rollback::{Resimulate, Snapshot};
+
+use crate::{AabbCollision, ActionID, CollisionEnt, Game, InputFrame, RsrcPool, State};
+
+type ControlSnapshot = <KeyboardControl<ActionID, MacroquadInputWrapper> as Snapshot>::Data;
+
+/// Everything in a game that changes from frame to frame: entities and logics. Rules are closures, so anything they capture isn't included.
+pub struct GameSnapshot {
+    state: State,
+    collision: AabbCollision<CollisionEnt>,
+    physics: PointPhysics,
+    resources: QueuedResources<RsrcPool, u16>,
+    control: ControlSnapshot,
+    bindings: Bindings,
+}
+
+impl Snapshot for Game {
+    type Data = GameSnapshot;
+
+    fn snapshot(&self) -> GameSnapshot {
+        GameSnapshot {
+            state: self.state.clone(),
+            collision: self.logics.collision.snapshot(),
+            physics: self.logics.physics.snapshot(),
+            resources: self.logics.resources.snapshot(),
+            control: self.logics.control.snapshot(),
+            bindings: self.logics.bindings.snapshot(),
+        }
+    }
+
+    fn restore(&mut self, snapshot: &GameSnapshot) {
+        self.state.clone_from(&snapshot.state);
+        self.logics.collision.restore(&snapshot.collision);
+        self.logics.physics.restore(&snapshot.physics);
+        self.logics.resources.restore(&snapshot.resources);
+        self.logics.control.restore(&snapshot.control);
+        self.logics.bindings.restore(&snapshot.bindings);
+    }
+}
+
+impl Resimulate for Game {
+    type Input = InputFrame;
+
+    fn step(&mut self, input: &InputFrame) {
+        Game::step(self, input);
+    }
+}
+
+impl Game {
+    /// Runs one frame with recorded input instead of the keyboard. Recording the control logic's input every frame and stepping through it again plays the game back exactly.
+    pub fn step(&mut self, input: &InputFrame) {
+        self.replay = Some(input.clone());
+        self.update();
+    }
+}
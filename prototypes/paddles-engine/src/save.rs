@@ -0,0 +1,40 @@
+//! saving and loading games
+use serde::{Deserialize, Serialize};
+
+use crate::{Game, Logics, PaddlesInteractions, State};
+
+/// Everything in a game that can be saved: entities, logics, and interactions. Rules added in code are closures, so they aren't saved, and the condition tables are filled in again on the next frame.
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    state: &'a State,
+    logics: &'a Logics,
+    interactions: &'a PaddlesInteractions,
+}
+
+#[derive(Deserialize)]
+struct Save {
+    state: State,
+    logics: Logics,
+    interactions: PaddlesInteractions,
+}
+
+impl Game {
+    /// writes the game's entities, logics, and interactions to JSON
+    pub fn save(&self) -> String {
+        serde_json::to_string(&SaveRef {
+            state: &self.state,
+            logics: &self.logics,
+            interactions: &self.interactions,
+        })
+        .expect("error saving game")
+    }
+
+    /// Replaces the game's entities, logics, and interactions with ones saved by [Game::save]. Rules are kept.
+    pub fn load(&mut self, json: &str) -> Result<(), serde_json::Error> {
+        let save: Save = serde_json::from_str(json)?;
+        self.state = save.state;
+        self.logics = save.logics;
+        self.interactions = save.interactions;
+        Ok(())
+    }
+}
@@ -1,10 +1,12 @@
 use macroquad::{input::KeyCode, math::Vec2};
+use serde::{Deserialize, Serialize};
 
 /// generates identifier structs (i got tired of typing all of them out). example: `id_impl_new!([derive(PartialOrd, Ord)] ScoreID)` expands out to
 ///
 /// ```
+/// # use serde::{Deserialize, Serialize};
 /// #[derive(PartialOrd, Ord)]
-/// #[derive(Clone, Copy, PartialEq, Eq)]
+/// #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 /// pub struct ScoreID(usize);
 /// impl ScoreID {
 ///     pub fn new(idx: usize) -> Self {
@@ -19,7 +21,7 @@ macro_rules! id_impl_new {
     ($([$($derive:meta)*] $id_type:ident),*) => {
         $(
             $(#[$derive])*
-            #[derive(Clone, Copy, PartialEq, Eq)]
+            #[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
             pub struct $id_type(usize);
 
             impl $id_type {
@@ -35,7 +37,7 @@ macro_rules! id_impl_new {
     };
 }
 
-id_impl_new!([] PaddleID, [] WallID, [] BallID, [derive(PartialOrd, Ord, Debug)] ScoreID, [derive(PartialOrd, Ord, Debug)] ActionID, [derive(Hash, Debug)] UserQueryID);
+id_impl_new!([derive(Debug)] PaddleID, [derive(Debug)] WallID, [derive(Debug)] BallID, [derive(PartialOrd, Ord, Debug)] ScoreID, [derive(PartialOrd, Ord, Debug)] ActionID, [derive(Hash, Debug)] UserQueryID);
 
 #[derive(Hash, Eq, PartialEq, Debug, Clone, Copy)]
 #[allow(dead_code)]
@@ -48,23 +50,36 @@ pub enum QueryType {
     PhysIdent,
     RsrcEvent,
     RsrcIdent,
-    BallCol,
     User(UserQueryID),
 }
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+pub type Query<T, Src = ()> = asterism::tables::QueryHandle<QueryType, T, Src>;
+
+/// handles to the rows of the condition table that every game has, for writing rules on top of
+pub struct Queries {
+    pub ctrl_event: Query<CtrlEvent>,
+    pub ctrl_ident: Query<CtrlIdent>,
+    pub col_event: Query<ColEvent>,
+    pub col_ident: Query<ColIdent>,
+    pub phys_event: Query<PhysEvent>,
+    pub phys_ident: Query<PhysIdent>,
+    pub rsrc_event: Query<RsrcEvent>,
+    pub rsrc_ident: Query<RsrcIdent>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum CollisionEnt {
     Paddle,
     Wall,
     Ball,
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum RsrcPool {
     Score(ScoreID),
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Paddle {
     pub pos: Vec2,
     pub size: Vec2,
@@ -91,7 +106,7 @@ impl Paddle {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Ball {
     pub pos: Vec2,
     pub size: Vec2,
@@ -116,7 +131,7 @@ impl Ball {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Wall {
     pub pos: Vec2,
     pub size: Vec2,
@@ -136,7 +151,7 @@ impl Wall {
     }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct Score {
     pub value: u16,
 }
@@ -0,0 +1,130 @@
+//! Breakout has to play out exactly the same when it's rolled back and resimulated, or rollback netplay would drift apart.
+use paddles_engine::*;
+
+#[allow(dead_code)]
+#[path = "../src/bin/breakout.rs"]
+mod breakout;
+
+const FRAMES: u64 = 600;
+
+/// left, right, and serve held on each frame: serve after a few frames, then sweep the paddle back and forth
+fn held(frame: u64) -> [bool; 3] {
+    let serve = (5..8).contains(&frame);
+    let right = (frame / 40) % 2 == 1;
+    let left = !right && frame > 10;
+    [left, right, serve]
+}
+
+/// the control logic's values for the keys held this frame, the way reading the keyboard would set them
+fn input(frame: u64, last: &InputFrame) -> InputFrame {
+    let values = held(frame)
+        .iter()
+        .zip(last[0].iter())
+        .map(|(held, last)| {
+            let value = if *held { 1.0 } else { 0.0 };
+            Values {
+                changed_by: value - last.value,
+                value,
+            }
+        })
+        .collect();
+    vec![values]
+}
+
+fn new_game() -> Game {
+    let mut game = Game::new();
+    breakout::init(&mut game);
+    game
+}
+
+/// plays the game from the start, returning its save after each frame
+fn play(game: &mut Game, rollback: &mut Rollback<Game>) -> Vec<String> {
+    let mut last = game.logics.control.record();
+    (0..FRAMES)
+        .map(|frame| {
+            let input = input(frame, &last);
+            rollback.advance(game, input.clone());
+            last = input;
+            game.save()
+        })
+        .collect()
+}
+
+#[test]
+fn replays_identically() {
+    let mut first = new_game();
+    let first_saves = play(&mut first, &mut Rollback::new(60));
+
+    let mut second = new_game();
+    let second_saves = play(&mut second, &mut Rollback::new(60));
+
+    assert_eq!(first_saves, second_saves);
+}
+
+#[test]
+fn resimulates_identically() {
+    let mut game = new_game();
+    let mut rollback = Rollback::new(60);
+    let saves = play(&mut game, &mut rollback);
+    let present = saves.last().unwrap().clone();
+
+    for frames_back in [1, 10, 30, 60] {
+        rollback
+            .resimulate(&mut game, FRAMES - frames_back)
+            .unwrap();
+        assert_eq!(game.save(), present, "{} frames back", frames_back);
+    }
+
+    assert_eq!(
+        rollback.resimulate(&mut game, FRAMES - 61),
+        Err(RollbackError::FrameNotBuffered(FRAMES - 61))
+    );
+}
+
+#[test]
+fn resimulates_changed_input() {
+    let mut game = new_game();
+    let mut rollback = Rollback::new(60);
+    let saves = play(&mut game, &mut rollback);
+    let present = saves.last().unwrap().clone();
+
+    // hold nothing for the last 30 frames instead
+    let frame = FRAMES - 30;
+    let recorded: Vec<InputFrame> = (frame..FRAMES)
+        .map(|frame| rollback.input(frame).unwrap().clone())
+        .collect();
+    let mut last = rollback.input(frame - 1).unwrap().clone();
+    for frame in frame..FRAMES {
+        let input = vec![last[0]
+            .iter()
+            .map(|last| Values {
+                changed_by: -last.value,
+                value: 0.0,
+            })
+            .collect()];
+        rollback.set_input(frame, input.clone()).unwrap();
+        last = input;
+    }
+    rollback.resimulate(&mut game, frame).unwrap();
+    assert_ne!(game.save(), present);
+
+    // and putting the recorded input back gets back to the present
+    for (frame, input) in (frame..FRAMES).zip(recorded) {
+        rollback.set_input(frame, input).unwrap();
+    }
+    rollback.resimulate(&mut game, frame).unwrap();
+    assert_eq!(game.save(), present);
+}
+
+#[test]
+fn rewinds_to_earlier_frames() {
+    let mut game = new_game();
+    let mut rollback = Rollback::new(60);
+    let saves = play(&mut game, &mut rollback);
+
+    let frame = FRAMES - 20;
+    rollback.rewind(&mut game, frame).unwrap();
+    assert_eq!(rollback.frame(), frame);
+    // the save after the frame before is the state at the start of this one
+    assert_eq!(game.save(), saves[frame as usize - 1]);
+}
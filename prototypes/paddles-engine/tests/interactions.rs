@@ -0,0 +1,37 @@
+//! The scoring rules paddles loads have to parse, and a ball hitting a side wall has to score for and hand the serve to the other player.
+use paddles_engine::*;
+
+fn rules() -> PaddlesInteractions {
+    InteractionTable::from_json(include_str!("../interactions/paddles.json")).unwrap()
+}
+
+#[test]
+fn side_walls_score_for_the_other_player() {
+    let rules = rules();
+    let hit_left =
+        PaddlesEvent::Collision(EntID::Ball(BallID::new(0)), EntID::Wall(WallID::new(0)));
+    let hit_top = PaddlesEvent::Collision(EntID::Ball(BallID::new(0)), EntID::Wall(WallID::new(2)));
+    let reactions = rules.dispatch(vec![hit_top, hit_left]);
+
+    assert_eq!(reactions.len(), 2);
+    assert!(reactions.iter().all(|(event, _)| *event == hit_left));
+    assert!(matches!(
+        reactions[0].1,
+        PaddlesReaction::SetKeyValid { set: 1, action } if *action == ActionID::new(2)
+    ));
+    assert!(matches!(
+        reactions[1].1,
+        PaddlesReaction::Resource(RsrcPool::Score(score), Transaction::Change(1))
+            if *score == ScoreID::new(1)
+    ));
+}
+
+#[test]
+fn json_round_trip() {
+    let rules = rules();
+    let json = rules.to_json();
+    assert_eq!(
+        PaddlesInteractions::from_json(&json).unwrap().to_json(),
+        json
+    );
+}
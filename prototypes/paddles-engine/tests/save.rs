@@ -0,0 +1,60 @@
+//! Loading a save has to give back the same game, so saving it again gives the same save and it keeps playing the same way.
+use paddles_engine::*;
+
+#[allow(dead_code)]
+#[path = "../src/bin/breakout.rs"]
+mod breakout;
+
+/// serve on frame 5 and then hold right, the way reading the keyboard would set the control logic's values
+fn input(frame: u64) -> InputFrame {
+    let held = [false, frame > 5, frame == 5];
+    let was_held = [false, frame > 6, frame == 6];
+    vec![held
+        .iter()
+        .zip(was_held.iter())
+        .map(|(held, was_held)| {
+            let value = if *held { 1.0 } else { 0.0 };
+            let last = if *was_held { 1.0 } else { 0.0 };
+            Values {
+                changed_by: value - last,
+                value,
+            }
+        })
+        .collect()]
+}
+
+fn new_game() -> Game {
+    let mut game = Game::new();
+    breakout::init(&mut game);
+    game
+}
+
+#[test]
+fn saves_round_trip() {
+    let mut game = new_game();
+    for frame in 0..120 {
+        game.step(&input(frame));
+    }
+    let save = game.save();
+    assert_ne!(save, new_game().save());
+
+    // rules are set up in code, so saves are loaded into a game that's already been set up
+    let mut loaded = new_game();
+    loaded.load(&save).unwrap();
+    assert_eq!(loaded.save(), save);
+
+    for frame in 120..240 {
+        game.step(&input(frame));
+        loaded.step(&input(frame));
+    }
+    assert_eq!(loaded.save(), game.save());
+}
+
+#[test]
+fn rejects_malformed_saves() {
+    let mut game = new_game();
+    let save = game.save();
+    assert!(game.load("{}").is_err());
+    assert!(game.load(&save[..save.len() / 2]).is_err());
+    assert_eq!(game.save(), save);
+}